@@ -0,0 +1,104 @@
+//! Compares the old linear-scan vertex deduplication in `Shape::from_obj` with `VertexWelder`
+//! Run with `cargo bench --bench vertex_welding`
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use graphics_lib::three_d::shape::importing::VertexWelder;
+
+type Key = (usize, Option<usize>, Option<usize>);
+
+/// What the importer used to do: scan every vertex emitted so far for a match
+fn linear_weld(corners: &[Key]) -> (usize, Vec<u32>) {
+    let mut keys: Vec<Key> = Vec::new();
+    let mut indices = Vec::with_capacity(corners.len());
+    for corner in corners {
+        let idx = match keys.iter().position(|k| k == corner) {
+            Some(idx) => idx,
+            None => {
+                keys.push(*corner);
+                keys.len() - 1
+            }
+        };
+        indices.push(idx as u32);
+    }
+    (keys.len(), indices)
+}
+
+fn hash_weld(corners: &[Key]) -> (usize, Vec<u32>) {
+    let mut welder = VertexWelder::new();
+    let indices = corners.iter().map(|c| welder.weld(*c).0).collect();
+    (welder.len(), indices)
+}
+
+/// The face corners of a `size` by `size` grid of quads, split into triangles
+fn grid(size: usize) -> Vec<Key> {
+    let idx = |x: usize, y: usize| y * (size + 1) + x;
+    let mut corners = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            for (cx, cy) in [(x, y), (x + 1, y), (x + 1, y + 1), (x, y), (x + 1, y + 1), (x, y + 1)] {
+                let i = idx(cx, cy);
+                corners.push((i, Some(i), Some(0)));
+            }
+        }
+    }
+    corners
+}
+
+/// Pulls the (triangulated) face corners out of an OBJ file
+fn obj_corners(path: &str) -> Vec<Key> {
+    let text = fs::read_to_string(path).unwrap();
+    let mut corners = Vec::new();
+    for line in text.lines() {
+        let mut tokens = line.split_ascii_whitespace();
+        if tokens.next() != Some("f") {
+            continue;
+        }
+        let face: Vec<Key> = tokens.map(|tok| {
+            let mut parts = tok.split('/');
+            let mut next = || parts.next().filter(|s| !s.is_empty()).map(|s| s.parse::<usize>().unwrap());
+            (next().unwrap(), next(), next())
+        }).collect();
+        for i in 2..face.len() {
+            corners.extend_from_slice(&[face[0], face[i - 1], face[i]]);
+        }
+    }
+    corners
+}
+
+fn time<F: FnMut() -> (usize, Vec<u32>)>(mut f: F) -> (Duration, usize) {
+    // Take the best of a few runs to cut down on noise
+    let mut best = Duration::MAX;
+    let mut vertices = 0;
+    for _ in 0..3 {
+        let start = Instant::now();
+        let (n, indices) = f();
+        best = best.min(start.elapsed());
+        vertices = n;
+        std::hint::black_box(indices);
+    }
+    (best, vertices)
+}
+
+fn compare(name: &str, corners: &[Key], run_linear: bool) {
+    let (hash_time, hash_vertices) = time(|| hash_weld(corners));
+    print!("{name:<28} {:>9} corners {:>8} vertices   hash: {:>10.3?}", corners.len(), hash_vertices, hash_time);
+
+    if run_linear {
+        let (linear_time, linear_vertices) = time(|| linear_weld(corners));
+        assert_eq!(linear_vertices, hash_vertices);
+        println!("   linear: {:>10.3?}   ({:.1}x)", linear_time, linear_time.as_secs_f64() / hash_time.as_secs_f64());
+    } else {
+        println!("   linear: skipped, too slow");
+    }
+}
+
+fn main() {
+    for path in ["media/monkey.obj", "media/torus.obj", "media/monkey_and_torus.obj"] {
+        compare(path, &obj_corners(path), true);
+    }
+    for size in [32, 64, 128, 1024] {
+        compare(&format!("{size}x{size} grid"), &grid(size), size <= 128);
+    }
+}
@@ -209,7 +209,7 @@ fn demo_3d() {
         ShaderType::BlinnPhong, 
         None, 
         Some(rotation_animation), 
-        false, 
+        &importing::ImportOptions { zero_ambient: true, ..Default::default() },
     ).unwrap();
 
     println!("Torus Material: {:?}", s.material);
@@ -11,6 +11,48 @@ impl Mat4 {
     pub fn new(m: Matrix) -> Mat4 {
         Mat4 { inner: m }
     }
+
+    /// Transforms a point the same way the shaders do, treating it as a row vector with w = 1
+    pub fn transform_point(&self, p: &[f32; 3]) -> [f32; 3] {
+        let m = &self.inner;
+        let mut out = [m[3][0], m[3][1], m[3][2]];
+        for (j, o) in out.iter_mut().enumerate() {
+            *o += p[0] * m[0][j] + p[1] * m[1][j] + p[2] * m[2][j];
+        }
+        out
+    }
+
+    /// Transforms a normal by the inverse transpose of the upper 3x3, so it stays perpendicular to its surface
+    /// The result is normalized
+    pub fn transform_normal(&self, n: &[f32; 3]) -> [f32; 3] {
+        let m = &self.inner;
+        // The cofactor matrix is the inverse transpose scaled by the determinant, which normalizing gets rid of
+        // We just have to keep the sign so mirrored transforms don't flip normals
+        let cofactor = |i: usize, j: usize| {
+            let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+            let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let sign = self.determinant3().signum();
+
+        let mut out = [0.0; 3];
+        for (j, o) in out.iter_mut().enumerate() {
+            *o = sign * (n[0] * cofactor(0, j) + n[1] * cofactor(1, j) + n[2] * cofactor(2, j));
+        }
+        let len = (out[0] * out[0] + out[1] * out[1] + out[2] * out[2]).sqrt();
+        if len > 0.0 {
+            out = [out[0] / len, out[1] / len, out[2] / len];
+        }
+        out
+    }
+
+    /// The determinant of the upper 3x3, which is negative if the transform mirrors things
+    pub fn determinant3(&self) -> f32 {
+        let m = &self.inner;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
 }
 
 impl Mul for Mat4 {
@@ -90,6 +132,17 @@ pub fn generate_rotate_z(angle: f32) -> Mat4 {
     ]}
 }
 
+/// Rotation by a unit quaternion given as `[x, y, z, w]`, the way glTF stores them
+pub fn generate_rotate_quaternion(q: &[f32; 4]) -> Mat4 {
+    let [x, y, z, w] = *q;
+    Mat4 { inner: [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w), 0.0], 
+        [2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w), 0.0], 
+        [2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+        [0.0, 0.0, 0.0, 1.0f32],
+    ]}
+}
+
 pub fn generate_translate(x_offset: Option<f32>, y_offset: Option<f32>, z_offset: Option<f32>) -> Mat4 {
     Mat4 { inner: [
         [1.0, 0.0, 0.0, 0.0], 
@@ -1,7 +1,9 @@
 pub mod animation;
 pub mod buffer;
+pub mod mesh;
+pub mod models;
 //pub mod scene;
 pub mod shaders;
 pub mod shape;
 pub mod VAO;
-//pub mod teapot;
\ No newline at end of file
+pub mod teapot;
@@ -143,3 +143,186 @@ impl Animation for Translation {
     }
 }
 
+
+/// How a track gets from one keyframe to the next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Holds each keyframe until the next one
+    Step,
+    /// Straight lines between keyframes, rotations are slerped
+    Linear,
+    /// Hermite splines, with tangents stored next to each keyframe
+    CubicSpline,
+}
+
+/// The values one property takes at points in time
+#[derive(Clone, Debug)]
+pub struct Track<const N: usize> {
+    pub interpolation: Interpolation,
+    /// In seconds, in increasing order
+    pub times: Vec<f32>,
+    /// One per time, or three per time for cubic splines: in tangent, value, out tangent
+    pub values: Vec<[f32; N]>,
+}
+
+impl<const N: usize> Track<N> {
+    /// The time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    fn value(&self, i: usize) -> [f32; N] {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.values[i * 3 + 1],
+            _ => self.values[i],
+        }
+    }
+
+    /// The keyframes on either side of `t`, and how far `t` is between them from 0 to 1
+    /// Before the first and after the last keyframe, both sides are the same keyframe
+    fn locate(&self, t: f32) -> (usize, usize, f32) {
+        let last = self.times.len() - 1;
+        if t <= self.times[0] {
+            return (0, 0, 0.0);
+        }
+        if t >= self.times[last] {
+            return (last, last, 0.0);
+        }
+        let next = self.times.partition_point(|&time| time <= t);
+        let prev = next - 1;
+        let dt = self.times[next] - self.times[prev];
+        (prev, next, if dt > 0.0 { (t - self.times[prev]) / dt } else { 0.0 })
+    }
+
+    /// The value at time `t`, holding the first and last keyframes outside of the track
+    pub fn sample(&self, t: f32) -> [f32; N] {
+        if self.times.is_empty() {
+            return [0.0; N];
+        }
+        let (prev, next, s) = self.locate(t);
+        let (a, b) = (self.value(prev), self.value(next));
+
+        let mut out = [0.0; N];
+        match self.interpolation {
+            Interpolation::Step => out = a,
+            Interpolation::Linear => for (i, o) in out.iter_mut().enumerate() {
+                *o = a[i] + (b[i] - a[i]) * s;
+            },
+            Interpolation::CubicSpline => {
+                // Tangents are stored per second, so they need scaling to the length of this segment
+                let dt = self.times[next] - self.times[prev];
+                let (out_tangent, in_tangent) = (self.values[prev * 3 + 2], self.values[next * 3]);
+                let (s2, s3) = (s * s, s * s * s);
+                for (i, o) in out.iter_mut().enumerate() {
+                    *o = (2.0 * s3 - 3.0 * s2 + 1.0) * a[i] 
+                        + (s3 - 2.0 * s2 + s) * dt * out_tangent[i] 
+                        + (-2.0 * s3 + 3.0 * s2) * b[i] 
+                        + (s3 - s2) * dt * in_tangent[i];
+                }
+            },
+        }
+        out
+    }
+}
+
+impl Track<4> {
+    /// Like `sample`, but treats the values as `[x, y, z, w]` quaternions and always gives back a unit one
+    pub fn sample_rotation(&self, t: f32) -> [f32; 4] {
+        if self.times.is_empty() {
+            return [0.0, 0.0, 0.0, 1.0];
+        }
+        let q = if self.interpolation == Interpolation::Linear {
+            let (prev, next, s) = self.locate(t);
+            slerp(self.value(prev), self.value(next), s)
+        } else {
+            self.sample(t)
+        };
+        let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+        if len > 0.0 { [q[0] / len, q[1] / len, q[2] / len, q[3] / len] } else { [0.0, 0.0, 0.0, 1.0] }
+    }
+}
+
+fn slerp(a: [f32; 4], mut b: [f32; 4], s: f32) -> [f32; 4] {
+    let mut dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    // q and -q are the same rotation, this makes sure we go the short way around
+    if dot < 0.0 {
+        b = [-b[0], -b[1], -b[2], -b[3]];
+        dot = -dot;
+    }
+    let (wa, wb) = if dot > 0.9995 {
+        // Close enough that a straight line works, and the angle would be too small to divide by
+        (1.0 - s, s)
+    } else {
+        let angle = dot.acos();
+        let sin = angle.sin();
+        (((1.0 - s) * angle).sin() / sin, (s * angle).sin() / sin)
+    };
+    [wa * a[0] + wb * b[0], wa * a[1] + wb * b[1], wa * a[2] + wb * b[2], wa * a[3] + wb * b[3]]
+}
+
+/// One node in a keyframed hierarchy: its rest pose, and whichever parts of it are animated
+#[derive(Clone, Debug)]
+pub struct KeyframedNode {
+    pub translation: [f32; 3],
+    /// `[x, y, z, w]`
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub translation_track: Option<Track<3>>,
+    pub rotation_track: Option<Track<4>>,
+    pub scale_track: Option<Track<3>>,
+}
+
+impl KeyframedNode {
+    /// A node that stays in its rest pose
+    pub fn new(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> KeyframedNode {
+        KeyframedNode { translation, rotation, scale, translation_track: None, rotation_track: None, scale_track: None }
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.translation_track.is_some() || self.rotation_track.is_some() || self.scale_track.is_some()
+    }
+
+    pub fn duration(&self) -> f32 {
+        let t = self.translation_track.as_ref().map_or(0.0, Track::duration);
+        let r = self.rotation_track.as_ref().map_or(0.0, Track::duration);
+        let s = self.scale_track.as_ref().map_or(0.0, Track::duration);
+        t.max(r).max(s)
+    }
+
+    /// The node's transform relative to its parent at time `t`
+    pub fn matrix(&self, t: f32) -> Mat4 {
+        let translation = self.translation_track.as_ref().map_or(self.translation, |track| track.sample(t));
+        let rotation = self.rotation_track.as_ref().map_or(self.rotation, |track| track.sample_rotation(t));
+        let scale = self.scale_track.as_ref().map_or(self.scale, |track| track.sample(t));
+
+        generate_scale(&scale) 
+            * generate_rotate_quaternion(&rotation) 
+            * generate_translate(Some(translation[0]), Some(translation[1]), Some(translation[2]))
+    }
+}
+
+/// Plays back keyframes, like the animations in glTF files
+/// Moving a node moves everything under it, so this holds the whole chain of nodes from the root down to the shape
+#[derive(Clone, Debug)]
+pub struct Keyframes {
+    /// Root first
+    pub nodes: Vec<KeyframedNode>,
+    /// Start over after the last keyframe, instead of holding it
+    pub looping: bool,
+}
+
+impl Keyframes {
+    pub fn duration(&self) -> f32 {
+        self.nodes.iter().map(KeyframedNode::duration).fold(0.0, f32::max)
+    }
+}
+
+impl Animation for Keyframes {
+    fn run(&mut self, t: f32, transform: &mut Transform) {
+        let duration = self.duration();
+        let t = if self.looping && duration > 0.0 { t % duration } else { t };
+
+        let matrix = self.nodes.iter().fold(IDENTITY, |world, node| node.matrix(t) * world);
+        *transform = Transform::from_matrix(matrix);
+    }
+}
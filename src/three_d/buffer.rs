@@ -6,6 +6,14 @@ pub type Vertex = [GLfloat; 3];
 
 pub type Normal = [GLfloat; 3];
 
+pub type Color = [GLfloat; 3];
+
+pub type TexCoord = [GLfloat; 2];
+
+/// The direction U goes along the surface, with the handedness of the tangent space in `w`
+/// The bitangent is `w * cross(normal, tangent)`
+pub type Tangent = [GLfloat; 4];
+
 
 pub struct VertexBuffer {
     id: GLuint,
@@ -71,6 +79,70 @@ impl Drop for NormalBuffer {
     }
 }
 
+pub struct ColorBuffer {
+    id: GLuint,
+}
+
+impl ColorBuffer {
+    pub fn new(data: &[Color], vao_lock: &VAOLock) -> ColorBuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * mem::size_of::<Color>()) as GLsizeiptr,
+                data.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+        }
+        ColorBuffer { id }
+    }
+    pub fn id(&self) -> &GLuint {
+        &self.id
+    }
+}
+
+impl Drop for ColorBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+pub struct TexCoordBuffer {
+    id: GLuint,
+}
+
+impl TexCoordBuffer {
+    pub fn new(data: &[TexCoord], vao_lock: &VAOLock) -> TexCoordBuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * mem::size_of::<TexCoord>()) as GLsizeiptr,
+                data.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+        }
+        TexCoordBuffer { id }
+    }
+    pub fn id(&self) -> &GLuint {
+        &self.id
+    }
+}
+
+impl Drop for TexCoordBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
 pub struct IndexBuffer {
     id: GLuint,
     pub num_indices: usize,
@@ -102,4 +174,36 @@ impl Drop for IndexBuffer {
             gl::DeleteBuffers(1, &self.id);
         }
     }
-}
\ No newline at end of file
+}
+
+pub struct TangentBuffer {
+    id: GLuint,
+}
+
+impl TangentBuffer {
+    pub fn new(data: &[Tangent], vao_lock: &VAOLock) -> TangentBuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * mem::size_of::<Tangent>()) as GLsizeiptr,
+                data.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+        }
+        TangentBuffer { id }
+    }
+    pub fn id(&self) -> &GLuint {
+        &self.id
+    }
+}
+
+impl Drop for TangentBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
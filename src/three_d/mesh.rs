@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use gl::types::*;
+
+use super::buffer::{Vertex, Normal, Color, TexCoord, Tangent};
+
+mod primitives;
+mod tangents;
+mod simplify;
+mod subdivide;
+pub use subdivide::SubdivisionScheme;
+mod validate;
+pub use validate::Validation;
+mod half_edge;
+pub use half_edge::{HalfEdge, HalfEdgeFace, HalfEdgeMesh, HalfEdgeVertex};
+mod optimize;
+pub use optimize::ACMR_CACHE_SIZE;
+mod surface;
+pub use surface::{BezierPatch, NurbsPatch, Resolution, Surface};
+
+/// The kind of primitive a range of the index buffer is drawn as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Triangles,
+    Lines,
+    Points,
+}
+
+impl Primitive {
+    pub fn gl_mode(&self) -> GLenum {
+        match self {
+            Primitive::Triangles => gl::TRIANGLES,
+            Primitive::Lines => gl::LINES,
+            Primitive::Points => gl::POINTS,
+        }
+    }
+}
+
+/// The unit normal of a triangle, facing the side its corners wind counter clockwise around
+/// Degenerate triangles get a zero normal
+pub fn face_normal(corners: &[Vertex; 3]) -> Normal {
+    let n = area_normal(corners);
+    normalize(n).unwrap_or(n)
+}
+
+/// A triangle's normal, as long as twice its area, so summing them weights bigger triangles more
+pub(crate) fn area_normal([p0, p1, p2]: &[Vertex; 3]) -> Normal {
+    cross(sub(*p1, *p0), sub(*p2, *p0))
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn length(v: [f32; 3]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// `v` scaled to be 1 long, or `None` if it has no length to scale
+pub(crate) fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let len = length(v);
+    (len > 0.0).then(|| v.map(|x| x / len))
+}
+
+/// A contiguous run of indices in a shape's index buffer, all drawn as the same primitive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawRange {
+    pub primitive: Primitive,
+    pub start: usize,
+    pub count: usize,
+    /// The group or object these elements were listed under, if the file named one
+    pub group: Option<String>,
+}
+
+/// An axis aligned box around some geometry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Bounds {
+    pub fn center(&self) -> [f32; 3] {
+        [(self.min[0] + self.max[0]) / 2.0, (self.min[1] + self.max[1]) / 2.0, (self.min[2] + self.max[2]) / 2.0]
+    }
+
+    pub fn size(&self) -> [f32; 3] {
+        [self.max[0] - self.min[0], self.max[1] - self.min[1], self.max[2] - self.min[2]]
+    }
+}
+
+/// A CPU side copy of the geometry in a shape's buffers
+/// The GPU can't give us the data back cheaply, so we hold on to it for things like exporting
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub positions: Vec<Vertex>,
+    pub normals: Vec<Normal>,
+    pub colors: Option<Vec<Color>>,
+    pub tex_coords: Option<Vec<TexCoord>>,
+    /// Only needed for normal mapping, see `compute_tangents`
+    pub tangents: Option<Vec<Tangent>>,
+    pub indices: Vec<u32>,
+    pub ranges: Vec<DrawRange>,
+}
+
+impl Mesh {
+    /// The indices in `range`
+    pub fn range_indices(&self, range: &DrawRange) -> &[u32] {
+        &self.indices[range.start..range.start + range.count]
+    }
+
+    /// The box around every position, or `None` if there aren't any
+    pub fn bounds(&self) -> Option<Bounds> {
+        let first = *self.positions.first()?;
+        Some(self.positions.iter().fold(Bounds { min: first, max: first }, |b, p| Bounds {
+            min: [b.min[0].min(p[0]), b.min[1].min(p[1]), b.min[2].min(p[2])],
+            max: [b.max[0].max(p[0]), b.max[1].max(p[1]), b.max[2].max(p[2])],
+        }))
+    }
+
+    /// Every triangle in the mesh, from all of its triangle ranges
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.ranges.iter()
+            .filter(|range| range.primitive == Primitive::Triangles)
+            .flat_map(|range| self.range_indices(range).chunks_exact(3))
+            .map(|tri| [tri[0], tri[1], tri[2]])
+    }
+
+    /// A new mesh with only the ranges `keep` picks, given each range's index, and the vertices they use
+    pub fn select_ranges(&self, mut keep: impl FnMut(usize) -> bool) -> Mesh {
+        let mut out = Mesh {
+            colors: self.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: self.tex_coords.as_ref().map(|_| Vec::new()),
+            tangents: self.tangents.as_ref().map(|_| Vec::new()),
+            ..Mesh::default()
+        };
+        let mut remap = HashMap::new();
+        for (_, range) in self.ranges.iter().enumerate().filter(|(i, _)| keep(*i)) {
+            let start = out.indices.len();
+            for &idx in self.range_indices(range) {
+                let new = *remap.entry(idx).or_insert_with(|| out.copy_vertex(self, idx));
+                out.indices.push(new);
+            }
+            out.ranges.push(DrawRange { start, ..range.clone() });
+        }
+        out
+    }
+
+    /// Replaces the normals with ones averaged from the triangles around each vertex
+    /// Bigger triangles count for more, and vertices that aren't in any triangle end up with a zero normal
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![[0.0; 3]; self.positions.len()];
+        for [a, b, c] in self.triangles() {
+            let [a, b, c] = [a as usize, b as usize, c as usize];
+            let n = area_normal(&[self.positions[a], self.positions[b], self.positions[c]]);
+            for v in [a, b, c] {
+                for (sum, x) in normals[v].iter_mut().zip(n) {
+                    *sum += x;
+                }
+            }
+        }
+
+        for normal in normals.iter_mut() {
+            if let Some(n) = normalize(*normal) {
+                *normal = n;
+            }
+        }
+        self.normals = normals;
+    }
+
+    /// Turns every triangle around, which swaps which side of it is the front
+    /// Normals are left alone, so flip those too if they were facing the right way
+    pub fn flip_winding(&mut self) {
+        for range in self.ranges.iter().filter(|range| range.primitive == Primitive::Triangles) {
+            for tri in self.indices[range.start..range.start + range.count].chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+    }
+
+    /// Flips texture coordinates upside down, for files that count V from the top of the image
+    pub fn flip_v(&mut self) {
+        for tex_coord in self.tex_coords.iter_mut().flatten() {
+            tex_coord[1] = 1.0 - tex_coord[1];
+        }
+        // V going the other way turns the bitangents around
+        for tangent in self.tangents.iter_mut().flatten() {
+            tangent[3] = -tangent[3];
+        }
+    }
+
+    /// Gives every triangle its own corners, each with the triangle's normal, so it's shaded flat
+    /// Lines and points keep sharing their vertices
+    pub fn compute_flat_normals(&mut self) {
+        let mut out = Mesh { colors: self.colors.as_ref().map(|_| Vec::new()), tex_coords: self.tex_coords.as_ref().map(|_| Vec::new()), ..Mesh::default() };
+        let mut shared = HashMap::new();
+        for range in &self.ranges {
+            let start = out.indices.len();
+            let indices = self.range_indices(range);
+            if range.primitive == Primitive::Triangles {
+                for tri in indices.chunks_exact(3) {
+                    let normal = face_normal(&[0, 1, 2].map(|i| self.positions[tri[i] as usize]));
+                    for &idx in tri {
+                        let new = out.copy_vertex(self, idx);
+                        out.normals[new as usize] = normal;
+                        out.indices.push(new);
+                    }
+                }
+            } else {
+                for &idx in indices {
+                    let new = *shared.entry(idx).or_insert_with(|| out.copy_vertex(self, idx));
+                    out.indices.push(new);
+                }
+            }
+            out.ranges.push(DrawRange { start, count: out.indices.len() - start, ..range.clone() });
+        }
+        *self = out;
+    }
+
+    /// Merges vertices closer together than `distance`, if their colors and texture coordinates are that close too
+    /// Merged vertices get the average of their normals, and triangles and lines that collapse are removed
+    pub fn merge_by_distance(&mut self, distance: f32) {
+        let distance = distance.max(f32::MIN_POSITIVE);
+        let close = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f32>() <= distance * distance;
+        let cell = |p: &Vertex| p.map(|x| (x / distance).floor() as i64);
+
+        // Positions are bucketed into cells as big as the distance, so only the cells next to a vertex need checking
+        let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+        let mut out = Mesh { colors: self.colors.as_ref().map(|_| Vec::new()), tex_coords: self.tex_coords.as_ref().map(|_| Vec::new()), ..Mesh::default() };
+        let mut remap = Vec::with_capacity(self.positions.len());
+        for (i, p) in self.positions.iter().enumerate() {
+            let [x, y, z] = cell(p);
+            let neighbours = (-1..=1).flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz])));
+            let found = neighbours.filter_map(|key| grid.get(&key)).flatten().copied().find(|&j| {
+                let j = j as usize;
+                close(p, &out.positions[j])
+                    && self.colors.as_ref().zip(out.colors.as_ref()).is_none_or(|(a, b)| close(&a[i], &b[j]))
+                    && self.tex_coords.as_ref().zip(out.tex_coords.as_ref()).is_none_or(|(a, b)| close(&a[i], &b[j]))
+            });
+            let idx = match found {
+                Some(j) => {
+                    for (sum, x) in out.normals[j as usize].iter_mut().zip(self.normals[i]) {
+                        *sum += x;
+                    }
+                    j
+                },
+                None => {
+                    let j = out.copy_vertex(self, i as u32);
+                    grid.entry([x, y, z]).or_default().push(j);
+                    j
+                },
+            };
+            remap.push(idx);
+        }
+        for normal in out.normals.iter_mut() {
+            if let Some(n) = normalize(*normal) {
+                *normal = n;
+            }
+        }
+
+        for range in &self.ranges {
+            let start = out.indices.len();
+            let size = match range.primitive {
+                Primitive::Triangles => 3,
+                Primitive::Lines => 2,
+                Primitive::Points => 1,
+            };
+            for element in self.range_indices(range).chunks_exact(size) {
+                let element: Vec<u32> = element.iter().map(|&idx| remap[idx as usize]).collect();
+                let collapsed = (1..element.len()).any(|i| element[..i].contains(&element[i]));
+                if !collapsed {
+                    out.indices.extend(element);
+                }
+            }
+            if out.indices.len() > start {
+                out.ranges.push(DrawRange { start, count: out.indices.len() - start, ..range.clone() });
+            }
+        }
+        *self = out;
+    }
+
+    /// Appends a copy of vertex `idx` of `from`, giving back its index in this mesh
+    fn copy_vertex(&mut self, from: &Mesh, idx: u32) -> u32 {
+        let i = idx as usize;
+        self.positions.push(from.positions[i]);
+        self.normals.push(from.normals[i]);
+        if let Some((colors, from)) = self.colors.as_mut().zip(from.colors.as_ref()) {
+            colors.push(from[i]);
+        }
+        if let Some((tex_coords, from)) = self.tex_coords.as_mut().zip(from.tex_coords.as_ref()) {
+            tex_coords.push(from[i]);
+        }
+        if let Some((tangents, from)) = self.tangents.as_mut().zip(from.tangents.as_ref()) {
+            tangents.push(from[i]);
+        }
+        self.positions.len() as u32 - 1
+    }
+
+    /// Appends a copy of vertex `idx`, giving back the copy's index
+    fn duplicate_vertex(&mut self, idx: u32) -> u32 {
+        let i = idx as usize;
+        self.positions.push(self.positions[i]);
+        self.normals.push(self.normals[i]);
+        if let Some(colors) = self.colors.as_mut() {
+            colors.push(colors[i]);
+        }
+        if let Some(tex_coords) = self.tex_coords.as_mut() {
+            tex_coords.push(tex_coords[i]);
+        }
+        if let Some(tangents) = self.tangents.as_mut() {
+            tangents.push(tangents[i]);
+        }
+        self.positions.len() as u32 - 1
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The volume a mesh's triangles enclose, which comes out negative if they face inwards
+    pub(super) fn volume(mesh: &Mesh) -> f32 {
+        mesh.triangles().map(|tri| {
+            let [a, b, c] = tri.map(|v| mesh.positions[v as usize]);
+            dot(a, cross(b, c)) / 6.0
+        }).sum()
+    }
+
+    /// Whether every edge has exactly two triangles on it, going along it opposite ways
+    pub(super) fn is_closed(mesh: &Mesh) -> bool {
+        let validation = mesh.validate();
+        validation.non_manifold_edges.is_empty()
+            && validation.inconsistent_edges.is_empty()
+            && HalfEdgeMesh::from_mesh(mesh).border_loops().is_empty()
+    }
+
+    /// Sorted triangles by the positions of their corners, starting each at its lowest, to compare meshes that
+    /// have been renumbered or reordered
+    pub(crate) fn triangle_positions(mesh: &Mesh) -> Vec<[[u32; 3]; 3]> {
+        let mut out: Vec<[[u32; 3]; 3]> = mesh.triangles().map(|tri| {
+            let mut p = tri.map(|v| mesh.positions[v as usize].map(f32::to_bits));
+            let lowest = (0..3).min_by_key(|&i| p[i]).unwrap();
+            p.rotate_left(lowest);
+            p
+        }).collect();
+        out.sort_unstable();
+        out
+    }
+
+    #[test]
+    fn face_normal_follows_winding() {
+        let (a, b, c) = ([0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, -2.0]);
+        assert_eq!(face_normal(&[a, b, c]), [0.0, 1.0, 0.0]);
+        assert_eq!(face_normal(&[a, c, b]), [0.0, -1.0, 0.0]);
+        assert_eq!(face_normal(&[a, b, [4.0, 0.0, 0.0]]), [0.0; 3]);
+    }
+
+    #[test]
+    fn smooth_normals_point_out_of_a_sphere() {
+        let mut mesh = Mesh::icosphere(1.0, 2);
+        mesh.normals.clear();
+        mesh.compute_normals();
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!((length(*n) - 1.0).abs() < 1e-5);
+            // Vertices split along the UV seam only see the triangles on their side, so they lean a little
+            assert!(dot(*p, *n) > 0.9, "{n:?} doesn't point away from {p:?}");
+        }
+    }
+
+    #[test]
+    fn flipping_winding_turns_a_cube_inside_out() {
+        let mut mesh = Mesh::cube(2.0, 1);
+        assert!((volume(&mesh) - 8.0).abs() < 1e-4);
+        mesh.flip_winding();
+        assert!((volume(&mesh) + 8.0).abs() < 1e-4);
+    }
+}
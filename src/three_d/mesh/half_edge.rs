@@ -0,0 +1,462 @@
+use super::*;
+
+// Every edge is split into two half-edges going opposite ways, each belonging to the face on its left (so faces
+// go counter-clockwise, like the rest of the crate), with borders made of half-edges that don't have a face
+// Vertices are welded by position, so a UV seam or hard edge doesn't show up as a border. Each corner of a face
+// remembers which vertex of the original mesh it came from, and that's where its normal, UV and color come from
+
+/// One side of an edge, pointing at `vertex`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// The vertex this points to
+    pub vertex: u32,
+    /// The face this goes around, `None` on borders
+    pub face: Option<u32>,
+    pub next: u32,
+    pub prev: u32,
+    /// The other side of the edge, going the other way
+    pub twin: u32,
+    /// Which of the original mesh's vertices the face uses at `vertex`, `None` on borders
+    pub corner: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HalfEdgeVertex {
+    pub position: Vertex,
+    /// A half-edge leaving the vertex, which is on the border if the vertex is. `None` if nothing uses the vertex
+    pub half_edge: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HalfEdgeFace {
+    /// Any one of the half-edges going around the face
+    pub half_edge: u32,
+    /// The range of the original mesh the face was in
+    pub range: usize,
+}
+
+/// A polygon mesh that knows which faces, edges and vertices are next to each other
+/// Edges can only have a face on each side, so where a third face meets an edge, or two faces go along it the same
+/// way, each of them gets a border there instead
+#[derive(Clone, Debug)]
+pub struct HalfEdgeMesh {
+    vertices: Vec<HalfEdgeVertex>,
+    half_edges: Vec<HalfEdge>,
+    faces: Vec<HalfEdgeFace>,
+    /// The original mesh, for the attributes the corners point at and for its ranges
+    source: Mesh,
+    /// Which of our vertices each of the original mesh's vertices was welded into
+    welded: Vec<u32>,
+    /// The faces' half-edges going from one vertex to another, more than one where faces don't agree on the edge
+    directed: HashMap<(u32, u32), Vec<u32>>,
+}
+
+/// The distinct positions in `positions`, and which of them each one is
+/// Negative zero counts as zero, so positions that compare equal always weld
+pub(super) fn weld_positions(positions: &[Vertex]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut distinct = Vec::new();
+    let mut ids = HashMap::new();
+    let welded = positions.iter().map(|p| {
+        *ids.entry(p.map(|x| (x + 0.0).to_bits())).or_insert_with(|| {
+            distinct.push(*p);
+            distinct.len() as u32 - 1
+        })
+    }).collect();
+    (distinct, welded)
+}
+
+impl HalfEdgeMesh {
+    /// Builds the half-edges for a mesh's triangles
+    pub fn from_mesh(mesh: &Mesh) -> HalfEdgeMesh {
+        let faces = mesh.ranges.iter().enumerate()
+            .filter(|(_, range)| range.primitive == Primitive::Triangles)
+            .flat_map(|(i, range)| mesh.range_indices(range).chunks_exact(3).map(move |tri| (tri.to_vec(), i)));
+        HalfEdgeMesh::from_faces(mesh, faces)
+    }
+
+    /// Builds the half-edges for polygons made of `mesh`'s vertices, each given with the range it belongs to
+    /// Lines and points in the mesh are kept as they are, and come back out of `to_mesh`
+    pub fn from_faces(mesh: &Mesh, faces: impl IntoIterator<Item = (Vec<u32>, usize)>) -> HalfEdgeMesh {
+        let (positions, welded) = weld_positions(&mesh.positions);
+        let vertices = positions.into_iter().map(|position| HalfEdgeVertex { position, half_edge: None }).collect();
+
+        let mut out = HalfEdgeMesh { vertices, half_edges: Vec::new(), faces: Vec::new(), source: mesh.clone(), welded, directed: HashMap::new() };
+        let mut directed: HashMap<(u32, u32), Vec<u32>> = HashMap::new();
+        for (corners, range) in faces {
+            let verts: Vec<u32> = corners.iter().map(|&c| out.welded[c as usize]).collect();
+            let n = verts.len();
+            let edges: Vec<(u32, u32)> = (0..n).map(|i| (verts[i], verts[(i + 1) % n])).collect();
+            // Faces that fold back on themselves can't be represented
+            let repeats = (1..n).any(|i| verts[..i].contains(&verts[i]));
+            if n < 3 || repeats {
+                continue;
+            }
+
+            let face = out.faces.len() as u32;
+            let first = out.half_edges.len() as u32;
+            for (i, &(a, b)) in edges.iter().enumerate() {
+                let h = first + i as u32;
+                directed.entry((a, b)).or_default().push(h);
+                out.half_edges.push(HalfEdge {
+                    vertex: b,
+                    face: Some(face),
+                    next: first + ((i + 1) % n) as u32,
+                    prev: first + ((i + n - 1) % n) as u32,
+                    twin: u32::MAX,
+                    corner: Some(corners[(i + 1) % n]),
+                });
+                out.vertices[a as usize].half_edge.get_or_insert(h);
+            }
+            out.faces.push(HalfEdgeFace { half_edge: first, range });
+        }
+
+        // Pair each half-edge with the one going the other way, or with a new border half-edge if there isn't
+        // exactly one each way
+        let mut border_from: HashMap<u32, Vec<u32>> = HashMap::new();
+        for h in 0..out.half_edges.len() as u32 {
+            let (a, b) = (out.origin(h), out.half_edges[h as usize].vertex);
+            match (directed[&(a, b)].as_slice(), directed.get(&(b, a)).map(Vec::as_slice)) {
+                ([_], Some(&[twin])) => out.half_edges[h as usize].twin = twin,
+                _ => {
+                    let border = out.half_edges.len() as u32;
+                    out.half_edges.push(HalfEdge { vertex: a, face: None, next: u32::MAX, prev: u32::MAX, twin: h, corner: None });
+                    out.half_edges[h as usize].twin = border;
+                    border_from.entry(b).or_default().push(border);
+                }
+            }
+        }
+        // Border half-edges link up with the one leaving the vertex they point to, and become their vertex's
+        // half-edge so walking around the vertex starts at the border
+        for h in out.half_edges.len() as u32 - border_from.values().map(Vec::len).sum::<usize>() as u32..out.half_edges.len() as u32 {
+            let to = out.half_edges[h as usize].vertex;
+            if let Some(next) = border_from.get_mut(&to).and_then(Vec::pop) {
+                out.half_edges[h as usize].next = next;
+                out.half_edges[next as usize].prev = h;
+            }
+            let from = out.origin(h);
+            out.vertices[from as usize].half_edge = Some(h);
+        }
+        out.directed = directed;
+        out
+    }
+
+    pub fn vertices(&self) -> &[HalfEdgeVertex] {
+        &self.vertices
+    }
+
+    pub fn half_edges(&self) -> &[HalfEdge] {
+        &self.half_edges
+    }
+
+    pub fn faces(&self) -> &[HalfEdgeFace] {
+        &self.faces
+    }
+
+    /// Which of our vertices one of the original mesh's vertices was welded into
+    pub fn welded_vertex(&self, corner: u32) -> u32 {
+        self.welded[corner as usize]
+    }
+
+    /// The faces' half-edges from one vertex to another
+    /// Where there are two or more, or more than one on this and the twin edge together, the edge was given borders
+    pub fn edge_half_edges(&self, from: u32, to: u32) -> &[u32] {
+        self.directed.get(&(from, to)).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set_position(&mut self, vertex: u32, position: Vertex) {
+        self.vertices[vertex as usize].position = position;
+    }
+
+    /// The vertex a half-edge leaves from
+    pub fn origin(&self, h: u32) -> u32 {
+        let twin = self.half_edges[h as usize].twin;
+        if twin != u32::MAX {
+            self.half_edges[twin as usize].vertex
+        } else {
+            // Only while building, before the twins are filled in
+            self.half_edges[self.half_edges[h as usize].prev as usize].vertex
+        }
+    }
+
+    pub fn is_border(&self, h: u32) -> bool {
+        self.half_edges[h as usize].face.is_none()
+    }
+
+    /// Whether the edge `h` is on has a face on only one side
+    pub fn is_border_edge(&self, h: u32) -> bool {
+        self.is_border(h) || self.is_border(self.half_edges[h as usize].twin)
+    }
+
+    pub fn is_border_vertex(&self, vertex: u32) -> bool {
+        self.vertices[vertex as usize].half_edge.is_some_and(|h| self.is_border(h))
+    }
+
+    /// The half-edges leaving a vertex, going around it counter-clockwise
+    pub fn outgoing(&self, vertex: u32) -> Vec<u32> {
+        let Some(start) = self.vertices[vertex as usize].half_edge else { return Vec::new() };
+        let mut out = vec![start];
+        let mut h = start;
+        // Each step goes round one face, but a border stops it with nowhere to go
+        while let Some(prev) = Some(self.half_edges[h as usize].prev).filter(|&prev| prev != u32::MAX) {
+            h = self.half_edges[prev as usize].twin;
+            if h == start || out.len() > self.half_edges.len() {
+                break;
+            }
+            out.push(h);
+        }
+        out
+    }
+
+    /// The vertices sharing an edge with `vertex`
+    pub fn neighbours(&self, vertex: u32) -> Vec<u32> {
+        self.outgoing(vertex).into_iter().map(|h| self.half_edges[h as usize].vertex).collect()
+    }
+
+    /// The faces around a vertex
+    pub fn vertex_faces(&self, vertex: u32) -> Vec<u32> {
+        self.outgoing(vertex).into_iter().filter_map(|h| self.half_edges[h as usize].face).collect()
+    }
+
+    /// The half-edges around a face, in order
+    pub fn face_half_edges(&self, face: u32) -> Vec<u32> {
+        let start = self.faces[face as usize].half_edge;
+        let mut out = vec![start];
+        let mut h = self.half_edges[start as usize].next;
+        while h != start {
+            out.push(h);
+            h = self.half_edges[h as usize].next;
+        }
+        out
+    }
+
+    /// The vertices around a face, counter-clockwise
+    pub fn face_vertices(&self, face: u32) -> Vec<u32> {
+        self.face_half_edges(face).into_iter().map(|h| self.half_edges[h as usize].vertex).collect()
+    }
+
+    /// The faces sharing an edge with `face`
+    pub fn adjacent_faces(&self, face: u32) -> Vec<u32> {
+        self.face_half_edges(face).into_iter()
+            .filter_map(|h| self.half_edges[self.half_edges[h as usize].twin as usize].face)
+            .collect()
+    }
+
+    /// Every border, as the border half-edges going around it in order
+    pub fn border_loops(&self) -> Vec<Vec<u32>> {
+        let mut seen = vec![false; self.half_edges.len()];
+        let mut loops = Vec::new();
+        for start in 0..self.half_edges.len() as u32 {
+            if seen[start as usize] || !self.is_border(start) {
+                continue;
+            }
+            let mut border = Vec::new();
+            let mut h = start;
+            while h != u32::MAX && !seen[h as usize] {
+                seen[h as usize] = true;
+                border.push(h);
+                h = self.half_edges[h as usize].next;
+            }
+            loops.push(border);
+        }
+        loops
+    }
+
+    /// The loop of edges running straight on from `h` across a quad mesh, like selecting an edge loop in a modeller
+    /// It stops at borders and at vertices that don't have exactly four edges, where straight on isn't clear
+    pub fn edge_loop(&self, h: u32) -> Vec<u32> {
+        let straight_on = |h: u32| -> Option<u32> {
+            let to = self.half_edges[h as usize].vertex;
+            if self.is_border_vertex(to) || self.outgoing(to).len() != 4 || self.is_border(h) {
+                return None;
+            }
+            let twin = self.half_edges[self.half_edges[h as usize].next as usize].twin;
+            Some(self.half_edges[twin as usize].next)
+        };
+
+        let mut forward = vec![h];
+        let mut current = h;
+        while let Some(next) = straight_on(current) {
+            if next == h {
+                // Came all the way around
+                return forward;
+            }
+            forward.push(next);
+            current = next;
+        }
+        // Then back the other way from the start, if it didn't close up
+        let mut backward = Vec::new();
+        let mut current = self.half_edges[h as usize].twin;
+        while let Some(next) = straight_on(current) {
+            backward.push(self.half_edges[next as usize].twin);
+            current = next;
+        }
+        backward.reverse();
+        backward.extend(forward);
+        backward
+    }
+
+    /// Closes every border loop with up to `max_edges` edges with a new face, giving back how many were filled
+    /// Each corner of the new face borrows its attributes from a face next to it
+    /// The faces are triangulated as fans when turned back into a mesh, so holes should be roughly convex
+    pub fn fill_holes(&mut self, max_edges: usize) -> usize {
+        let mut filled = 0;
+        for border in self.border_loops() {
+            let closed = self.half_edges[*border.last().unwrap() as usize].next == border[0];
+            if !closed || border.len() < 3 || border.len() > max_edges {
+                continue;
+            }
+            let face = self.faces.len() as u32;
+            let twin_face = self.half_edges[self.half_edges[border[0] as usize].twin as usize].face.unwrap();
+            self.faces.push(HalfEdgeFace { half_edge: border[0], range: self.faces[twin_face as usize].range });
+            for &h in &border {
+                // The next border edge leaves the vertex this points to, so its twin is a face's half-edge into it
+                let next = self.half_edges[h as usize].next;
+                let into = self.half_edges[next as usize].twin;
+                self.half_edges[h as usize].corner = self.half_edges[into as usize].corner;
+                self.half_edges[h as usize].face = Some(face);
+            }
+            // The vertices aren't on a border any more, so any half-edge leaving them will do
+            for &h in &border {
+                let from = self.origin(h);
+                self.vertices[from as usize].half_edge = Some(h);
+            }
+            filled += 1;
+        }
+        filled
+    }
+
+    /// Turns the faces back into a mesh of triangles, in the original mesh's ranges
+    /// Moved vertices keep their attributes from the original, normals included, so they may need working out again
+    pub fn to_mesh(&self) -> Mesh {
+        let source = &self.source;
+        let mut out = Mesh {
+            colors: source.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: source.tex_coords.as_ref().map(|_| Vec::new()),
+            tangents: source.tangents.as_ref().map(|_| Vec::new()),
+            ..Mesh::default()
+        };
+        let mut copies: HashMap<u32, u32> = HashMap::new();
+        let mut vertex = |out: &mut Mesh, corner: u32| *copies.entry(corner).or_insert_with(|| {
+            let idx = out.copy_vertex(source, corner);
+            out.positions[idx as usize] = self.vertices[self.welded[corner as usize] as usize].position;
+            idx
+        });
+
+        for (i, range) in source.ranges.iter().enumerate() {
+            let start = out.indices.len();
+            if range.primitive == Primitive::Triangles {
+                for face in (0..self.faces.len() as u32).filter(|&f| self.faces[f as usize].range == i) {
+                    let corners: Vec<u32> = self.face_half_edges(face).into_iter()
+                        .filter_map(|h| self.half_edges[h as usize].corner)
+                        .collect();
+                    for k in 2..corners.len() {
+                        for corner in [corners[0], corners[k - 1], corners[k]] {
+                            let idx = vertex(&mut out, corner);
+                            out.indices.push(idx);
+                        }
+                    }
+                }
+            } else {
+                for &v in source.range_indices(range) {
+                    let idx = vertex(&mut out, v);
+                    out.indices.push(idx);
+                }
+            }
+            if out.indices.len() > start {
+                out.ranges.push(DrawRange { start, count: out.indices.len() - start, ..range.clone() });
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{is_closed, volume};
+
+    /// A cube with the triangles on its top taken off
+    fn open_box() -> Mesh {
+        let mut mesh = Mesh::cube(2.0, 1);
+        let positions = mesh.positions.clone();
+        mesh.indices = mesh.triangles()
+            .filter(|tri| tri.iter().any(|&v| positions[v as usize][1] < 1.0))
+            .flatten()
+            .collect();
+        mesh.ranges[0].count = mesh.indices.len();
+        mesh
+    }
+
+    #[test]
+    fn a_closed_mesh_has_no_borders() {
+        let mesh = HalfEdgeMesh::from_mesh(&Mesh::icosphere(1.0, 2));
+        assert!(mesh.half_edges().iter().all(|h| h.face.is_some()));
+        // Euler's formula, V - E + F = 2 for anything shaped like a sphere
+        let (v, e, f) = (mesh.vertices().len(), mesh.half_edges().len() / 2, mesh.faces().len());
+        assert_eq!(v + f, e + 2);
+        for (i, h) in mesh.half_edges().iter().enumerate() {
+            assert_eq!(mesh.half_edges()[h.twin as usize].twin, i as u32);
+            assert_eq!(mesh.half_edges()[h.next as usize].prev, i as u32);
+            assert_eq!(mesh.origin(h.twin), h.vertex);
+        }
+        // The 12 vertices left from the icosahedron have 5 neighbours, the rest have 6
+        let fives = (0..v as u32).filter(|&v| mesh.neighbours(v).len() == 5).count();
+        assert_eq!(fives, 12);
+        assert!((0..v as u32).all(|v| matches!(mesh.neighbours(v).len(), 5 | 6)));
+    }
+
+    #[test]
+    fn seams_are_welded() {
+        // Every face of the cube has its own vertices, but they only make 8 corners
+        let mesh = HalfEdgeMesh::from_mesh(&Mesh::cube(1.0, 1));
+        assert_eq!(mesh.vertices().len(), 8);
+        assert!(mesh.border_loops().is_empty());
+        assert!((0..8).all(|v| mesh.vertex_faces(v).len() == mesh.outgoing(v).len()));
+    }
+
+    #[test]
+    fn borders_go_around_the_hole() {
+        let mesh = HalfEdgeMesh::from_mesh(&open_box());
+        let borders = mesh.border_loops();
+        assert_eq!(borders.len(), 1);
+        assert_eq!(borders[0].len(), 4);
+        for &h in &borders[0] {
+            assert!(mesh.is_border(h));
+            assert_eq!(mesh.vertices()[mesh.half_edges()[h as usize].vertex as usize].position[1], 1.0);
+        }
+        assert_eq!((0..8).filter(|&v| mesh.is_border_vertex(v)).count(), 4);
+    }
+
+    #[test]
+    fn fill_holes_closes_a_box() {
+        let mut mesh = HalfEdgeMesh::from_mesh(&open_box());
+        assert_eq!(mesh.fill_holes(3), 0);
+        assert_eq!(mesh.fill_holes(4), 1);
+        assert!(mesh.border_loops().is_empty());
+        // Each corner of the new face takes its attributes from a vertex at the same place
+        let face = mesh.faces().len() as u32 - 1;
+        for h in mesh.face_half_edges(face) {
+            let h = mesh.half_edges()[h as usize];
+            assert_eq!(mesh.source.positions[h.corner.unwrap() as usize], mesh.vertices()[h.vertex as usize].position);
+        }
+
+        let closed = mesh.to_mesh();
+        assert!(is_closed(&closed));
+        assert!((volume(&closed) - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn a_third_face_on_an_edge_gets_borders() {
+        let mut mesh = Mesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 5],
+            indices: vec![0, 1, 2, 1, 0, 3, 1, 0, 4],
+            ..Mesh::default()
+        };
+        mesh.ranges.push(DrawRange { primitive: Primitive::Triangles, start: 0, count: 9, group: None });
+        let half_edges = HalfEdgeMesh::from_mesh(&mesh);
+        assert_eq!(half_edges.faces().len(), 3);
+        assert!((0..half_edges.half_edges().len() as u32).all(|h| half_edges.is_border_edge(h)));
+        assert_eq!(half_edges.to_mesh().triangles().count(), 3);
+    }
+}
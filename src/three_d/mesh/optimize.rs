@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+// Reordering the index buffer doesn't change what gets drawn, only how fast. The GPU keeps the last few vertices
+// it transformed, so triangles that reuse them save work; vertex cache optimization orders triangles to do that
+// as much as possible, following Tom Forsyth's "Linear-Speed Vertex Cache Optimisation"
+// Overdraw optimization then moves whole runs of those triangles around, so the ones facing out of the mesh
+// tend to be drawn first and hide the rest, from Sander, Nehab and Barczak's "Fast Triangle Reordering"
+// Last, vertex fetch optimization renumbers the vertices in the order they're first used, so reading them goes
+// through memory in order
+
+/// How many vertices the Forsyth scoring thinks the cache holds, bigger than real ones so the order suits any
+const SCORING_CACHE_SIZE: usize = 32;
+/// The size of cache `acmr` is usually measured with, a FIFO about as big as the ones on real GPUs
+pub const ACMR_CACHE_SIZE: usize = 16;
+/// How much worse than the vertex cache order a run of triangles can get before the overdraw pass won't split it
+const OVERDRAW_THRESHOLD: f32 = 1.05;
+
+/// How much Forsyth's method wants to use a vertex next, from where it is in the cache and how many triangles
+/// still need it
+fn vertex_score(cache_position: Option<usize>, remaining: u32) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache = match cache_position {
+        // The last triangle's vertices get a fixed score, so the next one doesn't favour any of its edges
+        Some(p) if p < 3 => 0.75,
+        Some(p) => (1.0 - (p - 3) as f32 / (SCORING_CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+    // Vertices with few triangles left get a boost, so they get finished off instead of left stranded
+    cache + 2.0 / (remaining as f32).sqrt()
+}
+
+/// Runs a triangle through a FIFO cache of `cache_size`, giving back how many of its vertices weren't in it
+fn fifo(cache: &mut VecDeque<u32>, tri: &[u32], cache_size: usize) -> u32 {
+    let mut misses = 0;
+    for &v in tri {
+        if !cache.contains(&v) {
+            misses += 1;
+            cache.push_back(v);
+            if cache.len() > cache_size {
+                cache.pop_front();
+            }
+        }
+    }
+    misses
+}
+
+/// Counts the vertices a FIFO cache of `cache_size` would have to transform for each triangle
+fn cache_misses(triangles: &[u32], cache_size: usize) -> Vec<u32> {
+    let mut cache = VecDeque::with_capacity(cache_size + 1);
+    triangles.chunks_exact(3).map(|tri| fifo(&mut cache, tri, cache_size)).collect()
+}
+
+/// Puts the triangles in `indices` in an order that makes good use of the vertex cache
+fn forsyth(indices: &mut [u32], vertex_count: usize) {
+    let tri_count = indices.len() / 3;
+    // Which triangles use each vertex, with the ones already drawn moved to the end
+    let mut remaining = vec![0u32; vertex_count];
+    for &v in indices.iter() {
+        remaining[v as usize] += 1;
+    }
+    let mut offsets = vec![0usize; vertex_count + 1];
+    for v in 0..vertex_count {
+        offsets[v + 1] = offsets[v] + remaining[v] as usize;
+    }
+    let mut uses = vec![0u32; indices.len()];
+    let mut filled = offsets.clone();
+    for (i, &v) in indices.iter().enumerate() {
+        uses[filled[v as usize]] = i as u32 / 3;
+        filled[v as usize] += 1;
+    }
+
+    let mut scores: Vec<f32> = remaining.iter().map(|&r| vertex_score(None, r)).collect();
+    let tri_score = |scores: &[f32], t: usize| indices[t * 3..t * 3 + 3].iter().map(|&v| scores[v as usize]).sum::<f32>();
+    let mut tri_scores: Vec<f32> = (0..tri_count).map(|t| tri_score(&scores, t)).collect();
+    let mut drawn = vec![false; tri_count];
+    let mut order = Vec::with_capacity(tri_count);
+    let mut cache: Vec<u32> = Vec::with_capacity(SCORING_CACHE_SIZE + 3);
+    // Where to carry on looking for a triangle to start from, when nothing in the cache is left to draw
+    let mut cursor = 0;
+
+    let mut best = (0..tri_count).max_by(|&a, &b| tri_scores[a].total_cmp(&tri_scores[b]));
+    while let Some(t) = best {
+        drawn[t] = true;
+        order.push(t);
+        let tri = [indices[t * 3], indices[t * 3 + 1], indices[t * 3 + 2]];
+
+        for v in tri {
+            let v = v as usize;
+            let (start, end) = (offsets[v], offsets[v] + remaining[v] as usize);
+            if let Some(i) = uses[start..end].iter().position(|&u| u as usize == t) {
+                uses.swap(start + i, end - 1);
+            }
+            remaining[v] -= 1;
+        }
+        // The triangle's vertices go to the front, and anything pushed off the end has to be scored again too
+        let mut new_cache = tri.to_vec();
+        new_cache.extend(cache.iter().filter(|v| !tri.contains(v)));
+        let evicted = new_cache.split_off(new_cache.len().min(SCORING_CACHE_SIZE));
+        cache = new_cache;
+
+        for &v in &evicted {
+            scores[v as usize] = vertex_score(None, remaining[v as usize]);
+        }
+        for (p, &v) in cache.iter().enumerate() {
+            scores[v as usize] = vertex_score(Some(p), remaining[v as usize]);
+        }
+        best = None;
+        let mut best_score = f32::MIN;
+        for &v in cache.iter().chain(&evicted) {
+            let v = v as usize;
+            for &u in &uses[offsets[v]..offsets[v] + remaining[v] as usize] {
+                let u = u as usize;
+                tri_scores[u] = tri_score(&scores, u);
+                if tri_scores[u] > best_score {
+                    best_score = tri_scores[u];
+                    best = Some(u);
+                }
+            }
+        }
+        if best.is_none() {
+            while cursor < tri_count && drawn[cursor] {
+                cursor += 1;
+            }
+            best = (cursor < tri_count).then_some(cursor);
+        }
+    }
+
+    let old = indices.to_vec();
+    for (i, t) in order.into_iter().enumerate() {
+        indices[i * 3..i * 3 + 3].copy_from_slice(&old[t * 3..t * 3 + 3]);
+    }
+}
+
+impl Mesh {
+    /// The average number of vertices transformed per triangle with a FIFO vertex cache of `cache_size`
+    /// It's 3 with no reuse at all, and can get down to around 0.5 for a big grid, so lower is better
+    pub fn acmr(&self, cache_size: usize) -> f32 {
+        let (mut misses, mut triangles) = (0, 0);
+        for range in self.ranges.iter().filter(|range| range.primitive == Primitive::Triangles) {
+            let tris = self.range_indices(range);
+            misses += cache_misses(tris, cache_size).into_iter().sum::<u32>();
+            triangles += tris.len() / 3;
+        }
+        if triangles == 0 { 0.0 } else { misses as f32 / triangles as f32 }
+    }
+
+    /// Reorders the triangles in each range to reuse recently transformed vertices as much as possible
+    pub fn optimize_vertex_cache(&mut self) {
+        let vertex_count = self.positions.len();
+        for range in self.ranges.clone().iter().filter(|range| range.primitive == Primitive::Triangles) {
+            let count = range.count - range.count % 3;
+            forsyth(&mut self.indices[range.start..range.start + count], vertex_count);
+        }
+    }
+
+    /// Reorders runs of triangles in each range so the ones facing outwards are drawn first, covering up more of
+    /// what's behind them. This goes after `optimize_vertex_cache`, and only splits its order where doing so
+    /// doesn't cost more than a few percent of the cache hits
+    pub fn optimize_overdraw(&mut self) {
+        for range in self.ranges.clone().iter().filter(|range| range.primitive == Primitive::Triangles) {
+            let count = range.count - range.count % 3;
+            let tris = &mut self.indices[range.start..range.start + count];
+            let misses = cache_misses(tris, ACMR_CACHE_SIZE);
+
+            // Triangles that miss on all three vertices start a run that doesn't depend on the one before, and
+            // those get split again wherever the order up to there is already about as good as the whole run
+            let mut clusters = Vec::new();
+            let mut start = 0;
+            while start < misses.len() {
+                let end = (start + 1..misses.len()).find(|&t| misses[t] == 3).unwrap_or(misses.len());
+                let whole = misses[start..end].iter().sum::<u32>() as f32 / (end - start) as f32;
+                // A run that gets moved starts with nothing useful in the cache, so it's counted from empty
+                let mut cache = VecDeque::with_capacity(ACMR_CACHE_SIZE + 1);
+                let mut total = 0;
+                let mut from = start;
+                for t in start..end {
+                    total += fifo(&mut cache, &tris[t * 3..t * 3 + 3], ACMR_CACHE_SIZE);
+                    let so_far = total as f32 / (t + 1 - from) as f32;
+                    if t + 1 < end && so_far <= whole * OVERDRAW_THRESHOLD {
+                        clusters.push(from..t + 1);
+                        from = t + 1;
+                        total = 0;
+                        cache.clear();
+                    }
+                }
+                clusters.push(from..end);
+                start = end;
+            }
+
+            // Each run faces the way of its triangles' summed normals, and sits where their area is centered
+            let positions = &self.positions;
+            let corners = |t: usize| [0, 1, 2].map(|k| positions[tris[t * 3 + k] as usize]);
+            let mut middle = [0.0; 3];
+            let mut area = 0.0;
+            let mut cluster_info = Vec::with_capacity(clusters.len());
+            for cluster in &clusters {
+                let (mut normal, mut center, mut cluster_area) = ([0.0; 3], [0.0; 3], 0.0);
+                for t in cluster.clone() {
+                    let [a, b, c] = corners(t);
+                    let n = area_normal(&[a, b, c]);
+                    let weight = length(n);
+                    for k in 0..3 {
+                        normal[k] += n[k];
+                        center[k] += (a[k] + b[k] + c[k]) / 3.0 * weight;
+                    }
+                    cluster_area += weight;
+                }
+                for k in 0..3 {
+                    middle[k] += center[k];
+                }
+                area += cluster_area;
+                if cluster_area > 0.0 {
+                    center = center.map(|x| x / cluster_area);
+                }
+                cluster_info.push((normal, center));
+            }
+            if area > 0.0 {
+                middle = middle.map(|x| x / area);
+            }
+            // Facing further out from the middle of the mesh means more likely to be in front
+            let facing: Vec<f32> = cluster_info.iter().map(|&(normal, center)| {
+                normalize(normal).map_or(0.0, |normal| dot(sub(center, middle), normal))
+            }).collect();
+            let mut order: Vec<usize> = (0..clusters.len()).collect();
+            order.sort_by(|&a, &b| facing[b].total_cmp(&facing[a]));
+
+            let old = tris.to_vec();
+            let mut i = 0;
+            for cluster in order.into_iter().map(|c| clusters[c].clone()) {
+                let len = cluster.len() * 3;
+                tris[i..i + len].copy_from_slice(&old[cluster.start * 3..cluster.end * 3]);
+                i += len;
+            }
+        }
+    }
+
+    /// Renumbers the vertices in the order the indices first use them, dropping any that aren't used
+    pub fn optimize_vertex_fetch(&mut self) {
+        let mut out = Mesh {
+            colors: self.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: self.tex_coords.as_ref().map(|_| Vec::new()),
+            tangents: self.tangents.as_ref().map(|_| Vec::new()),
+            ..Mesh::default()
+        };
+        let mut remap = vec![u32::MAX; self.positions.len()];
+        for i in 0..self.indices.len() {
+            let v = self.indices[i] as usize;
+            if remap[v] == u32::MAX {
+                remap[v] = out.copy_vertex(self, v as u32);
+            }
+            self.indices[i] = remap[v];
+        }
+        out.indices = std::mem::take(&mut self.indices);
+        out.ranges = std::mem::take(&mut self.ranges);
+        *self = out;
+    }
+
+    /// Runs the vertex cache, overdraw and vertex fetch optimizations, in that order
+    pub fn optimize(&mut self) {
+        self.optimize_vertex_cache();
+        self.optimize_overdraw();
+        self.optimize_vertex_fetch();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::triangle_positions;
+
+    /// A sphere with its triangles in a scrambled order, so the cache barely gets used
+    fn scrambled_sphere() -> Mesh {
+        let mut mesh = Mesh::icosphere(1.0, 3);
+        let triangles: Vec<[u32; 3]> = mesh.triangles().collect();
+        // Stepping through by a prime bigger than the count visits each triangle once
+        mesh.indices = (0..triangles.len()).flat_map(|i| triangles[i * 7919 % triangles.len()]).collect();
+        mesh
+    }
+
+    #[test]
+    fn fifo_cache_counts_misses() {
+        // Two triangles sharing an edge only miss on the vertex that isn't shared
+        assert_eq!(cache_misses(&[0, 1, 2, 2, 1, 3], 16), vec![3, 1]);
+        // With room for only 3, the first vertex is pushed out before it's used again
+        assert_eq!(cache_misses(&[0, 1, 2, 1, 2, 3, 3, 2, 0], 3), vec![3, 1, 1]);
+    }
+
+    #[test]
+    fn forsyth_lowers_acmr() {
+        let mut mesh = scrambled_sphere();
+        let before = mesh.acmr(ACMR_CACHE_SIZE);
+        mesh.optimize_vertex_cache();
+        let after = mesh.acmr(ACMR_CACHE_SIZE);
+        assert!(before > 2.0, "the scrambled sphere should barely hit the cache, but had an ACMR of {before}");
+        assert!(after < 0.8, "the ACMR only went from {before} to {after}");
+    }
+
+    #[test]
+    fn optimize_keeps_the_same_triangles() {
+        let original = scrambled_sphere();
+        let mut mesh = original.clone();
+        mesh.optimize();
+        assert!(mesh.acmr(ACMR_CACHE_SIZE) < original.acmr(ACMR_CACHE_SIZE));
+        assert_eq!(triangle_positions(&mesh), triangle_positions(&original));
+        // Vertex fetch order means the vertices are first used in order
+        let mut next = 0;
+        for &v in &mesh.indices {
+            assert!(v <= next);
+            next = next.max(v + 1);
+        }
+        assert_eq!(next as usize, mesh.positions.len());
+    }
+}
@@ -0,0 +1,341 @@
+use std::f32::consts::{PI, TAU};
+
+use super::*;
+
+// Everything is centered on the origin with Y up, and texture coordinates have V going up the image
+// Seams get their own copies of the vertices along them, so textures don't smear back across the shape
+
+impl Mesh {
+    /// A flat `width` by `depth` rectangle facing up, split into `x_segments` by `z_segments` quads
+    pub fn plane(width: f32, depth: f32, x_segments: u32, z_segments: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        mesh.add_grid(x_segments, z_segments, |u, v| {
+            ([(u - 0.5) * width, 0.0, (0.5 - v) * depth], [0.0, 1.0, 0.0], [u, v])
+        });
+        mesh.finish()
+    }
+
+    /// A cube with sides `size` long, each face split into `segments` by `segments` quads
+    /// Every face has its own vertices, so the edges stay sharp, and its own copy of the whole texture
+    pub fn cube(size: f32, segments: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        let half = size / 2.0;
+        // The normal, then which ways U and V go across the face when looking at it from outside
+        let faces = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        for (normal, right, up) in faces {
+            mesh.add_grid(segments, segments, |u, v| {
+                let p = [0, 1, 2].map(|i| (normal[i] + right[i] * (2.0 * u - 1.0) + up[i] * (2.0 * v - 1.0)) * half);
+                (p, normal, [u, v])
+            });
+        }
+        mesh.finish()
+    }
+
+    /// A sphere made of `segments` slices around its middle and `rings` from pole to pole
+    /// The texture wraps around it like a map of the world
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        mesh.add_grid(segments.max(3), rings.max(2), |u, v| {
+            let n = sphere_point(u, v);
+            (n.map(|x| x * radius), n, [u, v])
+        });
+        mesh.finish()
+    }
+
+    /// A sphere made by splitting each triangle of an icosahedron into 4, `subdivisions` times
+    /// Its triangles are much closer to the same size than a UV sphere's
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut points: Vec<Vertex> = [
+            [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+            [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+            [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+        ].map(|p| normalize(p).unwrap()).to_vec();
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            // Neighbouring triangles share the point in the middle of their edge
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (pa, pb) = (points[a as usize], points[b as usize]);
+                points.push(normalize([pa[0] + pb[0], pa[1] + pb[1], pa[2] + pb[2]]).unwrap());
+                points.len() as u32 - 1
+            });
+            triangles = triangles.into_iter().flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            }).collect();
+        }
+
+        let mut mesh = Mesh::primitive();
+        // Corners are shared unless they need different texture coordinates, which only happens along the seam
+        let mut corners: HashMap<(u32, u32), u32> = HashMap::new();
+        for tri in triangles {
+            let mut uvs = tri.map(|idx| sphere_uv(points[idx as usize]));
+            // A triangle across the seam would stretch back over the whole texture, so its corners past the seam go past 1
+            let (min_u, max_u) = uvs.iter().fold((1.0f32, 0.0f32), |(min, max), uv| (min.min(uv[0]), max.max(uv[0])));
+            if max_u - min_u > 0.5 {
+                for uv in uvs.iter_mut().filter(|uv| uv[0] < 0.5) {
+                    uv[0] += 1.0;
+                }
+            }
+
+            for (idx, uv) in tri.into_iter().zip(uvs) {
+                let corner = *corners.entry((idx, uv[0].to_bits())).or_insert_with(|| {
+                    let n = points[idx as usize];
+                    mesh.add_vertex(n.map(|x| x * radius), n, uv)
+                });
+                mesh.indices.push(corner);
+            }
+        }
+        mesh.finish()
+    }
+
+    /// A cylinder standing on the XZ plane's middle, with `segments` around it and `height_segments` up its side
+    /// Its ends are capped with disks
+    pub fn cylinder(radius: f32, height: f32, segments: u32, height_segments: u32) -> Mesh {
+        Mesh::frustum(radius, radius, height, segments, height_segments)
+    }
+
+    /// A cone pointing up, with `segments` around it and `height_segments` up its side
+    /// Its base is capped with a disk
+    pub fn cone(radius: f32, height: f32, segments: u32, height_segments: u32) -> Mesh {
+        Mesh::frustum(radius, 0.0, height, segments, height_segments)
+    }
+
+    /// A ring around the Y axis, `major_radius` out to the middle of its tube and `minor_radius` around the tube
+    /// `segments` go around the ring and `sides` around the tube
+    pub fn torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        mesh.add_grid(segments.max(3), sides.max(3), |u, v| {
+            // Wrapped so that the seams line up exactly
+            let (theta, phi) = ((u % 1.0) * TAU, (v % 1.0) * TAU);
+            let around = [theta.cos(), 0.0, -theta.sin()];
+            let n = [around[0] * phi.cos(), phi.sin(), around[2] * phi.cos()];
+            let p = [0, 1, 2].map(|i| around[i] * major_radius + n[i] * minor_radius);
+            (p, n, [u, v])
+        });
+        mesh.finish()
+    }
+
+    /// A cylinder `height` tall with half spheres on the ends, so it's `height + 2 * radius` tall overall
+    /// `segments` go around it and `rings` go over each half sphere
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        let rings = rings.max(1);
+        let total = height + 2.0 * radius;
+        // The texture runs the whole length, so each part gets the slice of V that matches how long it is
+        let (bottom_v, top_v) = (radius / total, (radius + height) / total);
+
+        let mut cap = |top: bool| {
+            let sign = if top { 1.0 } else { -1.0 };
+            mesh.add_grid(segments.max(3), rings, |u, v| {
+                // From the pole to the equator on the bottom, and the equator to the pole on the top
+                let v_sphere = if top { 0.5 + v / 2.0 } else { v / 2.0 };
+                let n = sphere_point(u, v_sphere);
+                let p = [n[0] * radius, n[1] * radius + sign * height / 2.0, n[2] * radius];
+                let tex_v = if top { top_v + v * (1.0 - top_v) } else { v * bottom_v };
+                (p, n, [u, tex_v])
+            });
+        };
+        cap(false);
+        cap(true);
+        mesh.add_grid(segments.max(3), 1, |u, v| {
+            let n = sphere_point(u, 0.5);
+            ([n[0] * radius, (v - 0.5) * height, n[2] * radius], n, [u, bottom_v + v * (top_v - bottom_v)])
+        });
+        mesh.finish()
+    }
+
+    /// A flat circle facing up, with `segments` around it and `rings` from the middle out
+    /// The texture is laid flat over it, like on `plane`
+    pub fn disk(radius: f32, segments: u32, rings: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        mesh.add_disk(radius, 0.0, [0.0, 1.0, 0.0], segments, rings);
+        mesh.finish()
+    }
+
+    /// A cone with its top cut off, or a cylinder if the radii match
+    fn frustum(bottom_radius: f32, top_radius: f32, height: f32, segments: u32, height_segments: u32) -> Mesh {
+        let mut mesh = Mesh::primitive();
+        let segments = segments.max(3);
+        // The side leans in by however much narrower the top is, which tips its normals up by the same amount
+        let slope = (bottom_radius - top_radius) / height;
+        let scale = 1.0 / (1.0 + slope * slope).sqrt();
+        mesh.add_grid(segments, height_segments, |u, v| {
+            let around = sphere_point(u, 0.5);
+            let radius = bottom_radius + (top_radius - bottom_radius) * v;
+            let p = [around[0] * radius, (v - 0.5) * height, around[2] * radius];
+            (p, [around[0] * scale, slope * scale, around[2] * scale], [u, v])
+        });
+
+        mesh.add_disk(bottom_radius, -height / 2.0, [0.0, -1.0, 0.0], segments, 1);
+        if top_radius > 0.0 {
+            mesh.add_disk(top_radius, height / 2.0, [0.0, 1.0, 0.0], segments, 1);
+        }
+        mesh.finish()
+    }
+
+    /// An empty mesh with room for texture coordinates
+    pub(super) fn primitive() -> Mesh {
+        Mesh { tex_coords: Some(Vec::new()), ..Mesh::default() }
+    }
+
+    fn add_vertex(&mut self, position: Vertex, normal: Normal, tex_coord: TexCoord) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.tex_coords.get_or_insert_with(Vec::new).push(tex_coord);
+        self.positions.len() as u32 - 1
+    }
+
+    /// Adds a triangle, wound so that it faces the same way as its vertices' normals
+    /// Triangles with no area, like the ones touching the poles of a sphere, are left out
+    fn add_triangle(&mut self, mut corners: [u32; 3]) {
+        let normal = face_normal(&corners.map(|idx| self.positions[idx as usize]));
+        if normal == [0.0; 3] {
+            return;
+        }
+        let facing: f32 = corners.iter()
+            .map(|&idx| self.normals[idx as usize])
+            .map(|n| n[0] * normal[0] + n[1] * normal[1] + n[2] * normal[2])
+            .sum();
+        if facing < 0.0 {
+            corners.swap(1, 2);
+        }
+        self.indices.extend(corners);
+    }
+
+    /// Adds `cols` by `rows` quads, with `vertex` giving the position, normal and texture coordinate of each corner
+    /// from how far across and along the grid it is, both from 0 to 1
+    pub(super) fn add_grid(&mut self, cols: u32, rows: u32, vertex: impl Fn(f32, f32) -> (Vertex, Normal, TexCoord)) {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        let first = self.positions.len() as u32;
+        for row in 0..=rows {
+            for col in 0..=cols {
+                let (p, n, uv) = vertex(col as f32 / cols as f32, row as f32 / rows as f32);
+                self.add_vertex(p, n, uv);
+            }
+        }
+        let corner = |col: u32, row: u32| first + row * (cols + 1) + col;
+        for row in 0..rows {
+            for col in 0..cols {
+                let (a, b, c, d) = (corner(col, row), corner(col + 1, row), corner(col + 1, row + 1), corner(col, row + 1));
+                self.add_triangle([a, b, c]);
+                self.add_triangle([a, c, d]);
+            }
+        }
+    }
+
+    /// Adds a flat circle at height `y`, facing straight up or down along `normal`
+    fn add_disk(&mut self, radius: f32, y: f32, normal: Normal, segments: u32, rings: u32) {
+        let (segments, rings) = (segments.max(3), rings.max(1));
+        let planar = |p: Vertex| [p[0] / (2.0 * radius) + 0.5, -p[2] / (2.0 * radius) + 0.5];
+        let center = self.add_vertex([0.0, y, 0.0], normal, [0.5, 0.5]);
+
+        // The middle is a fan, so it doesn't end up with a ring of slivers around the center
+        let ring_start = |ring: u32| center + 1 + (ring - 1) * segments;
+        for ring in 1..=rings {
+            let r = radius * ring as f32 / rings as f32;
+            for i in 0..segments {
+                let around = sphere_point(i as f32 / segments as f32, 0.5);
+                let p = [around[0] * r, y, around[2] * r];
+                self.add_vertex(p, normal, planar(p));
+            }
+        }
+        for i in 0..segments {
+            let next = (i + 1) % segments;
+            self.add_triangle([center, ring_start(1) + i, ring_start(1) + next]);
+            for ring in 1..rings {
+                let (inner, outer) = (ring_start(ring), ring_start(ring + 1));
+                self.add_triangle([inner + i, outer + i, outer + next]);
+                self.add_triangle([inner + i, outer + next, inner + next]);
+            }
+        }
+    }
+
+    /// Puts everything added so far into one range of triangles
+    pub(super) fn finish(mut self) -> Mesh {
+        self.ranges = vec![DrawRange { primitive: Primitive::Triangles, start: 0, count: self.indices.len(), group: None }];
+        self
+    }
+}
+
+/// A point on the unit sphere, `u` of the way around from +X towards -Z and `v` of the way up from the bottom
+fn sphere_point(u: f32, v: f32) -> Vertex {
+    // The poles and the seam are pinned down exactly, so the vertices that meet there really do meet
+    if v <= 0.0 || v >= 1.0 {
+        return [0.0, if v <= 0.0 { -1.0 } else { 1.0 }, 0.0];
+    }
+    let (theta, phi) = ((u % 1.0) * TAU, (v - 0.5) * PI);
+    [phi.cos() * theta.cos(), phi.sin(), -phi.cos() * theta.sin()]
+}
+
+/// The inverse of `sphere_point`
+fn sphere_uv(p: Vertex) -> TexCoord {
+    let u = (-p[2]).atan2(p[0]) / TAU;
+    [if u < 0.0 { u + 1.0 } else { u }, p[1].clamp(-1.0, 1.0).asin() / PI + 0.5]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{is_closed, volume};
+
+    #[test]
+    fn solids_are_closed_and_face_outwards() {
+        let solids = [
+            ("cube", Mesh::cube(1.0, 2)),
+            ("uv_sphere", Mesh::uv_sphere(1.0, 16, 8)),
+            ("icosphere", Mesh::icosphere(1.0, 2)),
+            ("cylinder", Mesh::cylinder(1.0, 2.0, 16, 2)),
+            ("cone", Mesh::cone(1.0, 2.0, 16, 2)),
+            ("torus", Mesh::torus(1.0, 0.25, 24, 12)),
+            ("capsule", Mesh::capsule(0.5, 1.0, 16, 4)),
+        ];
+        for (name, mesh) in solids {
+            assert!(is_closed(&mesh), "{name} isn't closed");
+            assert!(volume(&mesh) > 0.0, "{name} faces inwards");
+            for tri in mesh.triangles() {
+                let n = face_normal(&tri.map(|v| mesh.positions[v as usize]));
+                // The poles and the tip of the cone have triangles without any area
+                if n != [0.0; 3] {
+                    assert!(tri.iter().all(|&v| dot(n, mesh.normals[v as usize]) > 0.0), "{name} has normals facing in");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solids_have_the_right_volume() {
+        assert!((volume(&Mesh::cube(2.0, 3)) - 8.0).abs() < 1e-4);
+        let sphere = 4.0 / 3.0 * PI;
+        assert!((volume(&Mesh::icosphere(1.0, 3)) / sphere - 1.0).abs() < 0.02);
+        assert!((volume(&Mesh::uv_sphere(1.0, 64, 32)) / sphere - 1.0).abs() < 0.02);
+        assert!((volume(&Mesh::cylinder(1.0, 2.0, 64, 1)) / (2.0 * PI) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn flat_shapes_face_up_with_one_border() {
+        for (name, mesh) in [("plane", Mesh::plane(2.0, 1.0, 4, 2)), ("disk", Mesh::disk(1.0, 16, 3))] {
+            assert_eq!(HalfEdgeMesh::from_mesh(&mesh).border_loops().len(), 1, "{name} should have one border");
+            for tri in mesh.triangles() {
+                assert_eq!(face_normal(&tri.map(|v| mesh.positions[v as usize])), [0.0, 1.0, 0.0], "{name} faces down");
+            }
+            assert!(mesh.normals.iter().all(|&n| n == [0.0, 1.0, 0.0]));
+        }
+        let bounds = Mesh::plane(2.0, 1.0, 4, 2).bounds().unwrap();
+        assert_eq!(bounds.size(), [2.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,477 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use super::*;
+
+// Simplification collapses edges one at a time, cheapest first, the way Garland and Heckbert describe
+// Each corner keeps a quadric, the sum of the squared distances to the planes of the triangles around it, and
+// collapsing an edge costs the squared distance the merged quadric puts the surviving corner from those planes
+// Edges always collapse onto one of their ends, so every vertex left keeps the normal, UV and color it came with
+
+/// How much more a border or UV seam costs to move off of than a plane does, so the outline of the mesh stays put
+const BORDER_WEIGHT: f64 = 16.0;
+/// How far each level of detail can stray from the one before, as a fraction of the mesh's diagonal
+const LOD_MAX_ERROR: f32 = 0.05;
+/// Collapses that would turn a triangle further than this (as the cosine of the angle) are skipped
+const MIN_NORMAL_DOT: f64 = 0.2;
+
+/// The upper triangle of a symmetric 4x4 matrix
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Squared distance to the plane `a x + b y + c z + d = 0`, with `(a, b, c)` of unit length
+    fn plane(a: f64, b: f64, c: f64, d: f64, weight: f64) -> Quadric {
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|x| x * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (x, y) in self.0.iter_mut().zip(other.0) {
+            *x += y;
+        }
+    }
+
+    fn error(&self, p: [f64; 3]) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+        let [x, y, z] = p;
+        (a2 * x * x + 2.0 * ab * x * y + 2.0 * ac * x * z + 2.0 * ad * x
+            + b2 * y * y + 2.0 * bc * y * z + 2.0 * bd * y
+            + c2 * z * z + 2.0 * cd * z + d2).max(0.0)
+    }
+}
+
+/// A possible collapse of `from` onto `to`, ordered so the cheapest comes out of the heap first
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+    /// What `versions` were for the two ends when this was worked out, if either has changed since it's out of date
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+// The same as the vector helpers in mesh.rs, but in f64, since quadrics summed from many planes lose too much in f32
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let len = dot(v, v).sqrt();
+    (len > 1e-20).then(|| v.map(|x| x / len))
+}
+
+/// The state of a simplification, where corners are the distinct positions in the mesh
+/// Vertices at the same position (split for different normals or UVs) move together as one corner
+struct Simplifier<'a> {
+    mesh: &'a Mesh,
+    /// Which corner each vertex is at
+    corner_of: Vec<u32>,
+    corners: Vec<[f64; 3]>,
+    quadrics: Vec<Quadric>,
+    /// Corners on the edge of the mesh or along a UV seam, which can only slide along it
+    on_border: Vec<bool>,
+    /// Triangles by vertex, and which range each came from. Collapsed ones are `None`
+    triangles: Vec<Option<[u32; 3]>>,
+    triangle_range: Vec<usize>,
+    /// The triangles around each corner, including some that have since collapsed
+    around: Vec<Vec<u32>>,
+    /// Where each vertex went, it's itself until it's collapsed
+    moved_to: Vec<u32>,
+    versions: Vec<u32>,
+    alive: usize,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(mesh: &'a Mesh) -> Simplifier<'a> {
+        // The half-edges weld vertices into corners the same way, and leave out triangles that are already degenerate
+        let topology = HalfEdgeMesh::from_mesh(mesh);
+        let half_edges = topology.half_edges();
+        let corner_of: Vec<u32> = (0..mesh.positions.len() as u32).map(|v| topology.welded_vertex(v)).collect();
+        let corners: Vec<[f64; 3]> = topology.vertices().iter().map(|v| v.position.map(f64::from)).collect();
+        let triangles: Vec<Option<[u32; 3]>> = (0..topology.faces().len() as u32).map(|f| {
+            let h = topology.face_half_edges(f);
+            Some([h[0], h[1], h[2]].map(|h| half_edges[h as usize].corner.unwrap()))
+        }).collect();
+        let triangle_range: Vec<usize> = topology.faces().iter().map(|f| f.range).collect();
+        let plane_of = |t: usize| {
+            let p = triangles[t].unwrap().map(|v| corners[corner_of[v as usize] as usize]);
+            normalize(cross(sub(p[1], p[0]), sub(p[2], p[0]))).map(|n| (n, p[0]))
+        };
+
+        let mut around = vec![Vec::new(); corners.len()];
+        let mut quadrics = vec![Quadric::default(); corners.len()];
+        for (t, tri) in triangles.iter().enumerate() {
+            let c = tri.unwrap().map(|v| corner_of[v as usize]);
+            for c in c {
+                around[c as usize].push(t as u32);
+            }
+            let Some((n, p)) = plane_of(t) else { continue };
+            let plane = Quadric::plane(n[0], n[1], n[2], -dot(n, p), 1.0);
+            for c in c {
+                quadrics[c as usize].add(&plane);
+            }
+        }
+
+        // A border edge has a triangle on one side, and one whose triangles use different vertices is a UV (or normal) seam
+        // Edges between ranges count as seams too, so the outlines of materials stay where they were
+        // Both get a plane standing up along them, so moving off of them costs a lot more than sliding along them
+        let mut on_border = vec![false; corners.len()];
+        for (h, edge) in half_edges.iter().enumerate() {
+            let twin = edge.twin;
+            // Each edge once, from a side with a triangle
+            if edge.face.is_none() || (!topology.is_border(twin) && (twin as usize) < h) {
+                continue;
+            }
+            let sides = if topology.is_border(twin) { vec![h as u32] } else { vec![h as u32, twin] };
+            if let [h, twin] = sides[..] {
+                let prev_corner = |h: u32| half_edges[half_edges[h as usize].prev as usize].corner;
+                let (f, g) = (half_edges[h as usize].face.unwrap(), half_edges[twin as usize].face.unwrap());
+                let seam = triangle_range[f as usize] != triangle_range[g as usize]
+                    || half_edges[h as usize].corner != prev_corner(twin)
+                    || prev_corner(h) != half_edges[twin as usize].corner;
+                if !seam {
+                    continue;
+                }
+            }
+            let (a, b) = (topology.origin(h as u32), edge.vertex);
+            on_border[a as usize] = true;
+            on_border[b as usize] = true;
+            for side in sides {
+                let t = half_edges[side as usize].face.unwrap() as usize;
+                let (Some((n, _)), Some(along)) = (plane_of(t), normalize(sub(corners[b as usize], corners[a as usize]))) else {
+                    continue;
+                };
+                let Some(up) = normalize(cross(along, n)) else { continue };
+                let plane = Quadric::plane(up[0], up[1], up[2], -dot(up, corners[a as usize]), BORDER_WEIGHT);
+                quadrics[a as usize].add(&plane);
+                quadrics[b as usize].add(&plane);
+            }
+        }
+
+        Simplifier {
+            mesh,
+            alive: triangles.len(),
+            corner_of,
+            versions: vec![0; corners.len()],
+            corners,
+            quadrics,
+            on_border,
+            triangle_range,
+            triangles,
+            around,
+            moved_to: (0..mesh.positions.len() as u32).collect(),
+        }
+    }
+
+    fn corners_of(&self, t: u32) -> Option<[u32; 3]> {
+        self.triangles[t as usize].map(|tri| tri.map(|v| self.corner_of[v as usize]))
+    }
+
+    /// The corners that share a live triangle with `corner`
+    fn neighbours(&self, corner: u32) -> Vec<u32> {
+        let mut out: Vec<u32> = self.around[corner as usize].iter()
+            .filter_map(|&t| self.corners_of(t))
+            .flatten()
+            .filter(|&c| c != corner)
+            .collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Whether the edge between corners `a` and `b` is on a border, a UV seam, or between two materials
+    fn is_seam(&self, a: u32, b: u32) -> bool {
+        let mut sides = self.around[a as usize].iter()
+            .filter_map(|&t| self.triangles[t as usize].map(|tri| (tri, self.triangle_range[t as usize])))
+            .filter(|(tri, _)| tri.iter().any(|&v| self.corner_of[v as usize] == b))
+            .map(|(tri, range)| {
+                let at = |corner| tri.iter().copied().find(|&v| self.corner_of[v as usize] == corner);
+                (at(a), at(b), range)
+            });
+        match (sides.next(), sides.next(), sides.next()) {
+            (Some(x), Some(y), None) => x != y,
+            _ => true,
+        }
+    }
+
+    /// How much collapsing `from` onto `to` costs, or `None` if it would tear a border off
+    fn cost(&self, from: u32, to: u32) -> Option<f64> {
+        if self.on_border[from as usize] {
+            // Border corners can only slide along their border, and corners where borders meet can't move at all
+            let seams = self.neighbours(from).into_iter().filter(|&other| self.is_seam(from, other)).count();
+            if seams != 2 || !self.is_seam(from, to) {
+                return None;
+            }
+        }
+        let mut q = self.quadrics[from as usize];
+        q.add(&self.quadrics[to as usize]);
+        Some(q.error(self.corners[to as usize]))
+    }
+
+    /// The cheaper way to collapse the edge between `a` and `b`
+    fn best_collapse(&self, a: u32, b: u32) -> Option<Collapse> {
+        let ab = self.cost(a, b).map(|cost| (cost, a, b));
+        let ba = self.cost(b, a).map(|cost| (cost, b, a));
+        let (cost, from, to) = match (ab, ba) {
+            (Some(ab), Some(ba)) => if ab.0 <= ba.0 { ab } else { ba },
+            (ab, ba) => ab.or(ba)?,
+        };
+        Some(Collapse { cost, from, to, versions: (self.versions[from as usize], self.versions[to as usize]) })
+    }
+
+    /// Whether collapsing keeps the mesh manifold and doesn't flip any triangles over
+    fn can_collapse(&self, from: u32, to: u32) -> bool {
+        // The ends can only share the neighbours on either side of the edge, or a pinched shape is left behind
+        let (from_around, to_around) = (self.neighbours(from), self.neighbours(to));
+        let shared_neighbours = from_around.iter().filter(|c| to_around.contains(c)).count();
+        let shared_triangles = self.around[from as usize].iter()
+            .filter_map(|&t| self.corners_of(t))
+            .filter(|c| c.contains(&to))
+            .count();
+        if shared_triangles == 0 || shared_neighbours > shared_triangles {
+            return false;
+        }
+
+        let target = self.corners[to as usize];
+        self.around[from as usize].iter().filter_map(|&t| self.corners_of(t)).filter(|c| !c.contains(&to)).all(|c| {
+            let p = c.map(|c| self.corners[c as usize]);
+            let moved = c.map(|c| if c == from { target } else { self.corners[c as usize] });
+            let before = normalize(cross(sub(p[1], p[0]), sub(p[2], p[0])));
+            let after = normalize(cross(sub(moved[1], moved[0]), sub(moved[2], moved[0])));
+            matches!((before, after), (Some(before), Some(after)) if dot(before, after) > MIN_NORMAL_DOT)
+        })
+    }
+
+    fn collapse(&mut self, from: u32, to: u32) {
+        // Each vertex at `from` goes to the vertex at `to` it shares a triangle with, so each side of a seam stays on its side
+        let mut targets: HashMap<u32, u32> = HashMap::new();
+        let mut fallback = None;
+        for &t in &self.around[from as usize] {
+            let Some(tri) = self.triangles[t as usize] else { continue };
+            let at_to = tri.iter().copied().find(|&v| self.corner_of[v as usize] == to);
+            if let Some(v) = at_to {
+                fallback.get_or_insert(v);
+                for &u in tri.iter().filter(|&&u| self.corner_of[u as usize] == from) {
+                    targets.entry(u).or_insert(v);
+                }
+            }
+        }
+        let Some(fallback) = fallback else { return };
+
+        let moved: Vec<u32> = std::mem::take(&mut self.around[from as usize]);
+        for &t in &moved {
+            let Some(mut tri) = self.triangles[t as usize] else { continue };
+            for v in tri.iter_mut().filter(|v| self.corner_of[**v as usize] == from) {
+                let target = *targets.entry(*v).or_insert(fallback);
+                self.moved_to[*v as usize] = target;
+                *v = target;
+            }
+            let c = tri.map(|v| self.corner_of[v as usize]);
+            if c[0] == c[1] || c[1] == c[2] || c[2] == c[0] {
+                self.triangles[t as usize] = None;
+                self.alive -= 1;
+            } else {
+                self.triangles[t as usize] = Some(tri);
+                self.around[to as usize].push(t);
+            }
+        }
+
+        let q = self.quadrics[from as usize];
+        self.quadrics[to as usize].add(&q);
+        self.versions[from as usize] += 1;
+        self.versions[to as usize] += 1;
+        self.around[to as usize].retain(|&t| self.triangles[t as usize].is_some());
+    }
+
+    /// Where vertex `v` ended up, following it through every collapse
+    fn resolve(&self, mut v: u32) -> u32 {
+        while self.moved_to[v as usize] != v {
+            v = self.moved_to[v as usize];
+        }
+        v
+    }
+
+    fn finish(self) -> Mesh {
+        let mesh = self.mesh;
+        let mut out = Mesh {
+            colors: mesh.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: mesh.tex_coords.as_ref().map(|_| Vec::new()),
+            tangents: mesh.tangents.as_ref().map(|_| Vec::new()),
+            ..Mesh::default()
+        };
+        let mut new_index: HashMap<u32, u32> = HashMap::new();
+        let mut index = |out: &mut Mesh, v: u32| *new_index.entry(v).or_insert_with(|| out.copy_vertex(mesh, v));
+
+        for (i, range) in mesh.ranges.iter().enumerate() {
+            let start = out.indices.len();
+            if range.primitive == Primitive::Triangles {
+                for (tri, _) in self.triangles.iter().zip(&self.triangle_range).filter(|(_, &r)| r == i) {
+                    if let Some(tri) = tri {
+                        for &v in tri {
+                            let idx = index(&mut out, v);
+                            out.indices.push(idx);
+                        }
+                    }
+                }
+            } else {
+                let size = if range.primitive == Primitive::Lines { 2 } else { 1 };
+                for element in mesh.range_indices(range).chunks_exact(size) {
+                    let element: Vec<u32> = element.iter().map(|&v| self.resolve(v)).collect();
+                    // Lines whose ends were collapsed together are gone
+                    if size == 2 && self.corner_of[element[0] as usize] == self.corner_of[element[1] as usize] {
+                        continue;
+                    }
+                    for v in element {
+                        let idx = index(&mut out, v);
+                        out.indices.push(idx);
+                    }
+                }
+            }
+            if out.indices.len() > start {
+                out.ranges.push(DrawRange { start, count: out.indices.len() - start, ..range.clone() });
+            }
+        }
+        out
+    }
+}
+
+impl Mesh {
+    /// A simpler copy of the mesh, made by collapsing edges until it's down to `target_triangles`, or until the next
+    /// collapse would move the surface further than `max_error` away from where it was
+    /// Pass `f32::INFINITY` as `max_error` to only stop at the triangle count, or 0 as the count to only stop at the error
+    /// Borders and UV seams are kept where they are, and lines and points are carried over
+    pub fn simplify(&self, target_triangles: usize, max_error: f32) -> Mesh {
+        let mut s = Simplifier::new(self);
+        let max_cost = (max_error as f64) * (max_error as f64);
+
+        let mut heap = BinaryHeap::new();
+        for corner in 0..s.corners.len() as u32 {
+            for other in s.neighbours(corner).into_iter().filter(|&other| other > corner) {
+                heap.extend(s.best_collapse(corner, other));
+            }
+        }
+
+        while s.alive > target_triangles {
+            let Some(next) = heap.pop() else { break };
+            if next.versions != (s.versions[next.from as usize], s.versions[next.to as usize]) {
+                continue;
+            }
+            if next.cost > max_cost {
+                break;
+            }
+            if !s.can_collapse(next.from, next.to) {
+                continue;
+            }
+            s.collapse(next.from, next.to);
+            for other in s.neighbours(next.to) {
+                heap.extend(s.best_collapse(next.to, other));
+            }
+        }
+        s.finish()
+    }
+
+    /// Simpler and simpler copies of the mesh, each with about a quarter of the triangles of the one before
+    /// Stops early once a copy can't get much simpler without tearing a border or losing its shape
+    pub fn lod_chain(&self, levels: usize) -> Vec<Mesh> {
+        let Some(bounds) = self.bounds() else { return Vec::new() };
+        let size = bounds.size();
+        let max_error = (size[0] * size[0] + size[1] * size[1] + size[2] * size[2]).sqrt() * LOD_MAX_ERROR;
+
+        let mut chain: Vec<Mesh> = Vec::new();
+        for _ in 0..levels {
+            let previous = chain.last().unwrap_or(self);
+            let triangles = previous.triangles().count();
+            let next = previous.simplify(triangles / 4, max_error);
+            if next.triangles().count() * 4 > triangles * 3 || next.triangles().count() == 0 {
+                break;
+            }
+            chain.push(next);
+        }
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{is_closed, volume};
+
+    #[test]
+    fn quadric_error_is_squared_distance() {
+        let plane = Quadric::plane(0.0, 1.0, 0.0, -2.0, 1.0);
+        assert_eq!(plane.error([5.0, 2.0, -3.0]), 0.0);
+        assert_eq!(plane.error([0.0, 5.0, 0.0]), 9.0);
+        let mut corner = plane;
+        corner.add(&Quadric::plane(1.0, 0.0, 0.0, 0.0, 1.0));
+        assert_eq!(corner.error([2.0, 0.0, 0.0]), 8.0);
+    }
+
+    #[test]
+    fn simplify_keeps_a_sphere_round() {
+        let sphere = Mesh::icosphere(1.0, 4);
+        let simple = sphere.simplify(500, f32::INFINITY);
+        let triangles = simple.triangles().count();
+        assert!(triangles <= 500 && triangles > 400, "went down to {triangles} triangles");
+        assert!(is_closed(&simple));
+        assert!((volume(&simple) / volume(&sphere) - 1.0).abs() < 0.05);
+        for p in &simple.positions {
+            assert!((length(*p) - 1.0).abs() < 0.05, "{p:?} strayed off the sphere");
+        }
+    }
+
+    #[test]
+    fn flat_areas_go_away_but_borders_stay() {
+        let plane = Mesh::plane(2.0, 2.0, 8, 8);
+        let simple = plane.simplify(0, 1e-4);
+        assert!(simple.triangles().count() < plane.triangles().count() / 4);
+        assert_eq!(simple.bounds().unwrap().size(), [2.0, 0.0, 2.0]);
+        // Every corner of the plane is still there
+        for corner in [[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, 1.0]] {
+            assert!(simple.positions.contains(&corner));
+        }
+        assert!(simple.triangles().all(|tri| face_normal(&tri.map(|v| simple.positions[v as usize])) == [0.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn max_error_stops_simplifying() {
+        let sphere = Mesh::icosphere(1.0, 3);
+        let rough = sphere.simplify(0, 0.1).triangles().count();
+        let close = sphere.simplify(0, 0.03).triangles().count();
+        assert!(rough < close && close < sphere.triangles().count());
+    }
+
+    #[test]
+    fn lod_chain_gets_simpler() {
+        let chain = Mesh::icosphere(1.0, 4).lod_chain(3);
+        assert_eq!(chain.len(), 3);
+        for pair in chain.windows(2) {
+            assert!(pair[1].triangles().count() < pair[0].triangles().count());
+        }
+    }
+}
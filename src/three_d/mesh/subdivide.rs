@@ -0,0 +1,489 @@
+use std::collections::HashSet;
+
+use super::*;
+use super::half_edge::weld_positions;
+
+// Both schemes work on points, the distinct positions in the mesh, so vertices split for different UVs or normals
+// still move together. Texture coordinates and colors are carried by wedges, a point with one set of attributes,
+// and are interpolated linearly, so UV seams stay where they are
+// Borders, edges with more than two faces, and edges sharper than the crease angle are creases: they're kept as
+// curves of their own, and the surface on either side doesn't pull on them
+// Points are welded the same way as a `HalfEdgeMesh`'s vertices, but the polygons keep their own map of the faces on
+// each edge. Every level after the first is made of new points and wedges that aren't vertices of any mesh, and a
+// `HalfEdgeMesh` can only be built over a mesh's vertices
+
+/// Which rules to subdivide with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubdivisionScheme {
+    /// Splits each triangle into four, best for triangle meshes
+    Loop,
+    /// Splits each face into quads, best for meshes made of quads
+    CatmullClark,
+}
+
+#[derive(Clone, Copy)]
+struct Wedge {
+    point: u32,
+    tex_coord: TexCoord,
+    color: Color,
+}
+
+struct Face {
+    wedges: Vec<u32>,
+    /// The range the face came from
+    range: usize,
+}
+
+/// A mesh being subdivided, made of polygons instead of triangles
+struct Polygons {
+    points: Vec<Vertex>,
+    wedges: Vec<Wedge>,
+    faces: Vec<Face>,
+    /// Edges that are creases, by their points with the lowest first
+    creases: HashSet<(u32, u32)>,
+}
+
+fn edge(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+fn lerp<const N: usize>(values: &[[f32; N]]) -> [f32; N] {
+    let mut sum = [0.0; N];
+    for v in values {
+        for (s, x) in sum.iter_mut().zip(v) {
+            *s += x / values.len() as f32;
+        }
+    }
+    sum
+}
+
+/// Adds up `points`, each multiplied by its weight
+fn weighted(points: &[(Vertex, f32)]) -> Vertex {
+    let mut sum = [0.0; 3];
+    for (p, w) in points {
+        for (s, x) in sum.iter_mut().zip(p) {
+            *s += x * w;
+        }
+    }
+    sum
+}
+
+/// The normal of a polygon, using Newell's method so faces that aren't quite flat still get a sensible one
+fn polygon_normal(points: impl Iterator<Item = Vertex> + Clone) -> Option<[f32; 3]> {
+    let next = points.clone().cycle().skip(1);
+    let mut n = [0.0; 3];
+    for (p, q) in points.zip(next) {
+        n[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        n[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        n[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    normalize(n)
+}
+
+impl Polygons {
+    /// Welds the mesh's triangles into polygons, pairing up triangles that were split from quads if `quads` is set
+    fn new(mesh: &Mesh, quads: bool, crease_angle: Option<f32>) -> Polygons {
+        let (points, point_of) = weld_positions(&mesh.positions);
+        let mut out = Polygons { points, wedges: Vec::new(), faces: Vec::new(), creases: HashSet::new() };
+        let mut wedge_ids = HashMap::new();
+        let wedge_of: Vec<u32> = (0..mesh.positions.len()).map(|i| {
+            let point = point_of[i];
+            let tex_coord = mesh.tex_coords.as_ref().map_or([0.0; 2], |t| t[i]);
+            let color = mesh.colors.as_ref().map_or([1.0; 3], |c| c[i]);
+            *wedge_ids.entry((point, tex_coord.map(f32::to_bits), color.map(f32::to_bits))).or_insert_with(|| {
+                out.wedges.push(Wedge { point, tex_coord, color });
+                out.wedges.len() as u32 - 1
+            })
+        }).collect();
+
+        for (range_idx, range) in mesh.ranges.iter().enumerate().filter(|(_, range)| range.primitive == Primitive::Triangles) {
+            let mut triangles = mesh.range_indices(range).chunks_exact(3)
+                .map(|tri| [0, 1, 2].map(|i| wedge_of[tri[i] as usize]))
+                .filter(|tri| {
+                    let [a, b, c] = tri.map(|w| out.wedges[w as usize].point);
+                    a != b && b != c && c != a
+                })
+                .peekable();
+            while let Some([a, b, c]) = triangles.next() {
+                // Quads come out of the OBJ importer and the primitives as (a, b, c) then (a, c, d)
+                let d = triangles.peek().filter(|next| quads && next[0] == a && next[1] == c).map(|next| next[2]);
+                let wedges = match d {
+                    Some(d) if !out.wedges_share_point(&[a, b, c], d) => {
+                        triangles.next();
+                        vec![a, b, c, d]
+                    },
+                    _ => vec![a, b, c],
+                };
+                out.faces.push(Face { wedges, range: range_idx });
+            }
+        }
+
+        // Borders and edges shared by more than two faces can't be smoothed across
+        let min_dot = crease_angle.map(f32::cos);
+        for (&(a, b), faces) in &out.edge_faces() {
+            let sharp = match faces[..] {
+                [f, g] => min_dot.is_some_and(|min_dot| {
+                    match (out.face_normal(f), out.face_normal(g)) {
+                        (Some(n), Some(m)) => n[0] * m[0] + n[1] * m[1] + n[2] * m[2] < min_dot,
+                        _ => false,
+                    }
+                }),
+                _ => true,
+            };
+            if sharp {
+                out.creases.insert((a, b));
+            }
+        }
+        out
+    }
+
+    fn wedges_share_point(&self, wedges: &[u32], other: u32) -> bool {
+        wedges.iter().any(|&w| self.wedges[w as usize].point == self.wedges[other as usize].point)
+    }
+
+    fn point_of(&self, wedge: u32) -> u32 {
+        self.wedges[wedge as usize].point
+    }
+
+    /// The edges of `face`, by point, in order
+    fn face_edges<'a>(&'a self, face: &'a Face) -> impl Iterator<Item = (u32, u32)> + 'a {
+        let n = face.wedges.len();
+        (0..n).map(move |i| (self.point_of(face.wedges[i]), self.point_of(face.wedges[(i + 1) % n])))
+    }
+
+    fn face_normal(&self, face: u32) -> Option<[f32; 3]> {
+        polygon_normal(self.faces[face as usize].wedges.iter().map(|&w| self.points[self.point_of(w) as usize]))
+    }
+
+    /// The faces on each edge
+    fn edge_faces(&self) -> HashMap<(u32, u32), Vec<u32>> {
+        let mut edges: HashMap<(u32, u32), Vec<u32>> = HashMap::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for (a, b) in self.face_edges(face) {
+                edges.entry(edge(a, b)).or_default().push(f as u32);
+            }
+        }
+        edges
+    }
+
+    /// One level of subdivision
+    fn subdivide(&self, scheme: SubdivisionScheme) -> Polygons {
+        let edge_faces = self.edge_faces();
+        let face_points: Vec<Vertex> = match scheme {
+            SubdivisionScheme::Loop => Vec::new(),
+            SubdivisionScheme::CatmullClark => self.faces.iter()
+                .map(|face| lerp(&face.wedges.iter().map(|&w| self.points[self.point_of(w) as usize]).collect::<Vec<_>>()))
+                .collect(),
+        };
+
+        // Edge points go after the points that are already there, in a fixed order so the output is the same every time
+        let mut edges: Vec<(u32, u32)> = edge_faces.keys().copied().collect();
+        edges.sort_unstable();
+        let edge_point: HashMap<(u32, u32), u32> = edges.iter().enumerate()
+            .map(|(i, &e)| (e, (self.points.len() + i) as u32))
+            .collect();
+
+        let mut points = self.move_points(scheme, &edge_faces, &face_points);
+        for &(a, b) in &edges {
+            let (pa, pb) = (self.points[a as usize], self.points[b as usize]);
+            let faces = &edge_faces[&(a, b)];
+            let p = if self.creases.contains(&(a, b)) || faces.len() != 2 {
+                weighted(&[(pa, 0.5), (pb, 0.5)])
+            } else {
+                match scheme {
+                    SubdivisionScheme::Loop => {
+                        // The corners across from the edge in each triangle
+                        let opposite = faces.iter().filter_map(|&f| {
+                            self.faces[f as usize].wedges.iter().map(|&w| self.point_of(w)).find(|&p| p != a && p != b)
+                        });
+                        let mut weights = vec![(pa, 3.0 / 8.0), (pb, 3.0 / 8.0)];
+                        weights.extend(opposite.map(|p| (self.points[p as usize], 1.0 / 8.0)));
+                        weighted(&weights)
+                    },
+                    SubdivisionScheme::CatmullClark => {
+                        let (fa, fb) = (face_points[faces[0] as usize], face_points[faces[1] as usize]);
+                        weighted(&[(pa, 0.25), (pb, 0.25), (fa, 0.25), (fb, 0.25)])
+                    },
+                }
+            };
+            points.push(p);
+        }
+        points.extend(&face_points);
+        let face_point_start = (self.points.len() + edges.len()) as u32;
+
+        // Wedges at the old points keep their ids, since the old points keep theirs
+        let mut wedges = self.wedges.clone();
+        let mut edge_wedges = HashMap::new();
+        let mut edge_wedge = |wedges: &mut Vec<Wedge>, a: u32, b: u32| *edge_wedges.entry(edge(a, b)).or_insert_with(|| {
+            let (wa, wb) = (self.wedges[a as usize], self.wedges[b as usize]);
+            wedges.push(Wedge {
+                point: edge_point[&edge(wa.point, wb.point)],
+                tex_coord: lerp(&[wa.tex_coord, wb.tex_coord]),
+                color: lerp(&[wa.color, wb.color]),
+            });
+            wedges.len() as u32 - 1
+        });
+
+        let mut faces = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            let n = face.wedges.len();
+            let mids: Vec<u32> = (0..n).map(|i| edge_wedge(&mut wedges, face.wedges[i], face.wedges[(i + 1) % n])).collect();
+            let range = face.range;
+            match scheme {
+                SubdivisionScheme::Loop => {
+                    let [a, b, c] = [0, 1, 2].map(|i| face.wedges[i]);
+                    let [ab, bc, ca] = [0, 1, 2].map(|i| mids[i]);
+                    for wedges in [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]] {
+                        faces.push(Face { wedges: wedges.to_vec(), range });
+                    }
+                },
+                SubdivisionScheme::CatmullClark => {
+                    let corners: Vec<Wedge> = face.wedges.iter().map(|&w| self.wedges[w as usize]).collect();
+                    wedges.push(Wedge {
+                        point: face_point_start + f as u32,
+                        tex_coord: lerp(&corners.iter().map(|w| w.tex_coord).collect::<Vec<_>>()),
+                        color: lerp(&corners.iter().map(|w| w.color).collect::<Vec<_>>()),
+                    });
+                    let center = wedges.len() as u32 - 1;
+                    for i in 0..n {
+                        faces.push(Face { wedges: vec![face.wedges[i], mids[i], center, mids[(i + n - 1) % n]], range });
+                    }
+                },
+            }
+        }
+
+        // Each half of a crease is still a crease
+        let creases = self.creases.iter().flat_map(|&(a, b)| {
+            let mid = edge_point[&(a, b)];
+            [edge(a, mid), edge(mid, b)]
+        }).collect();
+
+        Polygons { points, wedges, faces, creases }
+    }
+
+    /// Where each point moves to, from the points around it
+    fn move_points(&self, scheme: SubdivisionScheme, edge_faces: &HashMap<(u32, u32), Vec<u32>>, face_points: &[Vertex]) -> Vec<Vertex> {
+        let mut neighbours = vec![Vec::new(); self.points.len()];
+        for &(a, b) in edge_faces.keys() {
+            neighbours[a as usize].push(b);
+            neighbours[b as usize].push(a);
+        }
+        let mut faces_around = vec![Vec::new(); self.points.len()];
+        if scheme == SubdivisionScheme::CatmullClark {
+            for (f, face) in self.faces.iter().enumerate() {
+                for &w in &face.wedges {
+                    faces_around[self.point_of(w) as usize].push(f);
+                }
+            }
+        }
+
+        self.points.iter().enumerate().map(|(i, &p)| {
+            let around = &neighbours[i];
+            let sharp: Vec<u32> = around.iter().copied().filter(|&other| self.creases.contains(&edge(i as u32, other))).collect();
+            let n = around.len() as f32;
+            match sharp[..] {
+                // A point with a single crease through it is a dart, which is smooth
+                [] | [_] if !around.is_empty() => match scheme {
+                    SubdivisionScheme::Loop => {
+                        let beta = if around.len() == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n) };
+                        let mut weights = vec![(p, 1.0 - n * beta)];
+                        weights.extend(around.iter().map(|&q| (self.points[q as usize], beta)));
+                        weighted(&weights)
+                    },
+                    SubdivisionScheme::CatmullClark => {
+                        let faces = &faces_around[i];
+                        let f = lerp(&faces.iter().map(|&f| face_points[f]).collect::<Vec<_>>());
+                        let r = lerp(&around.iter().map(|&q| weighted(&[(p, 0.5), (self.points[q as usize], 0.5)])).collect::<Vec<_>>());
+                        weighted(&[(f, 1.0 / n), (r, 2.0 / n), (p, (n - 3.0) / n)])
+                    },
+                },
+                // Points along a crease only follow the crease
+                [a, b] => weighted(&[(p, 0.75), (self.points[a as usize], 0.125), (self.points[b as usize], 0.125)]),
+                // Where creases meet is a corner, which stays put
+                _ => p,
+            }
+        }).collect()
+    }
+
+    /// Triangulates the polygons back into a mesh, with normals that are smooth except across creases
+    /// Lines and points are copied over from `original` as they were
+    fn into_mesh(self, original: &Mesh) -> Mesh {
+        let mut out = Mesh {
+            colors: original.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: original.tex_coords.as_ref().map(|_| Vec::new()),
+            ..Mesh::default()
+        };
+
+        let triangles: Vec<([u32; 3], usize)> = self.faces.iter()
+            .flat_map(|face| (2..face.wedges.len()).map(move |i| ([face.wedges[0], face.wedges[i - 1], face.wedges[i]], face.range)))
+            .collect();
+        // Unnormalized, so bigger triangles count for more
+        let face_normals: Vec<[f32; 3]> = triangles.iter().map(|(tri, _)| {
+            area_normal(&tri.map(|w| self.points[self.point_of(w) as usize]))
+        }).collect();
+        let mut around = vec![Vec::new(); self.points.len()];
+        for (t, (tri, _)) in triangles.iter().enumerate() {
+            for &w in tri {
+                around[self.point_of(w) as usize].push(t);
+            }
+        }
+
+        // Triangles on the other side of a crease from a corner don't count towards its normal
+        let side_of = |t: usize, point: u32| -> Vec<usize> {
+            let mut side = vec![t];
+            let mut i = 0;
+            while i < side.len() {
+                let [a, b, c] = triangles[side[i]].0.map(|w| self.point_of(w));
+                for (x, y) in [(a, b), (b, c), (c, a)] {
+                    if (x != point && y != point) || self.creases.contains(&edge(x, y)) {
+                        continue;
+                    }
+                    let other = if x == point { y } else { x };
+                    for &u in &around[point as usize] {
+                        if !side.contains(&u) && triangles[u].0.iter().any(|&w| self.point_of(w) == other) {
+                            side.push(u);
+                        }
+                    }
+                }
+                i += 1;
+            }
+            side
+        };
+
+        let mut vertices = HashMap::new();
+        let mut sides: HashMap<(u32, usize), [f32; 3]> = HashMap::new();
+        for (i, range) in original.ranges.iter().enumerate() {
+            let start = out.indices.len();
+            if range.primitive == Primitive::Triangles {
+                for (t, (tri, _)) in triangles.iter().enumerate().filter(|(_, (_, r))| *r == i) {
+                    for &w in tri {
+                        let point = self.point_of(w);
+                        // Corners on the same side of every crease share a normal, so work each side out once
+                        let normal = match sides.get(&(point, t)) {
+                            Some(&normal) => normal,
+                            None => {
+                                let side = side_of(t, point);
+                                let sum = side.iter().fold([0.0; 3], |s, &u| [s[0] + face_normals[u][0], s[1] + face_normals[u][1], s[2] + face_normals[u][2]]);
+                                let normal = normalize(sum).unwrap_or([0.0; 3]);
+                                for &u in &side {
+                                    sides.insert((point, u), normal);
+                                }
+                                normal
+                            },
+                        };
+                        let wedge = self.wedges[w as usize];
+                        let idx = *vertices.entry((w, normal.map(f32::to_bits))).or_insert_with(|| {
+                            out.positions.push(self.points[point as usize]);
+                            out.normals.push(normal);
+                            if let Some(colors) = out.colors.as_mut() {
+                                colors.push(wedge.color);
+                            }
+                            if let Some(tex_coords) = out.tex_coords.as_mut() {
+                                tex_coords.push(wedge.tex_coord);
+                            }
+                            out.positions.len() as u32 - 1
+                        });
+                        out.indices.push(idx);
+                    }
+                }
+            } else {
+                for &v in original.range_indices(range) {
+                    let idx = out.copy_vertex(original, v);
+                    out.indices.push(idx);
+                }
+            }
+            if out.indices.len() > start {
+                out.ranges.push(DrawRange { start, count: out.indices.len() - start, ..range.clone() });
+            }
+        }
+        out
+    }
+}
+
+impl Mesh {
+    /// Smooths the mesh by subdividing it `levels` times, using Catmull-Clark if it's mostly quads and Loop if not
+    /// Edges where the faces meet at more than `crease_angle` (in radians) stay sharp, as do borders
+    pub fn subdivide(&self, levels: usize, crease_angle: Option<f32>) -> Mesh {
+        let triangles = self.triangles().count();
+        let quads = Polygons::new(self, true, None).faces.iter().filter(|face| face.wedges.len() == 4).count();
+        let scheme = if quads * 4 >= triangles { SubdivisionScheme::CatmullClark } else { SubdivisionScheme::Loop };
+        self.subdivide_with(scheme, levels, crease_angle)
+    }
+
+    /// Like `subdivide`, but with the scheme picked by hand
+    /// Catmull-Clark turns the triangles that were split from quads back into quads first, the rest stay triangles
+    /// The normals are worked out again, and tangents are dropped since they'd have to be too
+    pub fn subdivide_with(&self, scheme: SubdivisionScheme, levels: usize, crease_angle: Option<f32>) -> Mesh {
+        if levels == 0 {
+            return self.clone();
+        }
+        let mut polygons = Polygons::new(self, scheme == SubdivisionScheme::CatmullClark, crease_angle);
+        for _ in 0..levels {
+            polygons = polygons.subdivide(scheme);
+        }
+        polygons.into_mesh(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{is_closed, volume};
+
+    #[test]
+    fn catmull_clark_rounds_off_a_cube() {
+        let cube = Mesh::cube(1.0, 1);
+        // The corner goes to (F + 2R + (n - 3)P) / n, from the faces and edges around it, which is 5/18 out
+        let once = cube.subdivide_with(SubdivisionScheme::CatmullClark, 1, None);
+        for p in [[5.0 / 18.0; 3], [0.375, 0.375, 0.0], [0.5, 0.0, 0.0]] {
+            assert!(once.positions.iter().any(|&q| length(sub(p, q)) < 1e-6), "nothing ended up at {p:?}");
+        }
+
+        let smooth = cube.subdivide_with(SubdivisionScheme::CatmullClark, 2, None);
+        // Each quad splits into 4 each time, and comes back out as 2 triangles
+        assert_eq!(smooth.triangles().count(), 6 * 16 * 2);
+        assert!(is_closed(&smooth));
+        assert!(volume(&smooth) > 0.0 && volume(&smooth) < volume(&once));
+        assert_eq!(cube.subdivide(2, None).triangles().count(), smooth.triangles().count());
+    }
+
+    #[test]
+    fn loop_smooths_an_icosahedron() {
+        let ico = Mesh::icosphere(1.0, 0);
+        let once = ico.subdivide_with(SubdivisionScheme::Loop, 1, None);
+        // Warren's weights for the old points, 3/8n to each of the n neighbours, which are all 1/sqrt(5) along
+        let beta = 3.0 / 40.0;
+        let radius = 1.0 - 5.0 * beta + 5.0 * beta / 5f32.sqrt();
+        for p in &ico.positions {
+            assert!(once.positions.iter().any(|&q| length(sub(p.map(|x| x * radius), q)) < 1e-5), "{p:?} didn't move in");
+        }
+
+        let smooth = ico.subdivide_with(SubdivisionScheme::Loop, 2, None);
+        assert_eq!(smooth.triangles().count(), 20 * 16);
+        assert!(is_closed(&smooth));
+        assert!(volume(&smooth) > 0.0 && volume(&smooth) < volume(&ico));
+        assert_eq!(ico.subdivide(1, None).triangles().count(), 20 * 4);
+    }
+
+    #[test]
+    fn creases_keep_a_cube_square() {
+        let cube = Mesh::cube(1.0, 1);
+        for scheme in [SubdivisionScheme::CatmullClark, SubdivisionScheme::Loop] {
+            let sharp = cube.subdivide_with(scheme, 2, Some(0.5));
+            assert!(is_closed(&sharp));
+            assert!((volume(&sharp) - 1.0).abs() < 1e-4, "{scheme:?} changed the volume to {}", volume(&sharp));
+            // The sides are flat, so every normal is one of the cube's
+            assert!(sharp.normals.iter().all(|n| n.iter().filter(|x| x.abs() > 0.999).count() == 1));
+        }
+    }
+
+    #[test]
+    fn borders_stay_in_place() {
+        let plane = Mesh::plane(2.0, 2.0, 2, 2);
+        let smooth = plane.subdivide(2, None);
+        assert_eq!(smooth.triangles().count(), plane.triangles().count() * 16);
+        assert!(smooth.positions.iter().all(|p| p[1] == 0.0));
+        assert_eq!(smooth.bounds().unwrap().size(), [2.0, 0.0, 2.0]);
+        assert_eq!(HalfEdgeMesh::from_mesh(&smooth).border_loops().len(), 1);
+    }
+}
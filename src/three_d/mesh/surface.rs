@@ -0,0 +1,357 @@
+use super::*;
+
+// Any surface that maps (u, v) in the unit square to a point can be turned into triangles by sampling it on a grid
+// Normals come from how the point moves as u and v change, and the texture coordinates are just (u, v)
+// Bezier and NURBS patches are surfaces like that, so they go through the same tessellation as any other
+
+/// How many samples along each way to look at when working out how finely a surface needs to be split
+const CURVATURE_SAMPLES: u32 = 16;
+/// The most segments adaptive resolution will use along either way
+const MAX_SEGMENTS: u32 = 512;
+/// How far apart, in u and v, the points used to work out normals are
+const NORMAL_STEP: f32 = 1e-3;
+
+/// A surface given by a point for each (u, v), both from 0 to 1
+/// Normals face the way of the cross product of the directions it goes in along u and along v
+pub trait Surface {
+    fn point(&self, u: f32, v: f32) -> Vertex;
+}
+
+impl<F: Fn(f32, f32) -> Vertex> Surface for F {
+    fn point(&self, u: f32, v: f32) -> Vertex {
+        self(u, v)
+    }
+}
+
+/// How finely to split a surface into triangles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    /// This many segments along u, and this many along v
+    Fixed(u32, u32),
+    /// As many segments as it takes for the triangles to stay within this distance of the surface, judged from
+    /// how sharply it curves each way
+    Adaptive(f32),
+}
+
+/// A Bezier patch of any degree, from a grid of control points
+#[derive(Clone, Debug, PartialEq)]
+pub struct BezierPatch {
+    /// The control points, in rows going along u, with `width` to a row
+    pub points: Vec<Vertex>,
+    pub width: usize,
+}
+
+impl BezierPatch {
+    /// `points` has to be a whole number of rows, each `width` points long
+    pub fn new(points: Vec<Vertex>, width: usize) -> BezierPatch {
+        assert!(width > 0 && !points.is_empty() && points.len().is_multiple_of(width), "a patch needs whole rows of control points");
+        BezierPatch { points, width }
+    }
+
+    /// The usual 4 by 4 patch, with `points[v][u]`, like the ones the Utah teapot was first made of
+    pub fn bicubic(points: [[Vertex; 4]; 4]) -> BezierPatch {
+        BezierPatch::new(points.concat(), 4)
+    }
+}
+
+impl Surface for BezierPatch {
+    fn point(&self, u: f32, v: f32) -> Vertex {
+        let column: Vec<Vertex> = self.points.chunks_exact(self.width).map(|row| de_casteljau(row, u)).collect();
+        de_casteljau(&column, v)
+    }
+}
+
+/// A NURBS patch, which is a B-spline patch when every weight is 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct NurbsPatch {
+    /// The control points, in rows going along u, with `width` to a row
+    pub points: Vec<Vertex>,
+    /// How strongly each control point pulls the surface towards it
+    pub weights: Vec<f32>,
+    pub width: usize,
+    pub u_degree: usize,
+    pub v_degree: usize,
+    /// Non-decreasing, with as many knots as there are control points along u plus `u_degree + 1`
+    pub u_knots: Vec<f32>,
+    pub v_knots: Vec<f32>,
+}
+
+impl NurbsPatch {
+    /// A patch with clamped uniform knots, so it runs right up to the control points at its corners
+    /// `points` has to be a whole number of rows, each `width` points long, with a weight for each point
+    /// The degrees are lowered if there aren't enough control points for them
+    pub fn new(points: Vec<Vertex>, weights: Vec<f32>, width: usize, u_degree: usize, v_degree: usize) -> NurbsPatch {
+        assert!(width > 0 && !points.is_empty() && points.len().is_multiple_of(width), "a patch needs whole rows of control points");
+        assert_eq!(points.len(), weights.len(), "every control point needs a weight");
+        let height = points.len() / width;
+        let (u_degree, v_degree) = (u_degree.min(width - 1), v_degree.min(height - 1));
+        NurbsPatch {
+            u_knots: clamped_knots(width, u_degree),
+            v_knots: clamped_knots(height, v_degree),
+            points, weights, width, u_degree, v_degree,
+        }
+    }
+
+    /// A B-spline patch, with every weight 1
+    pub fn b_spline(points: Vec<Vertex>, width: usize, u_degree: usize, v_degree: usize) -> NurbsPatch {
+        let weights = vec![1.0; points.len()];
+        NurbsPatch::new(points, weights, width, u_degree, v_degree)
+    }
+}
+
+impl Surface for NurbsPatch {
+    fn point(&self, u: f32, v: f32) -> Vertex {
+        let height = self.points.len() / self.width;
+        let (u_span, u_basis) = basis(&self.u_knots, self.u_degree, self.width, u);
+        let (v_span, v_basis) = basis(&self.v_knots, self.v_degree, height, v);
+
+        let (mut sum, mut total) = ([0.0; 3], 0.0);
+        for (j, nv) in v_basis.iter().enumerate() {
+            for (i, nu) in u_basis.iter().enumerate() {
+                let idx = (v_span - self.v_degree + j) * self.width + u_span - self.u_degree + i;
+                let w = nu * nv * self.weights[idx];
+                let p = self.points[idx];
+                for k in 0..3 {
+                    sum[k] += p[k] * w;
+                }
+                total += w;
+            }
+        }
+        if total != 0.0 { sum.map(|x| x / total) } else { sum }
+    }
+}
+
+/// Evaluates the Bezier curve with these control points
+fn de_casteljau(points: &[Vertex], t: f32) -> Vertex {
+    let mut points = points.to_vec();
+    for n in (1..points.len()).rev() {
+        for i in 0..n {
+            let (a, b) = (points[i], points[i + 1]);
+            points[i] = [0, 1, 2].map(|k| a[k] + (b[k] - a[k]) * t);
+        }
+    }
+    points[0]
+}
+
+/// Knots that repeat `degree + 1` times at each end and are evenly spaced in between
+fn clamped_knots(count: usize, degree: usize) -> Vec<f32> {
+    let inner = count - degree;
+    (0..count + degree + 1).map(|i| (i.saturating_sub(degree) as f32 / inner as f32).min(1.0)).collect()
+}
+
+/// Finds which knot span `t` (from 0 to 1 across the whole curve) is in, and the B-spline basis functions that
+/// aren't zero there, using the Cox-de Boor recursion the way Piegl and Tiller lay it out in The NURBS Book
+fn basis(knots: &[f32], degree: usize, count: usize, t: f32) -> (usize, Vec<f32>) {
+    let (start, end) = (knots[degree], knots[count]);
+    let t = start + (end - start) * t.clamp(0.0, 1.0);
+    // The last span includes its end, so the far edge of the patch lands on the last control points
+    let span = (degree..count).rev().find(|&i| knots[i] <= t && knots[i] < knots[i + 1]).unwrap_or(degree);
+
+    let mut n = vec![0.0; degree + 1];
+    let (mut left, mut right) = (vec![0.0; degree + 1], vec![0.0; degree + 1]);
+    n[0] = 1.0;
+    for j in 1..=degree {
+        left[j] = t - knots[span + 1 - j];
+        right[j] = knots[span + j] - t;
+        let mut saved = 0.0;
+        for r in 0..j {
+            let denominator = right[r + 1] + left[j - r];
+            let temp = if denominator != 0.0 { n[r] / denominator } else { 0.0 };
+            n[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        n[j] = saved;
+    }
+    (span, n)
+}
+
+/// The normal at (u, v), from the directions the surface goes along u and v there
+fn surface_normal(surface: &impl Surface, u: f32, v: f32) -> Normal {
+    let mut at = (u, v);
+    // Where the surface pinches to a point, like the top of a lid, one of the directions is zero, so the normal
+    // is taken from a little way in instead
+    for _ in 0..4 {
+        let (u, v) = at;
+        let along_u = sub(surface.point((u + NORMAL_STEP).min(1.0), v), surface.point((u - NORMAL_STEP).max(0.0), v));
+        let along_v = sub(surface.point(u, (v + NORMAL_STEP).min(1.0)), surface.point(u, (v - NORMAL_STEP).max(0.0)));
+        let n = cross(along_u, along_v);
+        let (len, scale) = (length(n), length(along_u).max(length(along_v)));
+        // Measured against how far the points moved, since rounding keeps a pinched direction from being exactly zero
+        if len > 1e-6 * scale * scale {
+            return n.map(|x| x / len);
+        }
+        at = (u + (0.5 - u) * 0.01, v + (0.5 - v) * 0.01);
+    }
+    [0.0; 3]
+}
+
+/// Snaps each edge of a grid of points that has shrunk down to a point onto exactly one point, like the poles of a
+/// sphere, so the triangles touching it have no area and get left out, instead of being slivers facing any which way
+fn pinch(points: &mut [Vertex], width: usize) {
+    let height = points.len() / width;
+    let Some(bounds) = Mesh { positions: points.to_vec(), ..Mesh::default() }.bounds() else { return };
+    let close = length(bounds.size()) * 1e-5;
+    let edges: [Vec<usize>; 4] = [
+        (0..width).collect(),
+        ((height - 1) * width..height * width).collect(),
+        (0..height).map(|row| row * width).collect(),
+        (0..height).map(|row| row * width + width - 1).collect(),
+    ];
+    for edge in edges {
+        let first = points[edge[0]];
+        if edge.iter().all(|&i| length(sub(points[i], first)) <= close) {
+            edge.into_iter().for_each(|i| points[i] = first);
+        }
+    }
+}
+
+/// How many segments along u and v keep a grid within `tolerance` of the surface
+/// Splitting a curve that bends with second derivative `d` into pieces `h` long strays about `d h^2 / 8` from it,
+/// and the middle of a grid cell can stray both ways at once, so each way gets half the tolerance
+fn adaptive_segments(surface: &impl Surface, tolerance: f32) -> (u32, u32) {
+    let n = CURVATURE_SAMPLES;
+    let h = 1.0 / n as f32;
+    let point = |i: u32, j: u32| surface.point(i as f32 * h, j as f32 * h);
+    let bend = |a: Vertex, b: Vertex, c: Vertex| length([0, 1, 2].map(|k| a[k] - 2.0 * b[k] + c[k])) / (h * h);
+    let (mut along_u, mut along_v) = (0.0f32, 0.0f32);
+    for j in 0..=n {
+        for i in 1..n {
+            along_u = along_u.max(bend(point(i - 1, j), point(i, j), point(i + 1, j)));
+            along_v = along_v.max(bend(point(j, i - 1), point(j, i), point(j, i + 1)));
+        }
+    }
+    let segments = |d: f32| ((d / (4.0 * tolerance.max(f32::EPSILON))).sqrt().ceil() as u32).clamp(1, MAX_SEGMENTS);
+    (segments(along_u), segments(along_v))
+}
+
+impl Mesh {
+    /// Splits a surface into triangles, with its normals and (u, v) as the texture coordinates
+    pub fn tessellate(surface: &impl Surface, resolution: Resolution) -> Mesh {
+        Mesh::tessellate_patches(std::slice::from_ref(surface), resolution)
+    }
+
+    /// Splits each patch into triangles, all in one mesh, with each one getting the whole texture
+    /// Every patch gets the same resolution, the finest any of them needs when it's adaptive, so the points along
+    /// edges that patches share line up and there aren't cracks between them
+    pub fn tessellate_patches<S: Surface>(patches: &[S], resolution: Resolution) -> Mesh {
+        let (cols, rows) = match resolution {
+            Resolution::Fixed(cols, rows) => (cols, rows),
+            Resolution::Adaptive(tolerance) => patches.iter()
+                .map(|patch| adaptive_segments(patch, tolerance))
+                .fold((1, 1), |(a, b), (c, d)| (a.max(c), b.max(d))),
+        };
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        let mut mesh = Mesh::primitive();
+        for patch in patches {
+            let mut points: Vec<Vertex> = (0..=rows)
+                .flat_map(|row| (0..=cols).map(move |col| patch.point(col as f32 / cols as f32, row as f32 / rows as f32)))
+                .collect();
+            pinch(&mut points, cols as usize + 1);
+            mesh.add_grid(cols, rows, |u, v| {
+                let (col, row) = ((u * cols as f32).round() as usize, (v * rows as f32).round() as usize);
+                (points[row * (cols as usize + 1) + col], surface_normal(patch, u, v), [u, v])
+            });
+        }
+        mesh.finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bumpy 4 by 4 grid of control points over the unit square
+    fn control_points() -> [[Vertex; 4]; 4] {
+        std::array::from_fn(|v| std::array::from_fn(|u| [u as f32 / 3.0, ((u * 3 + v * 5) % 4) as f32 * 0.2, v as f32 / 3.0]))
+    }
+
+    fn close(a: Vertex, b: Vertex) -> bool {
+        length(sub(a, b)) < 1e-5
+    }
+
+    fn samples() -> impl Iterator<Item = (f32, f32)> {
+        (0..=8).flat_map(|v| (0..=8).map(move |u| (u as f32 / 8.0, v as f32 / 8.0)))
+    }
+
+    #[test]
+    fn bezier_patch_runs_through_its_corners() {
+        let points = control_points();
+        let patch = BezierPatch::bicubic(points);
+        assert!(close(patch.point(0.0, 0.0), points[0][0]));
+        assert!(close(patch.point(1.0, 0.0), points[0][3]));
+        assert!(close(patch.point(0.0, 1.0), points[3][0]));
+        assert!(close(patch.point(1.0, 1.0), points[3][3]));
+    }
+
+    #[test]
+    fn clamped_cubic_b_spline_is_the_bicubic_bezier() {
+        let points = control_points();
+        let bezier = BezierPatch::bicubic(points);
+        let b_spline = NurbsPatch::b_spline(points.concat(), 4, 3, 3);
+        assert_eq!(b_spline.u_knots, vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        for (u, v) in samples() {
+            assert!(close(b_spline.point(u, v), bezier.point(u, v)), "they differ at ({u}, {v})");
+        }
+    }
+
+    #[test]
+    fn equal_weights_make_no_difference() {
+        let points = control_points().concat();
+        let b_spline = NurbsPatch::b_spline(points.clone(), 4, 2, 2);
+        let nurbs = NurbsPatch::new(points, vec![3.0; 16], 4, 2, 2);
+        for (u, v) in samples() {
+            assert!(close(nurbs.point(u, v), b_spline.point(u, v)), "they differ at ({u}, {v})");
+        }
+    }
+
+    #[test]
+    fn weights_pull_the_surface_in() {
+        let points = control_points().concat();
+        let mut weights = vec![1.0; 16];
+        weights[5] = 10.0;
+        let (plain, pulled) = (NurbsPatch::b_spline(points.clone(), 4, 3, 3), NurbsPatch::new(points.clone(), weights, 4, 3, 3));
+        let distance = |patch: &NurbsPatch| length(sub(patch.point(1.0 / 3.0, 1.0 / 3.0), points[5]));
+        assert!(distance(&pulled) < distance(&plain));
+    }
+
+    #[test]
+    fn fixed_resolution_makes_a_grid() {
+        let mesh = Mesh::tessellate(&BezierPatch::bicubic(control_points()), Resolution::Fixed(4, 3));
+        assert_eq!(mesh.triangles().count(), 4 * 3 * 2);
+        assert_eq!(mesh.positions.len(), 5 * 4);
+        let uv = mesh.tex_coords.as_ref().unwrap();
+        assert!(uv.contains(&[0.0, 0.0]) && uv.contains(&[1.0, 1.0]));
+    }
+
+    #[test]
+    fn adaptive_resolution_follows_curvature() {
+        let flat = |u: f32, v: f32| [u, 0.0, -v];
+        assert_eq!(Mesh::tessellate(&flat, Resolution::Adaptive(0.01)).triangles().count(), 2);
+
+        let curved = |u: f32, v: f32| [u, u * u, -v];
+        let coarse = Mesh::tessellate(&curved, Resolution::Adaptive(0.01)).triangles().count();
+        let fine = Mesh::tessellate(&curved, Resolution::Adaptive(0.0001)).triangles().count();
+        assert!(coarse > 2 && fine > coarse);
+        // The surface faces the way of the cross product of its u and v directions, which is up for the flat one
+        let mesh = Mesh::tessellate(&flat, Resolution::Fixed(1, 1));
+        assert!(mesh.normals.iter().all(|n| close(*n, [0.0, 1.0, 0.0])));
+    }
+
+    #[test]
+    fn sphere_patches_come_out_closed() {
+        let sphere = |u: f32, v: f32| {
+            // Wrapped so the seam lines up exactly
+            let (theta, phi) = ((u % 1.0) * std::f32::consts::TAU, (v - 0.5) * std::f32::consts::PI);
+            [phi.cos() * theta.cos(), phi.sin(), -phi.cos() * theta.sin()]
+        };
+        let mut mesh = Mesh::tessellate(&sphere, Resolution::Fixed(24, 12));
+        mesh.drop_degenerates();
+        let half_edges = HalfEdgeMesh::from_mesh(&mesh);
+        assert!(half_edges.border_loops().is_empty());
+        // The normals at the poles come from just off them, so they still point out
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!(dot(*p, *n) > 0.9, "{n:?} doesn't point away from {p:?}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+// Tangents follow MikkTSpace's conventions, which is what normal maps are baked against by Blender, Substance and
+// glTF: each triangle's UV directions are projected onto the vertex normal, weighted by the angle of the corner,
+// and vertices where mirrored UVs meet are split so each side keeps its own handedness
+
+/// How far apart the corners' UVs have to be before a triangle tells us anything about the tangents
+const MIN_UV_AREA: f32 = 1e-12;
+
+/// `v` with the part along `normal` taken out
+fn project(v: [f32; 3], normal: [f32; 3]) -> [f32; 3] {
+    let d = dot(v, normal);
+    [v[0] - normal[0] * d, v[1] - normal[1] * d, v[2] - normal[2] * d]
+}
+
+/// Some direction along the surface, for vertices whose UVs don't give one
+fn any_tangent(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    normalize(project(axis, normal)).unwrap_or([1.0, 0.0, 0.0])
+}
+
+impl Mesh {
+    /// Works out a tangent for every vertex from the positions, normals and texture coordinates
+    /// Vertices shared between triangles with mirrored UVs get split in two, so this can add vertices
+    /// Meshes without texture coordinates have nothing to work tangents out from, so they're left without any
+    pub fn compute_tangents(&mut self) {
+        let Some(tex_coords) = self.tex_coords.as_ref() else {
+            self.tangents = None;
+            return;
+        };
+
+        // Sums of the projected tangents around each vertex, kept apart for each handedness
+        let count = self.positions.len();
+        let mut sums = [vec![[0.0f32; 3]; count], vec![[0.0f32; 3]; count]];
+        let mut used = [vec![false; count], vec![false; count]];
+        // Where each corner is in the index buffer, and which handedness its triangle has
+        let mut corners = Vec::new();
+
+        for range in self.ranges.iter().filter(|range| range.primitive == Primitive::Triangles) {
+            for start in (range.start..range.start + range.count).step_by(3).filter(|i| i + 3 <= range.start + range.count) {
+                let tri = [0, 1, 2].map(|i| self.indices[start + i] as usize);
+                let p = tri.map(|v| self.positions[v]);
+                let uv = tri.map(|v| tex_coords[v]);
+
+                let (e1, e2) = (sub(p[1], p[0]), sub(p[2], p[0]));
+                let (du1, dv1, du2, dv2) = (uv[1][0] - uv[0][0], uv[1][1] - uv[0][1], uv[2][0] - uv[0][0], uv[2][1] - uv[0][1]);
+                let det = du1 * dv2 - du2 * dv1;
+                let (tangent, bitangent) = if det.abs() > MIN_UV_AREA {
+                    ([0, 1, 2].map(|i| (e1[i] * dv2 - e2[i] * dv1) / det), [0, 1, 2].map(|i| (e2[i] * du1 - e1[i] * du2) / det))
+                } else {
+                    ([0.0; 3], [0.0; 3])
+                };
+
+                for (corner, &v) in tri.iter().enumerate() {
+                    let normal = self.normals[v];
+                    let flipped = dot(cross(normal, tangent), bitangent) < 0.0;
+                    let side = flipped as usize;
+                    corners.push((start + corner, side));
+                    used[side][v] = true;
+
+                    let (to_next, to_prev) = (sub(p[(corner + 1) % 3], p[corner]), sub(p[(corner + 2) % 3], p[corner]));
+                    let (Some(projected), Some(a), Some(b)) = (normalize(project(tangent, normal)), normalize(to_next), normalize(to_prev)) else {
+                        continue;
+                    };
+                    let angle = dot(a, b).clamp(-1.0, 1.0).acos();
+                    for (sum, x) in sums[side][v].iter_mut().zip(projected) {
+                        *sum += x * angle;
+                    }
+                }
+            }
+        }
+
+        let mut tangents: Vec<Tangent> = (0..count).map(|v| {
+            let side = if used[0][v] || !used[1][v] { 0 } else { 1 };
+            let t = normalize(project(sums[side][v], self.normals[v])).unwrap_or_else(|| any_tangent(self.normals[v]));
+            [t[0], t[1], t[2], if side == 0 { 1.0 } else { -1.0 }]
+        }).collect();
+        self.tangents = None;
+
+        // Vertices used by both sides keep the right handed one, and the left handed triangles get a copy
+        let mut copies = vec![None; count];
+        for (position, side) in corners {
+            let v = self.indices[position] as usize;
+            if side == 0 || !used[0][v] {
+                continue;
+            }
+            let copy = *copies[v].get_or_insert_with(|| {
+                let t = normalize(project(sums[1][v], self.normals[v])).unwrap_or_else(|| any_tangent(self.normals[v]));
+                tangents.push([t[0], t[1], t[2], -1.0]);
+                self.duplicate_vertex(v as u32)
+            });
+            self.indices[position] = copy;
+        }
+        self.tangents = Some(tangents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tangents_follow_u() {
+        let mut mesh = Mesh::plane(2.0, 1.0, 2, 2);
+        mesh.compute_tangents();
+        for t in mesh.tangents.as_ref().unwrap() {
+            assert!(length(sub([t[0], t[1], t[2]], [1.0, 0.0, 0.0])) < 1e-5, "{t:?} doesn't go along +X");
+            assert_eq!(t[3].abs(), 1.0);
+        }
+    }
+
+    #[test]
+    fn tangents_are_unit_length_and_flat_to_the_normal() {
+        let mut mesh = Mesh::uv_sphere(1.0, 16, 8);
+        mesh.compute_tangents();
+        for (t, n) in mesh.tangents.as_ref().unwrap().iter().zip(&mesh.normals) {
+            let t = [t[0], t[1], t[2]];
+            assert!((length(t) - 1.0).abs() < 1e-4);
+            assert!(dot(t, *n).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn mirrored_uvs_split_vertices() {
+        // Two triangles sharing an edge, with the texture mirrored across it
+        let mut mesh = Mesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [-1.0, 0.0, 0.0]],
+            normals: vec![[0.0, 1.0, 0.0]; 4],
+            tex_coords: Some(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Mesh::default()
+        };
+        mesh.ranges.push(DrawRange { primitive: Primitive::Triangles, start: 0, count: 6, group: None });
+        mesh.compute_tangents();
+        assert_eq!(mesh.positions.len(), 6);
+        let tangents = mesh.tangents.as_ref().unwrap();
+        for tri in mesh.triangles() {
+            assert!(tri.iter().all(|&v| tangents[v as usize][3] == tangents[tri[0] as usize][3]));
+        }
+
+        mesh.tex_coords = None;
+        mesh.compute_tangents();
+        assert!(mesh.tangents.is_none());
+    }
+}
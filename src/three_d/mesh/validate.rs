@@ -0,0 +1,399 @@
+use super::*;
+
+// Edges come from a `HalfEdgeMesh`, so they're compared by position, not by vertex, and a UV seam or a hard edge (where
+// the vertices are split but the surface carries on) doesn't look like a border. Vertices are reported by the first
+// vertex at their position
+
+/// Everything `Mesh::validate` found wrong with a mesh
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Validation {
+    /// Ranges that go past the end of the indices, by their place in `ranges`
+    pub bad_ranges: Vec<usize>,
+    /// Places in `indices` that point past the end of the vertices
+    pub out_of_range_indices: Vec<usize>,
+    /// Vertices whose position is NaN or infinite
+    pub nan_positions: Vec<u32>,
+    /// Vertices whose normal has no length, or is NaN or infinite
+    pub zero_normals: Vec<u32>,
+    /// Triangles without any area, by where they start in `indices`
+    pub degenerate_triangles: Vec<usize>,
+    /// Edges with more than two triangles on them
+    pub non_manifold_edges: Vec<(u32, u32)>,
+    /// Edges whose two triangles both go around them the same way, so one of them faces backwards
+    pub inconsistent_edges: Vec<(u32, u32)>,
+    /// Vertices that are exact copies of an earlier one
+    pub duplicate_vertices: Vec<u32>,
+}
+
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        *self == Validation::default()
+    }
+
+    /// A line for each kind of problem found, saying how many there were
+    pub fn problems(&self) -> Vec<String> {
+        [
+            (self.bad_ranges.len(), "ranges going past the end of the indices"),
+            (self.out_of_range_indices.len(), "indices past the end of the vertices"),
+            (self.nan_positions.len(), "vertices with NaN or infinite positions"),
+            (self.zero_normals.len(), "vertices without a usable normal"),
+            (self.degenerate_triangles.len(), "triangles without any area"),
+            (self.non_manifold_edges.len(), "edges shared by more than two triangles"),
+            (self.inconsistent_edges.len(), "edges between triangles wound different ways"),
+            (self.duplicate_vertices.len(), "duplicate vertices"),
+        ].into_iter().filter(|(count, _)| *count > 0).map(|(count, what)| format!("{count} {what}")).collect()
+    }
+}
+
+/// How many indices make up one element of `primitive`
+fn element_size(primitive: Primitive) -> usize {
+    match primitive {
+        Primitive::Triangles => 3,
+        Primitive::Lines => 2,
+        Primitive::Points => 1,
+    }
+}
+
+impl Mesh {
+    /// Whether every index of a triangle points at a vertex with a real position
+    fn usable(&self, tri: &[u32]) -> bool {
+        tri.iter().all(|&v| self.positions.get(v as usize).is_some_and(|p| p.iter().all(|x| x.is_finite())))
+    }
+
+    /// The triangles whose ranges and indices are all in bounds, with where each starts in `indices`
+    fn checked_triangles(&self) -> impl Iterator<Item = (usize, [u32; 3])> + '_ {
+        self.ranges.iter()
+            .filter(|range| range.primitive == Primitive::Triangles && range.start + range.count <= self.indices.len())
+            .flat_map(|range| (range.start..range.start + range.count - range.count % 3).step_by(3))
+            .map(|start| (start, [self.indices[start], self.indices[start + 1], self.indices[start + 2]]))
+            .filter(|(_, tri)| self.usable(tri))
+    }
+
+    /// Looks for anything that would make the mesh draw wrong, or trip up the other mesh operations
+    pub fn validate(&self) -> Validation {
+        let mut out = Validation::default();
+        let vertices = self.positions.len();
+
+        for (i, range) in self.ranges.iter().enumerate() {
+            if range.start + range.count > self.indices.len() {
+                out.bad_ranges.push(i);
+                continue;
+            }
+            out.out_of_range_indices.extend((range.start..range.start + range.count).filter(|&i| self.indices[i] as usize >= vertices));
+        }
+        out.nan_positions = (0..vertices as u32).filter(|&v| !self.positions[v as usize].iter().all(|x| x.is_finite())).collect();
+        out.zero_normals = self.normals.iter().enumerate()
+            .filter(|(_, n)| {
+                let len = n[0] * n[0] + n[1] * n[1] + n[2] * n[2];
+                !len.is_finite() || len == 0.0
+            })
+            .map(|(v, _)| v as u32)
+            .collect();
+
+        let mut triangles = Vec::new();
+        for (start, tri) in self.checked_triangles() {
+            let p = tri.map(|v| self.positions[v as usize]);
+            if cross(sub(p[1], p[0]), sub(p[2], p[0])) == [0.0; 3] {
+                out.degenerate_triangles.push(start);
+            } else {
+                triangles.push(tri.to_vec());
+            }
+        }
+        // Only the adjacency is used, so every face can say it's in the first range
+        let half_edges = HalfEdgeMesh::from_faces(self, triangles.into_iter().map(|tri| (tri, 0)));
+        let mut first = vec![u32::MAX; half_edges.vertices().len()];
+        for v in (0..vertices as u32).rev() {
+            first[half_edges.welded_vertex(v) as usize] = v;
+        }
+        for h in (0..half_edges.half_edges().len() as u32).filter(|&h| !half_edges.is_border(h)) {
+            let (a, b) = (half_edges.origin(h), half_edges.half_edges()[h as usize].vertex);
+            // Each edge is looked at from the first half-edge going its way, and from its lower vertex if both do
+            let (along, against) = (half_edges.edge_half_edges(a, b), half_edges.edge_half_edges(b, a));
+            if along[0] != h || (against.first().is_some_and(|&other| other < h)) {
+                continue;
+            }
+            let edge = (first[a as usize].min(first[b as usize]), first[a as usize].max(first[b as usize]));
+            match (along.len(), against.len()) {
+                (n, m) if n + m > 2 => out.non_manifold_edges.push(edge),
+                (2, 0) => out.inconsistent_edges.push(edge),
+                _ => {},
+            }
+        }
+        out.non_manifold_edges.sort_unstable();
+        out.inconsistent_edges.sort_unstable();
+
+        let mut seen = HashMap::new();
+        for v in 0..vertices as u32 {
+            if seen.insert(self.vertex_key(v), v).is_some() {
+                out.duplicate_vertices.push(v);
+            }
+        }
+        out
+    }
+
+    /// Every attribute of a vertex, exactly, for finding copies
+    fn vertex_key(&self, v: u32) -> Vec<u32> {
+        let i = v as usize;
+        let mut key: Vec<u32> = self.positions[i].iter().chain(self.normals.get(i).into_iter().flatten()).map(|x| x.to_bits()).collect();
+        key.extend(self.colors.iter().flat_map(|c| c[i]).map(f32::to_bits));
+        key.extend(self.tex_coords.iter().flat_map(|t| t[i]).map(f32::to_bits));
+        key.extend(self.tangents.iter().flat_map(|t| t[i]).map(f32::to_bits));
+        key
+    }
+
+    /// Rebuilds the indices and ranges with only the elements `keep` says to, giving back how many were dropped
+    fn retain_elements(&mut self, mut keep: impl FnMut(&Mesh, Primitive, &[u32]) -> bool) -> usize {
+        let indices = std::mem::take(&mut self.indices);
+        let ranges = std::mem::take(&mut self.ranges);
+        let (mut kept, mut dropped) = (Vec::with_capacity(indices.len()), 0);
+        for range in ranges {
+            let start = kept.len();
+            let end = (range.start + range.count).min(indices.len());
+            for element in indices[range.start.min(end)..end].chunks(element_size(range.primitive)) {
+                if element.len() == element_size(range.primitive) && keep(self, range.primitive, element) {
+                    kept.extend_from_slice(element);
+                } else {
+                    dropped += 1;
+                }
+            }
+            if kept.len() > start {
+                self.ranges.push(DrawRange { start, count: kept.len() - start, ..range });
+            }
+        }
+        self.indices = kept;
+        dropped
+    }
+
+    /// Drops anything that points past the end of the vertices or at a NaN or infinite position,
+    /// and cuts ranges short that go past the end of the indices. Gives back how many elements were dropped
+    pub fn drop_invalid(&mut self) -> usize {
+        self.retain_elements(|mesh, _, element| mesh.usable(element))
+    }
+
+    /// Drops triangles without any area and lines whose ends are in the same place, giving back how many there were
+    pub fn drop_degenerates(&mut self) -> usize {
+        self.retain_elements(|mesh, primitive, element| {
+            let p = |i: usize| mesh.positions[element[i] as usize];
+            match primitive {
+                Primitive::Triangles => cross(sub(p(1), p(0)), sub(p(2), p(0))) != [0.0; 3],
+                Primitive::Lines => p(0) != p(1),
+                Primitive::Points => true,
+            }
+        })
+    }
+
+    /// Merges vertices that are exact copies of each other, in every attribute, giving back how many went
+    /// Unused vertices are dropped along the way. Every index has to point at a vertex, see `drop_invalid`
+    pub fn weld(&mut self) -> usize {
+        let before = self.positions.len();
+        let mut out = Mesh {
+            colors: self.colors.as_ref().map(|_| Vec::new()),
+            tex_coords: self.tex_coords.as_ref().map(|_| Vec::new()),
+            tangents: self.tangents.as_ref().map(|_| Vec::new()),
+            ranges: self.ranges.clone(),
+            ..Mesh::default()
+        };
+        let mut seen = HashMap::new();
+        let mut remap = HashMap::new();
+        for &v in &self.indices {
+            let idx = *remap.entry(v).or_insert_with(|| *seen.entry(self.vertex_key(v)).or_insert_with(|| out.copy_vertex(self, v)));
+            out.indices.push(idx);
+        }
+        *self = out;
+        before - self.positions.len()
+    }
+
+    /// Turns triangles around so each agrees with the ones next to it, giving back how many were turned
+    /// Closed pieces are turned to face outwards, open ones keep whichever way most of their triangles already faced
+    /// Edges shared by more than two triangles don't connect anything, since there's no one way to agree across them
+    /// Triangles that fold onto themselves once their positions are welded aren't turned, since they don't face anywhere
+    pub fn unify_winding(&mut self) -> usize {
+        // Only the adjacency is used, so every face can say it's in the first range
+        let half_edges = HalfEdgeMesh::from_faces(self, self.checked_triangles().map(|(_, tri)| (tri.to_vec(), 0)));
+        let triangles: Vec<(usize, [u32; 3])> = self.checked_triangles()
+            .filter(|(_, tri)| {
+                let [a, b, c] = tri.map(|v| half_edges.welded_vertex(v));
+                a != b && b != c && c != a
+            })
+            .collect();
+        // The triangles across each edge of triangle `t`, and whether they go along the edge the same way it does
+        // Edges with anything but one triangle on the other side don't connect it to anything
+        let across = |t: usize| half_edges.face_half_edges(t as u32).into_iter().map(|h| {
+            let (a, b) = (half_edges.origin(h), half_edges.half_edges()[h as usize].vertex);
+            match (half_edges.edge_half_edges(a, b), half_edges.edge_half_edges(b, a)) {
+                ([_], [other]) => Some((*other, false)),
+                (&[x, y], []) => Some((if x == h { y } else { x }, true)),
+                _ => None,
+            }.map(|(other, same_way)| (half_edges.half_edges()[other as usize].face.unwrap() as usize, same_way))
+        }).collect::<Vec<_>>();
+
+        // Walks each connected piece, working out which triangles need turning to match the first one
+        let mut flip: Vec<Option<bool>> = vec![None; triangles.len()];
+        let mut flipped = 0;
+        for seed in 0..triangles.len() {
+            if flip[seed].is_some() {
+                continue;
+            }
+            flip[seed] = Some(false);
+            let mut piece = vec![seed];
+            let mut closed = true;
+            let mut i = 0;
+            while i < piece.len() {
+                let t = piece[i];
+                for side in across(t) {
+                    let Some((other, same_way)) = side else {
+                        closed = false;
+                        continue;
+                    };
+                    if flip[other].is_none() {
+                        flip[other] = Some(flip[t].unwrap() ^ same_way);
+                        piece.push(other);
+                    }
+                }
+                i += 1;
+            }
+
+            let turned = piece.iter().filter(|&&t| flip[t] == Some(true)).count();
+            let turn_all = if closed {
+                // The volume comes out negative when the triangles face inwards
+                let volume: f32 = piece.iter().map(|&t| {
+                    let [p0, p1, p2] = triangles[t].1.map(|v| self.positions[v as usize]);
+                    let v = p0[0] * (p1[1] * p2[2] - p1[2] * p2[1]) - p0[1] * (p1[0] * p2[2] - p1[2] * p2[0]) + p0[2] * (p1[0] * p2[1] - p1[1] * p2[0]);
+                    if flip[t] == Some(true) { -v } else { v }
+                }).sum();
+                volume < 0.0
+            } else {
+                turned * 2 > piece.len()
+            };
+            for &t in &piece {
+                let turn = flip[t].unwrap() ^ turn_all;
+                flip[t] = Some(turn);
+                if turn {
+                    let start = triangles[t].0;
+                    self.indices.swap(start + 1, start + 2);
+                    flipped += 1;
+                }
+            }
+        }
+        flipped
+    }
+
+    /// Makes every normal unit length, and gives vertices without a usable normal one from the triangles around them
+    /// Gives back how many had to be replaced
+    pub fn renormalize(&mut self) -> usize {
+        let mut smooth: Option<Vec<Normal>> = None;
+        let mut replaced = 0;
+        for v in 0..self.normals.len() {
+            let n = self.normals[v];
+            let len = length(n);
+            if len.is_finite() && len > 0.0 {
+                self.normals[v] = n.map(|x| x / len);
+                continue;
+            }
+            let smooth = smooth.get_or_insert_with(|| {
+                let indices: Vec<u32> = self.checked_triangles().flat_map(|(_, tri)| tri).collect();
+                let range = DrawRange { primitive: Primitive::Triangles, start: 0, count: indices.len(), group: None };
+                let mut copy = Mesh { positions: self.positions.clone(), indices, ranges: vec![range], ..Mesh::default() };
+                copy.compute_normals();
+                copy.normals
+            });
+            self.normals[v] = smooth[v];
+            replaced += 1;
+        }
+        replaced
+    }
+
+    /// Runs every repair, in an order where each one helps the next
+    pub fn repair(&mut self) {
+        self.drop_invalid();
+        self.weld();
+        self.drop_degenerates();
+        self.unify_winding();
+        self.renormalize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{is_closed, volume};
+
+    #[test]
+    fn a_cube_is_valid() {
+        let validation = Mesh::cube(1.0, 2).validate();
+        assert!(validation.is_valid(), "{:?}", validation.problems());
+    }
+
+    #[test]
+    fn validate_finds_problems() {
+        let mut mesh = Mesh::cube(1.0, 1);
+        mesh.indices.swap(1, 2);
+        mesh.positions[mesh.indices[6] as usize] = [f32::NAN; 3];
+        mesh.normals[0] = [0.0; 3];
+        mesh.indices.push(99);
+        mesh.ranges[0].count += 1;
+        let validation = mesh.validate();
+        assert_eq!(validation.out_of_range_indices, vec![mesh.indices.len() - 1]);
+        assert_eq!(validation.nan_positions.len(), 1);
+        assert_eq!(validation.zero_normals, vec![0]);
+        assert_eq!(validation.inconsistent_edges.len(), 3);
+        assert_eq!(validation.problems().len(), 4);
+    }
+
+    #[test]
+    fn a_third_triangle_on_an_edge_is_non_manifold() {
+        let mut mesh = Mesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 6],
+            // The last triangle uses a copy of vertex 1, which still counts as the same edge
+            indices: vec![0, 1, 2, 1, 0, 3, 5, 0, 4],
+            ..Mesh::default()
+        };
+        mesh.ranges.push(DrawRange { primitive: Primitive::Triangles, start: 0, count: 9, group: None });
+        let validation = mesh.validate();
+        assert_eq!(validation.non_manifold_edges, vec![(0, 1)]);
+        assert!(validation.inconsistent_edges.is_empty());
+    }
+
+    #[test]
+    fn unify_winding_turns_a_flipped_triangle_back() {
+        let mut mesh = Mesh::cube(1.0, 1);
+        mesh.indices.swap(4, 5);
+        assert!(!mesh.validate().inconsistent_edges.is_empty());
+        assert_eq!(mesh.unify_winding(), 1);
+        assert!(is_closed(&mesh));
+        assert!((volume(&mesh) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn unify_winding_turns_closed_pieces_outwards() {
+        let mut mesh = Mesh::icosphere(1.0, 1);
+        mesh.flip_winding();
+        assert_eq!(mesh.unify_winding(), mesh.triangles().count());
+        assert!(volume(&mesh) > 0.0);
+    }
+
+    #[test]
+    fn repair_fixes_a_broken_cube() {
+        let mut mesh = Mesh::cube(1.0, 1);
+        let triangles = mesh.triangles().count();
+        // A copy of the first vertex, a flipped triangle, a triangle without area, and one at a NaN vertex
+        let copy = mesh.duplicate_vertex(0);
+        mesh.indices[0] = copy;
+        mesh.indices.swap(7, 8);
+        mesh.positions.push([f32::NAN; 3]);
+        mesh.normals.push([0.0, 1.0, 0.0]);
+        mesh.tex_coords.as_mut().unwrap().push([0.0; 2]);
+        let nan = mesh.positions.len() as u32 - 1;
+        mesh.indices.extend([1, 1, 2, 0, 1, nan]);
+        mesh.ranges[0].count += 6;
+        mesh.normals[3] = [0.0; 3];
+
+        mesh.repair();
+        let validation = mesh.validate();
+        assert!(validation.is_valid(), "{:?}", validation.problems());
+        assert_eq!(mesh.triangles().count(), triangles);
+        assert!(is_closed(&mesh));
+        assert!((volume(&mesh) - 1.0).abs() < 1e-5);
+    }
+}
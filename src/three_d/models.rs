@@ -0,0 +1,160 @@
+use super::buffer::{Vertex, Color};
+use super::mesh::*;
+use super::shape::importing::{read_obj, ImportOptions, ImportReport, NoResolver};
+use super::teapot;
+
+// Meshes that are built into the library, so examples and tests have something to draw without any files
+// Every model is centered on the origin with Y up, and scaled so its longest side is 1 long
+// TODO: the Stanford bunny is still missing. It needs a decimated copy of the Stanford scan in media/, loaded like
+// `suzanne`, and we don't have one yet. A look-alike is no use for comparing against other renderers
+
+/// The Utah teapot, with its normals
+pub fn teapot() -> Mesh {
+    let mut mesh = Mesh {
+        positions: teapot::VERTICES.to_vec(),
+        normals: teapot::NORMALS.to_vec(),
+        indices: teapot::INDICES.iter().map(|&i| i as u32).collect(),
+        ranges: vec![DrawRange {
+            primitive: Primitive::Triangles,
+            start: 0,
+            count: teapot::INDICES.len(),
+            group: Some("teapot".to_string()),
+        }],
+        ..Mesh::default()
+    };
+    // The data winds clockwise, and the patches pinch down to a point at the top and bottom, leaving slivers
+    mesh.flip_winding();
+    mesh.drop_degenerates();
+    // The first vertex is only there to line the indices up, since the original counted from 1
+    mesh.optimize_vertex_fetch();
+    fit(&mut mesh);
+    mesh
+}
+
+/// Blender's monkey head, with texture coordinates
+pub fn suzanne() -> Mesh {
+    let obj = include_str!("../../media/monkey.obj");
+    let (mut mesh, _) = read_obj(obj.as_bytes(), "monkey.obj", &NoResolver, &ImportOptions::default(), &mut ImportReport::default())
+        .expect("the built in monkey.obj should always load")
+        .remove(0);
+    // The file has a leftover vertex that no face uses
+    mesh.optimize_vertex_fetch();
+    fit(&mut mesh);
+    mesh
+}
+
+/// The Cornell box, from the measurements Cornell publish, with each wall and block in its own named range
+/// The walls are colored in the vertex colors, and the light is a white patch just under the ceiling
+/// The open side faces -Z, so it's seen from the front by a camera looking down +Z
+pub fn cornell_box() -> Mesh {
+    const WHITE: Color = [0.73, 0.73, 0.73];
+    const RED: Color = [0.65, 0.05, 0.05];
+    const GREEN: Color = [0.12, 0.45, 0.15];
+    const LIGHT: Color = [1.0, 1.0, 1.0];
+    let room: Vertex = [278.0, 274.4, 279.6];
+    let short: Vertex = [186.0, 82.5, 169.0];
+    let tall: Vertex = [368.5, 165.0, 351.5];
+
+    let mut mesh = Mesh { colors: Some(Vec::new()), ..Mesh::default() };
+    let mut add = |group: &str, color: Color, faces: &[[Vertex; 4]], toward: Vertex, facing_in: bool| {
+        let start = mesh.indices.len();
+        for quad in faces {
+            mesh.add_quad(*quad, color, toward, facing_in);
+        }
+        mesh.ranges.push(DrawRange {
+            primitive: Primitive::Triangles,
+            start,
+            count: mesh.indices.len() - start,
+            group: Some(group.to_string()),
+        });
+    };
+
+    add("floor", WHITE, &[[[552.8, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 559.2], [549.6, 0.0, 559.2]]], room, true);
+    // Lowered a little from the ceiling, so the two don't fight over the same depth
+    add("light", LIGHT, &[[[343.0, 548.7, 227.0], [343.0, 548.7, 332.0], [213.0, 548.7, 332.0], [213.0, 548.7, 227.0]]], room, true);
+    add("ceiling", WHITE, &[[[556.0, 548.8, 0.0], [556.0, 548.8, 559.2], [0.0, 548.8, 559.2], [0.0, 548.8, 0.0]]], room, true);
+    add("back wall", WHITE, &[[[549.6, 0.0, 559.2], [0.0, 0.0, 559.2], [0.0, 548.8, 559.2], [556.0, 548.8, 559.2]]], room, true);
+    add("right wall", GREEN, &[[[0.0, 0.0, 559.2], [0.0, 0.0, 0.0], [0.0, 548.8, 0.0], [0.0, 548.8, 559.2]]], room, true);
+    add("left wall", RED, &[[[552.8, 0.0, 0.0], [549.6, 0.0, 559.2], [556.0, 548.8, 559.2], [556.0, 548.8, 0.0]]], room, true);
+    add("short block", WHITE, &[
+        [[130.0, 165.0, 65.0], [82.0, 165.0, 225.0], [240.0, 165.0, 272.0], [290.0, 165.0, 114.0]],
+        [[290.0, 0.0, 114.0], [290.0, 165.0, 114.0], [240.0, 165.0, 272.0], [240.0, 0.0, 272.0]],
+        [[130.0, 0.0, 65.0], [130.0, 165.0, 65.0], [290.0, 165.0, 114.0], [290.0, 0.0, 114.0]],
+        [[82.0, 0.0, 225.0], [82.0, 165.0, 225.0], [130.0, 165.0, 65.0], [130.0, 0.0, 65.0]],
+        [[240.0, 0.0, 272.0], [240.0, 165.0, 272.0], [82.0, 165.0, 225.0], [82.0, 0.0, 225.0]],
+    ], short, false);
+    add("tall block", WHITE, &[
+        [[423.0, 330.0, 247.0], [265.0, 330.0, 296.0], [314.0, 330.0, 456.0], [472.0, 330.0, 406.0]],
+        [[423.0, 0.0, 247.0], [423.0, 330.0, 247.0], [472.0, 330.0, 406.0], [472.0, 0.0, 406.0]],
+        [[472.0, 0.0, 406.0], [472.0, 330.0, 406.0], [314.0, 330.0, 456.0], [314.0, 0.0, 456.0]],
+        [[314.0, 0.0, 456.0], [314.0, 330.0, 456.0], [265.0, 330.0, 296.0], [265.0, 0.0, 296.0]],
+        [[265.0, 0.0, 296.0], [265.0, 330.0, 296.0], [423.0, 330.0, 247.0], [423.0, 0.0, 247.0]],
+    ], tall, false);
+
+    fit(&mut mesh);
+    mesh
+}
+
+impl Mesh {
+    /// Adds a flat quad, turned to face `toward` a point if `facing_in`, or away from it if not
+    fn add_quad(&mut self, mut quad: [Vertex; 4], color: Color, toward: Vertex, facing_in: bool) {
+        let mut normal = face_normal(&[quad[0], quad[1], quad[2]]);
+        if (dot(normal, sub(toward, quad[0])) > 0.0) != facing_in {
+            quad.reverse();
+            normal = normal.map(|x| -x);
+        }
+        let first = self.positions.len() as u32;
+        for p in quad {
+            self.positions.push(p);
+            self.normals.push(normal);
+            self.colors.get_or_insert_with(Vec::new).push(color);
+        }
+        self.indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+    }
+}
+
+/// Moves a mesh to be centered on the origin, and scales it so its longest side is 1
+fn fit(mesh: &mut Mesh) {
+    let Some(bounds) = mesh.bounds() else { return };
+    let center = bounds.center();
+    let size = bounds.size().into_iter().fold(0.0, f32::max);
+    for p in mesh.positions.iter_mut() {
+        *p = [(p[0] - center[0]) / size, (p[1] - center[1]) / size, (p[2] - center[2]) / size];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_are_centered_and_fit_in_one_unit() {
+        for (name, mesh) in [("teapot", teapot()), ("suzanne", suzanne()), ("cornell_box", cornell_box())] {
+            let bounds = mesh.bounds().unwrap();
+            assert!(bounds.center().iter().all(|x| x.abs() < 1e-5), "{name} isn't centered");
+            assert!((bounds.size().into_iter().fold(0.0, f32::max) - 1.0).abs() < 1e-5, "{name} isn't 1 across");
+            let validation = mesh.validate();
+            assert!(validation.degenerate_triangles.is_empty() && validation.inconsistent_edges.is_empty(), "{name}: {:?}", validation.problems());
+            assert_eq!(mesh.normals.len(), mesh.positions.len());
+        }
+        assert!(suzanne().tex_coords.is_some());
+    }
+
+    #[test]
+    fn cornell_box_faces_into_the_room() {
+        let mesh = cornell_box();
+        let groups: Vec<&str> = mesh.ranges.iter().filter_map(|range| range.group.as_deref()).collect();
+        assert_eq!(groups, ["floor", "light", "ceiling", "back wall", "right wall", "left wall", "short block", "tall block"]);
+        // The floor faces up and the ceiling down, and the normals agree with the winding
+        let up = |range: &DrawRange| {
+            let tri = &mesh.range_indices(range)[..3];
+            face_normal(&[0, 1, 2].map(|i| mesh.positions[tri[i] as usize]))
+        };
+        assert!(up(&mesh.ranges[0])[1] > 0.99);
+        assert!(up(&mesh.ranges[2])[1] < -0.99);
+        for tri in mesh.triangles() {
+            let n = face_normal(&tri.map(|v| mesh.positions[v as usize]));
+            assert!(tri.iter().all(|&v| dot(n, mesh.normals[v as usize]) > 0.99));
+        }
+    }
+}
@@ -9,10 +9,12 @@ use image;
 use crate::matrix::*;
 use super::shaders::Program;
 use super::shaders::ShaderType;
-use super::{shaders, animation::Animation, buffer::*, VAO::*};
+use super::{shaders, animation::Animation, buffer::*, mesh::*, VAO::*};
 
 pub mod importing;
 use importing::*;
+pub mod exporting;
+mod primitives;
 
 pub const FOV: f32 = std::f32::consts::PI / 3.0;
 pub const ZFAR: f32 = 1024.0;
@@ -43,6 +45,35 @@ impl Transform {
         self.transform_matrix = self.scaling_matrix * self.rotation_matrix * self.translation_matrix;
     }
 
+    /// Splits a matrix made of a scale, rotation and translation back into those three
+    /// Shearing can't be represented, so matrices with it come out slightly different
+    pub fn from_matrix(matrix: Mat4) -> Transform {
+        let m = &matrix.inner;
+        let len = |row: &[f32; 4]| (row[0] * row[0] + row[1] * row[1] + row[2] * row[2]).sqrt();
+        let mut scale = [len(&m[0]), len(&m[1]), len(&m[2])];
+        // A mirror has to go in the scale, rotations can't do it
+        if matrix.determinant3() < 0.0 {
+            scale[0] = -scale[0];
+        }
+
+        let mut rotation = IDENTITY;
+        for ((row, axis), s) in rotation.inner.iter_mut().zip(m).zip(scale) {
+            if s != 0.0 {
+                for (r, x) in row.iter_mut().zip(axis).take(3) {
+                    *r = x / s;
+                }
+            }
+        }
+
+        let mut transform = Transform::default();
+        transform.set_transform_matrix(
+            Some(generate_scale(&scale)), 
+            Some(rotation), 
+            Some(generate_translate(Some(m[3][0]), Some(m[3][1]), Some(m[3][2]))),
+        );
+        transform
+    }
+
     pub fn set_scaling(&mut self, scaling: Mat4) {
         self.scaling_matrix = scaling;
 
@@ -74,12 +105,31 @@ impl Default for Transform {
     }
 }
 
-pub struct Shape {
+/// A mesh's buffers on the GPU, with the VAO that draws them
+struct Buffers {
     vao: VertexArrayObject,
 
     positions: VertexBuffer,
     normals: NormalBuffer,
+    colors: Option<ColorBuffer>,
+    tex_coords: Option<TexCoordBuffer>,
+    tangents: Option<TangentBuffer>,
     indices: IndexBuffer,
+}
+
+/// A simpler version of a shape's mesh, drawn in its place once the shape covers less of the screen
+struct Lod {
+    buffers: Buffers,
+    mesh: Mesh,
+    /// The biggest the shape can get on screen while this is drawn, as a fraction of the window's height
+    max_screen_size: f32,
+}
+
+pub struct Shape {
+    buffers: Buffers,
+    mesh: Mesh,
+    /// From the most detailed down, each used at smaller sizes than the one before
+    lods: Vec<Lod>,
 
     transform: Transform,
     animation: Option<Box<dyn Animation>>,
@@ -109,7 +159,125 @@ impl Shape {
 */
 
 impl Shape {
+    /// Uploads `mesh` to the GPU, and keeps it around as the shape's CPU side copy
+    /// This is the only part of making a shape that needs a GL context, so meshes can be built and changed without one
+    /// Meshes with texture coordinates but no tangents get them worked out, so normal maps work on any shape
+    pub fn from_mesh(
+        mut mesh: Mesh,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,
+        material: Material,) -> Shape
+    {
+        if mesh.tangents.is_none() && mesh.tex_coords.is_some() {
+            mesh.compute_tangents();
+        }
+        Shape {
+            buffers: Buffers::new(&mesh),
+            mesh,
+            lods: Vec::new(),
+            transform: transform.unwrap_or_default(), 
+            animation, shader_type, 
+            material
+        }
+    }
+
+    /// The CPU side copy of the shape's geometry
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+
+    /// Replaces the shape's geometry, uploading the new mesh in place of the old one
+    /// Any levels of detail were made from the old mesh, so they're dropped
+    pub fn set_mesh(&mut self, mut mesh: Mesh) {
+        if mesh.tangents.is_none() && mesh.tex_coords.is_some() {
+            mesh.compute_tangents();
+        }
+        self.buffers = Buffers::new(&mesh);
+        self.mesh = mesh;
+        self.lods.clear();
+    }
+
+    /// Makes `levels` simpler versions of the mesh, each with about a quarter of the triangles of the one before
+    /// The first is drawn once the shape is less than half the window tall, and each after at half the size of the last
+    /// Attributes still have to be bound for the new levels, with `bind_attributes`
+    pub fn generate_lods(&mut self, levels: usize) {
+        let lods = self.mesh.lod_chain(levels);
+        self.set_lods(lods);
+    }
+
+    /// Replaces the levels of detail with `lods`, from the most detailed down, drawn at the same sizes `generate_lods` uses
+    pub fn set_lods(&mut self, lods: Vec<Mesh>) {
+        self.lods.clear();
+        let mut max_screen_size = 0.5;
+        for mesh in lods {
+            self.add_lod(mesh, max_screen_size);
+            max_screen_size /= 2.0;
+        }
+    }
+
+    /// Adds a simpler version of the mesh, drawn when the shape is at most `max_screen_size` of the window's height
+    /// Levels should be added from the most detailed down, with smaller and smaller sizes
+    pub fn add_lod(&mut self, mut mesh: Mesh, max_screen_size: f32) {
+        if mesh.tangents.is_none() && mesh.tex_coords.is_some() {
+            mesh.compute_tangents();
+        }
+        self.lods.push(Lod { buffers: Buffers::new(&mesh), mesh, max_screen_size });
+    }
+
+    /// The simpler versions of the mesh, from the most detailed down
+    pub fn lods(&self) -> impl Iterator<Item = &Mesh> {
+        self.lods.iter().map(|lod| &lod.mesh)
+    }
+
+    /// How tall the shape's bounding sphere looks, as a fraction of the window's height
+    /// Shapes the camera is inside of count as infinitely big
+    pub fn screen_size(&self, view: &Mat4) -> f32 {
+        let Some(bounds) = self.mesh.bounds() else { return 0.0 };
+        let size = bounds.size();
+        let radius = (size[0] * size[0] + size[1] * size[1] + size[2] * size[2]).sqrt() / 2.0;
+
+        let matrix = self.transform.transform_matrix * *view;
+        let m = &matrix.inner;
+        let c = bounds.center();
+        let depth = c[0] * m[0][2] + c[1] * m[1][2] + c[2] * m[2][2] + m[3][2];
+        // The sphere gets as big as the longest axis of the transform stretches it
+        let scale = (0..3).map(|i| (m[i][0] * m[i][0] + m[i][1] * m[i][1] + m[i][2] * m[i][2]).sqrt()).fold(0.0, f32::max);
+        let radius = radius * scale;
+        if depth <= radius {
+            return f32::INFINITY;
+        }
+        radius / (depth * (FOV / 2.0).tan())
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Points the program's attributes at the shape's buffers, for the mesh and every level of detail
     pub fn bind_attributes(&self, program: &Program) {
+        self.buffers.bind_attributes(program);
+        for lod in &self.lods {
+            lod.buffers.bind_attributes(program);
+        }
+    }
+}
+
+impl Buffers {
+    fn new(mesh: &Mesh) -> Buffers {
+        let (vao, vao_lock) = VertexArrayObject::new().unwrap();
+
+        let positions = VertexBuffer::new(&mesh.positions, &vao_lock);
+        let normals = NormalBuffer::new(&mesh.normals, &vao_lock);
+        let colors = mesh.colors.as_ref().map(|colors| ColorBuffer::new(colors, &vao_lock));
+        let tex_coords = mesh.tex_coords.as_ref().map(|tex_coords| TexCoordBuffer::new(tex_coords, &vao_lock));
+        let tangents = mesh.tangents.as_ref().map(|tangents| TangentBuffer::new(tangents, &vao_lock));
+        let indices = IndexBuffer::new(&mesh.indices, &vao_lock); 
+
+        Buffers { vao, positions, normals, colors, tex_coords, tangents, indices }
+    }
+
+    fn bind_attributes(&self, program: &Program) {
         unsafe {
             gl::BindVertexArray(*self.vao.id());
 
@@ -139,6 +307,32 @@ impl Shape {
             );
             gl::EnableVertexAttribArray(norm_attr as GLuint);
 
+            // Colors, texture coordinates and tangents are optional, and not every program uses them
+            if let Some(colors) = &self.colors {
+                let color_attr = gl::GetAttribLocation(program.0, CString::new("color").unwrap().as_ptr());
+                if color_attr >= 0 {
+                    gl::BindBuffer(gl::ARRAY_BUFFER, *colors.id());
+                    gl::VertexAttribPointer(color_attr as GLuint, 3, gl::FLOAT, gl::FALSE as GLboolean, 0, ptr::null());
+                    gl::EnableVertexAttribArray(color_attr as GLuint);
+                }
+            }
+            if let Some(tex_coords) = &self.tex_coords {
+                let tex_attr = gl::GetAttribLocation(program.0, CString::new("tex_coords").unwrap().as_ptr());
+                if tex_attr >= 0 {
+                    gl::BindBuffer(gl::ARRAY_BUFFER, *tex_coords.id());
+                    gl::VertexAttribPointer(tex_attr as GLuint, 2, gl::FLOAT, gl::FALSE as GLboolean, 0, ptr::null());
+                    gl::EnableVertexAttribArray(tex_attr as GLuint);
+                }
+            }
+            if let Some(tangents) = &self.tangents {
+                let tangent_attr = gl::GetAttribLocation(program.0, CString::new("tangent").unwrap().as_ptr());
+                if tangent_attr >= 0 {
+                    gl::BindBuffer(gl::ARRAY_BUFFER, *tangents.id());
+                    gl::VertexAttribPointer(tangent_attr as GLuint, 4, gl::FLOAT, gl::FALSE as GLboolean, 0, ptr::null());
+                    gl::EnableVertexAttribArray(tangent_attr as GLuint);
+                }
+            }
+
             gl::BindVertexArray(0);
         }
     }
@@ -209,8 +403,14 @@ impl Shape {
                 [         0.0         ,    0.0, -(2.0*ZFAR*ZNEAR)/(ZFAR-ZNEAR),   0.0],
             ]
         };
+        // The simplest level that's still meant for this size, or the full mesh if the shape is big enough
+        let screen_size = if self.lods.is_empty() { 0.0 } else { self.screen_size(view) };
+        let (buffers, mesh) = self.lods.iter().rev()
+            .find(|lod| screen_size <= lod.max_screen_size)
+            .map_or((&self.buffers, &self.mesh), |lod| (&lod.buffers, &lod.mesh));
+
         unsafe {
-            gl::BindVertexArray(*self.vao.id());
+            gl::BindVertexArray(*buffers.vao.id());
             
             let perspective_handle = gl::GetUniformLocation(program.0, CString::new("perspective").unwrap().as_ptr());
             let view_handle = gl::GetUniformLocation(program.0, CString::new("view").unwrap().as_ptr());
@@ -246,12 +446,15 @@ impl Shape {
                 gl::Uniform1f(specular_exp_handle, self.material.specular_exp);
             }
 
-            gl::DrawElements(
-                gl::TRIANGLES,      
-                self.indices.num_indices as GLint,    
-                gl::UNSIGNED_INT,   
-                ptr::null()           
-            );
+            for range in &mesh.ranges {
+                debug_assert!(range.start + range.count <= buffers.indices.num_indices);
+                gl::DrawElements(
+                    range.primitive.gl_mode(),
+                    range.count as GLint,
+                    gl::UNSIGNED_INT,
+                    (range.start * mem::size_of::<GLuint>()) as *const _
+                );
+            }
 
             gl::BindVertexArray(0);
         }
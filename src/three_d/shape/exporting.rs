@@ -0,0 +1,266 @@
+use std::{fs, io::{self, BufWriter, Write}, collections::HashMap, path::Path};
+
+use super::*;
+
+mod ply;
+pub use ply::*;
+mod stl;
+pub use stl::*;
+
+/// Settings that change how shapes are exported
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// Apply each shape's current transform to its positions and normals, instead of writing them as loaded
+    pub bake_transform: bool,
+}
+
+/// Writes `shapes` to `path` as one OBJ, with their materials in an MTL next to it with the same name
+pub fn export_obj(shapes: &[&Shape], path: &str, options: &ExportOptions) -> io::Result<()> {
+    let path = Path::new(path);
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path.file_name().unwrap_or_default().to_string_lossy();
+
+    let obj = BufWriter::new(fs::File::create(path)?);
+    let mtl = BufWriter::new(fs::File::create(&mtl_path)?);
+    write_obj(shapes, obj, mtl, &mtl_name, options)
+}
+
+/// Writes `shapes` as one OBJ to `obj`, and their materials to `mtl`
+/// `mtl_name` is what the OBJ's `mtllib` statement refers to
+pub fn write_obj<W: Write, M: Write>(shapes: &[&Shape], obj: W, mtl: M, mtl_name: &str, options: &ExportOptions) -> io::Result<()> {
+    let objects: Vec<_> = shapes.iter()
+        .map(|shape| (&shape.mesh, &shape.material, options.bake_transform.then_some(&shape.transform.transform_matrix)))
+        .collect();
+    write_objects(&objects, obj, mtl, mtl_name)
+}
+
+/// Like `write_obj`, for meshes that haven't been uploaded to the GPU, each with the material it's drawn with
+pub fn write_obj_meshes<W: Write, M: Write>(meshes: &[(&Mesh, &Material)], obj: W, mtl: M, mtl_name: &str) -> io::Result<()> {
+    let objects: Vec<_> = meshes.iter().map(|&(mesh, material)| (mesh, material, None)).collect();
+    write_objects(&objects, obj, mtl, mtl_name)
+}
+
+/// Each object is a mesh, its material, and the transform to bake into it if there is one
+fn write_objects<W: Write, M: Write>(objects: &[(&Mesh, &Material, Option<&Mat4>)], mut obj: W, mut mtl: M, mtl_name: &str) -> io::Result<()> {
+    writeln!(obj, "# Exported by graphics-lib")?;
+    writeln!(obj, "mtllib {mtl_name}")?;
+    writeln!(mtl, "# Exported by graphics-lib")?;
+
+    // Materials are written once per name, shapes with different materials that share a name get a suffix
+    let mut materials: HashMap<String, &Material> = HashMap::new();
+
+    // OBJ indices count up through the whole file, not per object, and separately for each kind of vertex data
+    let mut offsets = Offsets { position: 1, tex_coord: 1, normal: 1 };
+    for (i, &(mesh, material, matrix)) in objects.iter().enumerate() {
+        let base_name = material.name.clone().unwrap_or_else(|| format!("material{i}"));
+        let mut mat_name = base_name.clone();
+        let mut n = 2;
+        while let Some(existing) = materials.get(&mat_name) {
+            if *existing == material {
+                break;
+            }
+            mat_name = format!("{base_name}_{n}");
+            n += 1;
+        }
+        if !materials.contains_key(&mat_name) {
+            write_material(&mut mtl, &mat_name, material)?;
+            materials.insert(mat_name.clone(), material);
+        }
+
+        if objects.len() > 1 {
+            writeln!(obj, "o shape{i}")?;
+        }
+        write_mesh(&mut obj, mesh, &mat_name, &offsets, matrix)?;
+        offsets.position += mesh.positions.len();
+        offsets.tex_coord += mesh.tex_coords.as_ref().map_or(0, Vec::len);
+        offsets.normal += mesh.normals.len();
+    }
+
+    obj.flush()?;
+    mtl.flush()
+}
+
+impl Shape {
+    /// Writes this shape to `path` as an OBJ, with its material in an MTL next to it
+    pub fn export_obj(&self, path: &str, options: &ExportOptions) -> io::Result<()> {
+        export_obj(&[self], path, options)
+    }
+}
+
+/// Where one shape's `v`, `vt` and `vn` lines start, counting from 1
+struct Offsets {
+    position: usize,
+    tex_coord: usize,
+    normal: usize,
+}
+
+/// `matrix` is baked into the positions and normals if there is one
+fn write_mesh<W: Write>(obj: &mut W, mesh: &Mesh, mat_name: &str, offsets: &Offsets, matrix: Option<&Mat4>) -> io::Result<()> {
+    for (i, p) in mesh.positions.iter().enumerate() {
+        let p = matrix.map_or(*p, |m| m.transform_point(p));
+        write!(obj, "v {} {} {}", p[0], p[1], p[2])?;
+        if let Some(colors) = &mesh.colors {
+            let c = colors[i];
+            write!(obj, " {} {} {}", c[0], c[1], c[2])?;
+        }
+        writeln!(obj)?;
+    }
+    if let Some(tex_coords) = &mesh.tex_coords {
+        for t in tex_coords {
+            writeln!(obj, "vt {} {}", t[0], t[1])?;
+        }
+    }
+    for n in &mesh.normals {
+        let n = matrix.map_or(*n, |m| m.transform_normal(n));
+        writeln!(obj, "vn {} {} {}", n[0], n[1], n[2])?;
+    }
+
+    // Every vertex has a position, normal and maybe a texture coord, all at the same index within this shape
+    // Lines can't refer to normals and points can only refer to positions, so they get shorter corners
+    let v = |idx: u32| idx as usize + offsets.position;
+    let vt = |idx: u32| idx as usize + offsets.tex_coord;
+    let vn = |idx: u32| idx as usize + offsets.normal;
+    let corner = |idx: u32| {
+        if mesh.tex_coords.is_some() { format!("{}/{}/{}", v(idx), vt(idx), vn(idx)) } else { format!("{}//{}", v(idx), vn(idx)) }
+    };
+    let line_corner = |idx: u32| {
+        if mesh.tex_coords.is_some() { format!("{}/{}", v(idx), vt(idx)) } else { v(idx).to_string() }
+    };
+    // A mirroring transform turns faces inside out unless we flip them back
+    let flip = matrix.is_some_and(|m| m.determinant3() < 0.0);
+
+    writeln!(obj, "usemtl {mat_name}")?;
+    let mut group = None;
+    for range in &mesh.ranges {
+        if range.group != group {
+            match &range.group {
+                Some(name) => writeln!(obj, "g {name}")?,
+                None => writeln!(obj, "g")?,
+            }
+            group = range.group.clone();
+        }
+
+        let indices = mesh.range_indices(range);
+        match range.primitive {
+            Primitive::Triangles => {
+                for tri in indices.chunks_exact(3) {
+                    let (b, c) = if flip { (tri[2], tri[1]) } else { (tri[1], tri[2]) };
+                    writeln!(obj, "f {} {} {}", corner(tri[0]), corner(b), corner(c))?;
+                }
+            },
+            Primitive::Lines => {
+                for line in indices.chunks_exact(2) {
+                    writeln!(obj, "l {} {}", line_corner(line[0]), line_corner(line[1]))?;
+                }
+            },
+            Primitive::Points => {
+                write!(obj, "p")?;
+                for idx in indices {
+                    write!(obj, " {}", v(*idx))?;
+                }
+                writeln!(obj)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Writes `mat` as one `newmtl` block called `name`
+pub fn write_material<W: Write>(mtl: &mut W, name: &str, mat: &Material) -> io::Result<()> {
+    let color = |c: [f32; 3]| format!("{} {} {}", c[0], c[1], c[2]);
+
+    writeln!(mtl, "\nnewmtl {name}")?;
+    writeln!(mtl, "Ka {}", color(mat.ambient_color))?;
+    writeln!(mtl, "Kd {}", color(mat.diffuse_color))?;
+    writeln!(mtl, "Ks {}", color(mat.specular_color))?;
+    writeln!(mtl, "Ke {}", color(mat.emission_color))?;
+    writeln!(mtl, "Ns {}", mat.specular_exp)?;
+    writeln!(mtl, "d {}", mat.transparency)?;
+    if let Some(tf) = mat.transmission_filter_color {
+        writeln!(mtl, "Tf {}", color(tf))?;
+    }
+    if let Some(ni) = mat.optical_density {
+        writeln!(mtl, "Ni {ni}")?;
+    }
+    if let Some(illum) = mat.illum_model {
+        writeln!(mtl, "illum {}", illum.index())?;
+    }
+
+    let textures = &mat.textures;
+    let maps = [
+        ("map_Ka", &textures.ambient),
+        ("map_Kd", &textures.diffuse),
+        ("map_Ks", &textures.specular),
+        ("map_Ns", &textures.specular_exp),
+        ("map_Ke", &textures.emission),
+        ("map_d", &textures.dissolve),
+        ("map_bump", &textures.bump),
+        ("disp", &textures.displacement),
+        ("decal", &textures.decal),
+    ];
+    for (statement, map) in maps {
+        if let Some(map) = map {
+            writeln!(mtl, "{statement} {map}")?;
+        }
+    }
+    for map in &textures.reflection {
+        writeln!(mtl, "refl {map}")?;
+    }
+
+    if let Some(pbr) = &mat.pbr {
+        writeln!(mtl, "Pr {}", pbr.roughness)?;
+        writeln!(mtl, "Pm {}", pbr.metallic)?;
+        writeln!(mtl, "Ps {}", pbr.sheen)?;
+        writeln!(mtl, "Pc {}", pbr.clearcoat_thickness)?;
+        writeln!(mtl, "Pcr {}", pbr.clearcoat_roughness)?;
+        writeln!(mtl, "aniso {}", pbr.anisotropy)?;
+        writeln!(mtl, "anisor {}", pbr.anisotropy_rotation)?;
+        for (statement, map) in [("map_Pr", &pbr.roughness_map), ("map_Pm", &pbr.metallic_map), ("map_Ps", &pbr.sheen_map), ("norm", &pbr.normal_map)] {
+            if let Some(map) = map {
+                writeln!(mtl, "{statement} {map}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves the one MTL the test wrote
+    struct MtlResolver(Vec<u8>);
+
+    impl Resolver for MtlResolver {
+        fn open(&self, name: &str) -> io::Result<Box<dyn io::BufRead + '_>> {
+            match name {
+                "test.mtl" => Ok(Box::new(&self.0[..])),
+                _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+    }
+
+    #[test]
+    fn materials_survive_a_round_trip() {
+        let near = Mesh::cube(1.0, 1);
+        let mut far = near.clone();
+        for p in &mut far.positions {
+            p[0] += 3.0;
+        }
+        let red = Material { name: Some("Red".to_string()), diffuse_color: [1.0, 0.0, 0.0], ..Material::default() };
+        let blue = Material { name: Some("Blue".to_string()), diffuse_color: [0.0, 0.0, 1.0], ..Material::default() };
+
+        let (mut obj, mut mtl) = (Vec::new(), Vec::new());
+        write_obj_meshes(&[(&near, &red), (&far, &blue)], &mut obj, &mut mtl, "test.mtl").unwrap();
+        let mut report = ImportReport::default();
+        let shapes = read_obj(&obj[..], "test.obj", &MtlResolver(mtl), &ImportOptions::default(), &mut report).unwrap();
+
+        assert_eq!(shapes.len(), 2);
+        for ((mesh, material), (original, expected)) in shapes.iter().zip([(&near, &red), (&far, &blue)]) {
+            assert_eq!(material.name, expected.name);
+            assert_eq!(material.diffuse_color, expected.diffuse_color);
+            assert_eq!(mesh.triangles().count(), original.triangles().count());
+            assert_eq!(mesh.bounds(), original.bounds());
+        }
+    }
+}
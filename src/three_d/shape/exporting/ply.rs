@@ -0,0 +1,224 @@
+use std::{fmt, fs, io::{self, BufWriter, Write}};
+
+use super::*;
+
+/// Something that can be written as a property value
+trait PlyScalar: fmt::Display + Copy {
+    fn write_bytes<W: Write>(self, out: &mut W, big_endian: bool) -> io::Result<()>;
+}
+
+impl PlyScalar for f32 {
+    fn write_bytes<W: Write>(self, out: &mut W, big_endian: bool) -> io::Result<()> {
+        out.write_all(&if big_endian { self.to_be_bytes() } else { self.to_le_bytes() })
+    }
+}
+
+impl PlyScalar for u32 {
+    fn write_bytes<W: Write>(self, out: &mut W, big_endian: bool) -> io::Result<()> {
+        out.write_all(&if big_endian { self.to_be_bytes() } else { self.to_le_bytes() })
+    }
+}
+
+impl PlyScalar for u8 {
+    fn write_bytes<W: Write>(self, out: &mut W, _big_endian: bool) -> io::Result<()> {
+        out.write_all(&[self])
+    }
+}
+
+/// Writes the body of a PLY, one element per row
+struct PlyWriter<W: Write> {
+    out: W,
+    format: PlyFormat,
+    start_of_row: bool,
+}
+
+impl<W: Write> PlyWriter<W> {
+    fn value<T: PlyScalar>(&mut self, value: T) -> io::Result<()> {
+        match self.format {
+            PlyFormat::Ascii => {
+                if !self.start_of_row {
+                    write!(self.out, " ")?;
+                }
+                self.start_of_row = false;
+                write!(self.out, "{value}")
+            },
+            PlyFormat::BinaryLittleEndian => value.write_bytes(&mut self.out, false),
+            PlyFormat::BinaryBigEndian => value.write_bytes(&mut self.out, true),
+        }
+    }
+
+    fn end_row(&mut self) -> io::Result<()> {
+        self.start_of_row = true;
+        if self.format == PlyFormat::Ascii { writeln!(self.out) } else { Ok(()) }
+    }
+}
+
+/// Writes `shapes` to `path` as one PLY
+pub fn export_ply(shapes: &[&Shape], path: &str, format: PlyFormat, options: &ExportOptions) -> io::Result<()> {
+    write_ply(shapes, BufWriter::new(fs::File::create(path)?), format, options)
+}
+
+/// Writes `shapes` as one PLY to `out`
+/// Triangles become faces, lines become edges, and points are just left as vertices
+/// Vertex colors are written if any shape has them, shapes without them use their material's diffuse color
+pub fn write_ply<W: Write>(shapes: &[&Shape], out: W, format: PlyFormat, options: &ExportOptions) -> io::Result<()> {
+    let objects: Vec<_> = shapes.iter()
+        .map(|shape| (&shape.mesh, &shape.material, options.bake_transform.then_some(&shape.transform.transform_matrix)))
+        .collect();
+    write_objects(&objects, out, format)
+}
+
+/// Like `write_ply`, for meshes that haven't been uploaded to the GPU, each with the material it's drawn with
+pub fn write_ply_meshes<W: Write>(meshes: &[(&Mesh, &Material)], out: W, format: PlyFormat) -> io::Result<()> {
+    let objects: Vec<_> = meshes.iter().map(|&(mesh, material)| (mesh, material, None)).collect();
+    write_objects(&objects, out, format)
+}
+
+/// Each object is a mesh, its material, and the transform to bake into it if there is one
+fn write_objects<W: Write>(objects: &[(&Mesh, &Material, Option<&Mat4>)], mut out: W, format: PlyFormat) -> io::Result<()> {
+    let num_vertices: usize = objects.iter().map(|(mesh, _, _)| mesh.positions.len()).sum();
+    let num_faces: usize = objects.iter().map(|(mesh, _, _)| mesh.triangles().count()).sum();
+    let num_edges: usize = objects.iter()
+        .flat_map(|(mesh, _, _)| mesh.ranges.iter().filter(|range| range.primitive == Primitive::Lines))
+        .map(|range| range.count / 2)
+        .sum();
+    let has_colors = objects.iter().any(|(mesh, _, _)| mesh.colors.is_some());
+    // PLY has no way to leave out values, so texture coordinates are only written if everything has them
+    let has_tex_coords = !objects.is_empty() && objects.iter().all(|(mesh, _, _)| mesh.tex_coords.is_some());
+
+    writeln!(out, "ply")?;
+    writeln!(out, "format {} 1.0", format.name())?;
+    writeln!(out, "comment Exported by graphics-lib")?;
+    writeln!(out, "element vertex {num_vertices}")?;
+    for name in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(out, "property float {name}")?;
+    }
+    if has_colors {
+        for name in ["red", "green", "blue"] {
+            writeln!(out, "property uchar {name}")?;
+        }
+    }
+    if has_tex_coords {
+        writeln!(out, "property float s")?;
+        writeln!(out, "property float t")?;
+    }
+    if num_faces > 0 {
+        writeln!(out, "element face {num_faces}")?;
+        writeln!(out, "property list uchar uint vertex_indices")?;
+    }
+    if num_edges > 0 {
+        writeln!(out, "element edge {num_edges}")?;
+        writeln!(out, "property uint vertex1")?;
+        writeln!(out, "property uint vertex2")?;
+    }
+    writeln!(out, "end_header")?;
+
+    let mut body = PlyWriter { out, format, start_of_row: true };
+    let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    for &(mesh, material, matrix) in objects {
+        for (i, (p, n)) in mesh.positions.iter().zip(&mesh.normals).enumerate() {
+            let (p, n) = matrix.map_or((*p, *n), |m| (m.transform_point(p), m.transform_normal(n)));
+            for x in p.into_iter().chain(n) {
+                body.value(x)?;
+            }
+            if has_colors {
+                let c = mesh.colors.as_ref().map_or(material.diffuse_color, |colors| colors[i]);
+                for x in c {
+                    body.value(to_byte(x))?;
+                }
+            }
+            if let Some(tex_coords) = mesh.tex_coords.as_ref().filter(|_| has_tex_coords) {
+                body.value(tex_coords[i][0])?;
+                body.value(tex_coords[i][1])?;
+            }
+            body.end_row()?;
+        }
+    }
+
+    // Indices count up through all the shapes' vertices
+    let mut offset = 0;
+    for &(mesh, _, matrix) in objects {
+        // A mirroring transform turns faces inside out unless we flip them back
+        let flip = matrix.is_some_and(|m| m.determinant3() < 0.0);
+        for [a, b, c] in mesh.triangles() {
+            let (b, c) = if flip { (c, b) } else { (b, c) };
+            body.value(3u8)?;
+            for idx in [a, b, c] {
+                body.value(idx + offset)?;
+            }
+            body.end_row()?;
+        }
+        offset += mesh.positions.len() as u32;
+    }
+
+    let mut offset = 0;
+    for &(mesh, _, _) in objects {
+        for range in mesh.ranges.iter().filter(|range| range.primitive == Primitive::Lines) {
+            for line in mesh.range_indices(range).chunks_exact(2) {
+                body.value(line[0] + offset)?;
+                body.value(line[1] + offset)?;
+                body.end_row()?;
+            }
+        }
+        offset += mesh.positions.len() as u32;
+    }
+
+    body.out.flush()
+}
+
+impl Shape {
+    /// Writes this shape to `path` as a PLY
+    pub fn export_ply(&self, path: &str, format: PlyFormat, options: &ExportOptions) -> io::Result<()> {
+        export_ply(&[self], path, format, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::three_d::mesh::tests::triangle_positions;
+
+    #[test]
+    fn a_cube_survives_a_round_trip() {
+        let mut cube = Mesh::cube(1.0, 2);
+        // An edge along one side, which comes back as a line
+        let start = cube.indices.len();
+        cube.indices.extend([0, 1]);
+        cube.ranges.push(DrawRange { primitive: Primitive::Lines, start, count: 2, group: None });
+        let material = Material { diffuse_color: [1.0, 0.0, 0.0], ..Material::default() };
+
+        for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian, PlyFormat::BinaryBigEndian] {
+            let mut out = Vec::new();
+            write_ply_meshes(&[(&cube, &material)], &mut out, format).unwrap();
+            let read = read_ply(&out[..], "test.ply").unwrap();
+
+            assert_eq!(triangle_positions(&read), triangle_positions(&cube));
+            assert_eq!(read.positions, cube.positions);
+            assert_eq!(read.normals, cube.normals);
+            assert_eq!(read.tex_coords, cube.tex_coords);
+            let lines: Vec<&DrawRange> = read.ranges.iter().filter(|range| range.primitive == Primitive::Lines).collect();
+            assert_eq!(lines.len(), 1);
+            assert_eq!(read.range_indices(lines[0]), [0, 1]);
+        }
+    }
+
+    #[test]
+    fn meshes_without_colors_take_their_materials() {
+        let cube = Mesh::cube(1.0, 1);
+        let mut colored = cube.clone();
+        colored.colors = Some(vec![[0.0, 1.0, 0.0]; colored.positions.len()]);
+        let red = Material { diffuse_color: [1.0, 0.0, 0.0], ..Material::default() };
+
+        let mut out = Vec::new();
+        write_ply_meshes(&[(&cube, &red), (&colored, &red)], &mut out, PlyFormat::Ascii).unwrap();
+        let read = read_ply(&out[..], "test.ply").unwrap();
+        let colors = read.colors.as_ref().unwrap();
+        let n = cube.positions.len();
+        assert!(colors[..n].iter().all(|c| *c == [1.0, 0.0, 0.0]));
+        assert!(colors[n..].iter().all(|c| *c == [0.0, 1.0, 0.0]));
+        // The second mesh's indices were moved up past the first's vertices
+        assert_eq!(read.triangles().count(), cube.triangles().count() * 2);
+        assert!(read.triangles().skip(cube.triangles().count()).flatten().all(|v| v as usize >= n));
+    }
+}
@@ -0,0 +1,112 @@
+use std::{fs, io::{self, BufWriter, Write}};
+
+use super::*;
+
+/// How an STL is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+/// Writes the triangles of `shapes` to `path` as one STL
+pub fn export_stl(shapes: &[&Shape], path: &str, format: StlFormat) -> io::Result<()> {
+    write_stl(shapes, BufWriter::new(fs::File::create(path)?), format)
+}
+
+/// Writes the triangles of `shapes` as one STL to `out`
+/// STL has no transforms, so everything is written where it is in world space
+/// Facet normals are worked out from the triangles, since vertex normals can't be stored
+pub fn write_stl<W: Write>(shapes: &[&Shape], out: W, format: StlFormat) -> io::Result<()> {
+    let meshes: Vec<_> = shapes.iter().map(|shape| (&shape.mesh, shape.transform.transform_matrix)).collect();
+    write_stl_meshes(&meshes, out, format)
+}
+
+/// Like `write_stl`, for meshes that haven't been uploaded to the GPU, each with the matrix that puts it in world space
+pub fn write_stl_meshes<W: Write>(meshes: &[(&Mesh, Mat4)], mut out: W, format: StlFormat) -> io::Result<()> {
+    let facets = meshes.iter().flat_map(|&(mesh, matrix)| {
+        // A mirroring transform turns faces inside out unless we flip them back
+        let flip = matrix.determinant3() < 0.0;
+        mesh.triangles().map(move |[a, b, c]| {
+            let (b, c) = if flip { (c, b) } else { (b, c) };
+            let corner = |idx: u32| matrix.transform_point(&mesh.positions[idx as usize]);
+            let corners = [corner(a), corner(b), corner(c)];
+            (face_normal(&corners), corners)
+        })
+    });
+
+    match format {
+        StlFormat::Ascii => {
+            writeln!(out, "solid graphics-lib")?;
+            for (n, corners) in facets {
+                writeln!(out, "  facet normal {} {} {}", n[0], n[1], n[2])?;
+                writeln!(out, "    outer loop")?;
+                for p in corners {
+                    writeln!(out, "      vertex {} {} {}", p[0], p[1], p[2])?;
+                }
+                writeln!(out, "    endloop")?;
+                writeln!(out, "  endfacet")?;
+            }
+            writeln!(out, "endsolid graphics-lib")?;
+        },
+        StlFormat::Binary => {
+            let mut header = [0u8; 80];
+            // Binary headers mustn't start with "solid", or some readers will think it's ascii
+            let text = b"Exported by graphics-lib";
+            header[..text.len()].copy_from_slice(text);
+            out.write_all(&header)?;
+
+            let count: usize = meshes.iter().map(|(mesh, _)| mesh.triangles().count()).sum();
+            out.write_all(&(count as u32).to_le_bytes())?;
+            for (n, corners) in facets {
+                for x in n.into_iter().chain(corners.into_iter().flatten()) {
+                    out.write_all(&x.to_le_bytes())?;
+                }
+                out.write_all(&[0, 0])?;
+            }
+        },
+    }
+    out.flush()
+}
+
+impl Shape {
+    /// Writes this shape's triangles to `path` as an STL, in world space
+    pub fn export_stl(&self, path: &str, format: StlFormat) -> io::Result<()> {
+        export_stl(&[self], path, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::three_d::mesh::tests::triangle_positions;
+
+    #[test]
+    fn a_cube_survives_a_round_trip() {
+        let cube = Mesh::cube(1.0, 2);
+        for format in [StlFormat::Ascii, StlFormat::Binary] {
+            let mut out = Vec::new();
+            write_stl_meshes(&[(&cube, IDENTITY)], &mut out, format).unwrap();
+            let read = read_stl(&out[..], "test.stl", &ImportOptions::default()).unwrap();
+            assert_eq!(triangle_positions(&read), triangle_positions(&cube));
+        }
+    }
+
+    #[test]
+    fn mirrored_meshes_are_turned_back_the_right_way() {
+        let cube = Mesh::cube(1.0, 1);
+        let mirror = Mat4::new([[-1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [2.0, 0.0, 0.0, 1.0]]);
+        let mut out = Vec::new();
+        write_stl_meshes(&[(&cube, mirror)], &mut out, StlFormat::Binary).unwrap();
+        let read = read_stl(&out[..], "test.stl", &ImportOptions::default()).unwrap();
+
+        assert_eq!(read.bounds(), Some(Bounds { min: [1.5, -0.5, -0.5], max: [2.5, 0.5, 0.5] }));
+        // Every facet's normal agrees with its winding, and points away from the middle of the cube
+        for [a, b, c] in read.triangles() {
+            let corners = [a, b, c].map(|v| read.positions[v as usize]);
+            let normal = read.normals[a as usize];
+            assert_eq!(normal, face_normal(&corners));
+            assert!(dot(normal, sub(corners[0], [2.0, 0.0, 0.0])) > 0.0);
+        }
+    }
+}
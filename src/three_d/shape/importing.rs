@@ -1,26 +1,305 @@
-use std::{fs, collections::HashMap, path::Path};
+use std::{fs, fmt, io::{self, BufRead, BufReader, Read}, collections::{HashMap, hash_map::Entry}, hash::Hash};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
 
 use crate::three_d::shaders;
 
 use super::*;
 
-pub const MAT: Material = Material {
-    ambient_color: [0.2, 0.0, 0.0],
-    diffuse_color: [0.6, 0.0, 0.0],
-    emission_color: [0.0; 3],
-    specular_color: [1.0, 1.0, 1.0],
-    specular_exp: 16.0,
-    transparency: 1.0,
-    transmission_filter_color: None,
-    optical_density: None,
-    illum_model: None,
-};
+mod mtl;
+pub use mtl::*;
+mod gltf;
+pub use gltf::load_gltf;
+mod ply;
+pub use ply::{PlyFormat, read_ply};
+mod stl;
+pub use stl::read_stl;
+mod loading;
+pub use loading::*;
+mod cache;
+pub use cache::*;
+mod processing;
+pub use processing::{check_meshes, process_meshes};
 
 #[derive(Debug)]
 pub enum ImportError {
     IncorrectExtension,
-    FileError(Box<dyn std::error::Error>),
-    UnexpectedPolygon,
+    FileError(Box<dyn std::error::Error + Send + Sync>),
+    /// A token was there, but it couldn't be understood
+    Parse { file: String, line: usize, column: usize, message: String },
+    /// A statement ended before all of its arguments were given
+    MissingToken { file: String, line: usize, expected: &'static str },
+    /// A face, line or point referred to an element that doesn't exist
+    IndexOutOfRange { file: String, line: usize, index: isize, len: usize },
+    /// `usemtl` named a material that isn't in any loaded library
+    MissingMaterial { file: String, line: usize, name: String },
+    /// The file doesn't follow its format, for formats that aren't read line by line
+    Invalid { file: String, message: String },
+    /// A background load's result was asked for after it had already been handed out
+    ResultTaken,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::IncorrectExtension => write!(f, "file has the wrong extension"),
+            ImportError::FileError(err) => write!(f, "couldn't read file: {err}"),
+            ImportError::Parse { file, line, column, message } => 
+                write!(f, "{file}:{line}:{column}: {message}"),
+            ImportError::MissingToken { file, line, expected } => 
+                write!(f, "{file}:{line}: expected {expected}, but the line ended"),
+            ImportError::IndexOutOfRange { file, line, index, len } => 
+                write!(f, "{file}:{line}: index {index} is out of range, only {len} elements are defined"),
+            ImportError::MissingMaterial { file, line, name } => 
+                write!(f, "{file}:{line}: no material named '{name}' was loaded"),
+            ImportError::Invalid { file, message } => write!(f, "{file}: {message}"),
+            ImportError::ResultTaken => write!(f, "the load's result was already handed out"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+fn invalid(file: &str, message: String) -> ImportError {
+    ImportError::Invalid { file: file.to_string(), message }
+}
+
+/// Something in a file we skipped or had to change, that didn't stop it from loading
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportWarning {
+    pub file: String,
+    /// For formats that are read line by line
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: {}", self.file, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// Everything that came up while importing a file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl ImportReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    fn warn(&mut self, file: &str, line: Option<usize>, message: String) {
+        log::debug!("{file}: {message}");
+        self.warnings.push(ImportWarning { file: file.to_string(), line, message });
+    }
+
+    /// Logs every warning, for the importers that can't hand the report back
+    fn log(&self) {
+        for warning in &self.warnings {
+            log::warn!("{warning}");
+        }
+    }
+}
+
+/// Settings that change how files are imported
+#[derive(Clone, Debug, Default)]
+pub struct ImportOptions {
+    /// Ignore the ambient color (`Ka`) of materials and use black instead
+    pub zero_ambient: bool,
+    /// Share corners at the same position between triangles, for formats that give each triangle its own (like STL)
+    /// This smooths normals across the shared corners, instead of shading each triangle flat
+    pub weld_vertices: bool,
+    /// Keep a binary copy of what was loaded next to the file, and load from that while the file is unchanged
+    /// Only `load_file` and `load_in_background` use this, and animations aren't kept in the copy
+    pub cache: bool,
+    /// Move the geometry so the middle of its bounding box is at the origin
+    pub recenter: bool,
+    /// Scale the geometry so its longest side is 1 long
+    pub normalize: bool,
+    /// The file has Z pointing up, so turn it to have Y up like everything else
+    pub z_up: bool,
+    /// Turn every triangle around, for files wound clockwise
+    pub flip_winding: bool,
+    /// Flip texture coordinates upside down, for files that count V from the top of the image
+    pub flip_v: bool,
+    /// Multiply every position by this
+    pub scale: Option<f32>,
+    /// Where normals come from
+    pub normals: NormalSource,
+    /// Merge vertices closer together than this, along with anything that collapses because of it
+    pub merge_distance: Option<f32>,
+    /// Check each mesh for problems like degenerate triangles or NaN positions, and report them as warnings
+    pub validate: bool,
+    /// Fix what can be fixed of those problems: welding duplicate vertices, dropping broken and degenerate
+    /// triangles, making the winding agree and renormalizing normals. This checks the meshes too
+    pub repair: bool,
+    /// How many times to subdivide each shape, smoothing it out, see `Mesh::subdivide`
+    pub subdivisions: usize,
+    /// Edges where faces meet at more than this angle, in radians, stay sharp when subdividing
+    pub crease_angle: Option<f32>,
+    /// How many simpler versions of each shape to make for drawing at a distance, see `Mesh::lod_chain`
+    pub lod_levels: usize,
+    /// Reorder each shape's triangles and vertices to draw faster, see `Mesh::optimize`
+    /// How much it helped is logged as the average number of vertices transformed per triangle, before and after
+    pub optimize: bool,
+}
+
+/// How an import gets its normals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalSource {
+    /// Use the file's normals, and only work them out for vertices that don't have one
+    #[default]
+    File,
+    /// Average the normals of the triangles around each vertex, ignoring the file's
+    Smooth,
+    /// Give each triangle its own normal, ignoring the file's
+    Flat,
+}
+
+/// Finds the files that an OBJ or MTL file refers to by name, like material libraries and textures
+/// Implement this to load assets from somewhere other than the filesystem, like an archive
+pub trait Resolver {
+    /// Opens `name`, exactly as it's written in the file that refers to it
+    fn open(&self, name: &str) -> io::Result<Box<dyn BufRead + '_>>;
+}
+
+/// Resolves names relative to a directory on disk
+pub struct FileResolver {
+    pub dir: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new<P: AsRef<Path>>(dir: P) -> FileResolver {
+        FileResolver { dir: dir.as_ref().to_path_buf() }
+    }
+
+    /// Where the file a name refers to is on disk
+    pub fn path(&self, name: &str) -> PathBuf {
+        // Files exported on Windows often use backslashes in their paths
+        #[cfg(not(windows))]
+        let name = name.replace('\\', "/");
+
+        self.dir.join(name)
+    }
+}
+
+impl Resolver for FileResolver {
+    fn open(&self, name: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        Ok(Box::new(BufReader::new(fs::File::open(self.path(name))?)))
+    }
+}
+
+/// For files that don't refer to anything, or when those references should be ignored
+pub struct NoResolver;
+
+impl Resolver for NoResolver {
+    fn open(&self, name: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("no resolver to find '{name}'")))
+    }
+}
+
+/// Any closure from a name to the file's bytes works as a resolver, which is handy for `include_bytes!`
+impl<F> Resolver for F
+where
+    F: Fn(&str) -> io::Result<&'static [u8]>,
+{
+    fn open(&self, name: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        Ok(Box::new(self(name)?))
+    }
+}
+
+/// Reads everything left in `reader`, replacing any invalid UTF-8
+fn read_text<R: Read>(mut reader: R) -> Result<String, ImportError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|err| ImportError::FileError(Box::new(err)))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Splits a line into whitespace separated tokens, keeping track of where each one is
+/// so that errors can point at the exact spot that went wrong
+#[derive(Clone, Copy)]
+struct Tokens<'a> {
+    file: &'a str,
+    line_no: usize,
+    line: &'a str,
+    pos: usize,
+    // Column of the last token handed out
+    column: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(file: &'a str, line_no: usize, line: &'a str) -> Tokens<'a> {
+        Tokens { file, line_no, line, pos: 0, column: 1 }
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = &self.line[self.pos..];
+        let start = self.pos + rest.len() - rest.trim_start().len();
+        if start == self.line.len() {
+            return None;
+        }
+        let len = self.line[start..].find(|c: char| c.is_ascii_whitespace()).unwrap_or(self.line.len() - start);
+
+        self.pos = start + len;
+        self.column = start + 1;
+        Some(&self.line[start..self.pos])
+    }
+
+    /// The next token, without moving past it
+    fn peek(&self) -> Option<&'a str> {
+        let mut copy = *self;
+        copy.next()
+    }
+
+    /// Moves past the next token only if it parses as a `T`
+    fn parse_if<T: FromStr>(&mut self) -> Option<T> {
+        let value = self.peek()?.parse().ok()?;
+        self.next();
+        Some(value)
+    }
+
+    /// Everything that hasn't been handed out yet, without surrounding whitespace
+    fn rest(&mut self) -> &'a str {
+        let rest = self.line[self.pos..].trim();
+        self.pos = self.line.len();
+        rest
+    }
+
+    fn expect(&mut self, expected: &'static str) -> Result<&'a str, ImportError> {
+        self.next().ok_or_else(|| self.missing(expected))
+    }
+
+    fn parse<T: FromStr>(&mut self, expected: &'static str) -> Result<T, ImportError> {
+        let tok = self.expect(expected)?;
+        self.parse_token(tok, expected)
+    }
+
+    /// Like `parse`, but the line is allowed to end instead
+    fn parse_opt<T: FromStr>(&mut self, expected: &'static str) -> Result<Option<T>, ImportError> {
+        match self.next() {
+            Some(tok) => self.parse_token(tok, expected).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_token<T: FromStr>(&self, tok: &str, expected: &'static str) -> Result<T, ImportError> {
+        tok.parse().map_err(|_| self.error(format!("expected {expected}, found '{tok}'")))
+    }
+
+    /// An error pointing at the last token handed out
+    fn error(&self, message: String) -> ImportError {
+        ImportError::Parse { file: self.file.to_string(), line: self.line_no, column: self.column, message }
+    }
+
+    fn missing(&self, expected: &'static str) -> ImportError {
+        ImportError::MissingToken { file: self.file.to_string(), line: self.line_no, expected }
+    }
 }
 
 /* 
@@ -188,377 +467,669 @@ pub fn from_obj_2(path: &str, vao_lock: &VAOLock) -> Result<(VertexBuffer, Index
 }
 */
 
-impl Shape {
-    pub fn from_obj(
-        path: &str,
-        shader_type: shaders::ShaderType,
-        // FIXME: We shouldn't have to specify a shader type and the program itself!
-        transform: Option<Transform>, 
-        animation: Option<Box<dyn Animation>>, 
-        _bface_culling: bool,) -> Result<Shape, ImportError> 
-    {
-        if &path[path.len() - 4..] != ".obj" { return Err(ImportError::IncorrectExtension); }
-        let bytes = fs::read(path);
-        if let Err(err) = bytes {
-            return Err(ImportError::FileError(Box::new(err)));
+/// The raw vertex data listed in an OBJ file, before it's indexed for drawing
+#[derive(Default)]
+struct ObjData {
+    positions: Vec<Vertex>,
+    // One color per position, white if the file didn't give one
+    colors: Vec<Color>,
+    has_colors: bool,
+    tex_coords: Vec<TexCoord>,
+    normals: Vec<Normal>,
+    // Only free-form curves and surfaces refer to these, and we can't draw those yet
+    param_vertices: Vec<[f32; 3]>,
+}
+
+/// Which position, texture coord and normal one corner of an element refers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct VertexKey {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Joins lines ending in a `\` onto the line after them and strips comments
+/// Each logical line comes with the (1-based) number of the line it started on
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut start = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if start.is_none() {
+            start = Some(i + 1);
         }
-        let f = String::from_utf8_lossy(bytes.as_ref().unwrap());
-        let lines = f.split("\n");
-
-        // Get materials library
-        let mut new_path = String::from(&path[..path.len() - 3]);
-        new_path.push_str("mtl");
-        let mat_map = if Path::new(&new_path).exists() {
-            Material::from_mtl(&new_path)?
-        } else { HashMap::new() };
-
-        let mut vertices = Vec::new();
-        //let mut tex_coords = Vec::new();
-        let mut normals: Vec<Normal> = Vec::new();
-        
-        let mut vertices_out = Vec::new();
-        let mut normals_out = Vec::new();
-
-        let mut indices: Vec<u32> = Vec::new();
-
-        let mut material_name = None;
-
-        for line in lines {
-            let mut tokens = line.split_ascii_whitespace();
-
-            match tokens.next().unwrap_or("") {
-                // Vertex coords
-                "v" => {   
-                    // We don't care about w, so just find the first three numbers
-                    let x = tokens.next().unwrap().parse().unwrap();
-                    let y = tokens.next().unwrap().parse().unwrap();
-                    let z = tokens.next().unwrap().parse().unwrap();
-
-                    let v = [x, y, z];
-                    vertices.push(v);
-                },
-                "vt" => {
-                    // We don't handle these yet
-                    println!("Found texture coord, skipping");
-                },
-                "vn" => {
-                    // Normal
-                    // We will assume these are unit vectors for now
-                    // TODO: Fix this assumption
-                    let x = tokens.next().unwrap().parse().unwrap();
-                    let y = tokens.next().unwrap().parse().unwrap();
-                    let z = tokens.next().unwrap().parse().unwrap();
-
-                    let v = [x, y, z];
-                    normals.push(v);
-                },
-                "vp" => {
-                    // We skip these for now
-                    // TODO: What do these do?
-                    println!("Found parameter space vertex, skipping");
-                },
-                "f" => {
-                    // Create a face
-                    // TODO: I currently don't understand how glium matches normals with vertices
-                    // I think it assumes that one index refers to both the vertex and the normal
-                    // This is not true, and we need to fix this
-
-                    // TODO: This code currently assumes that the obj provides normal information. Fix!
-
-                    // Closure to create a new index
-                    let mut add_idx = |v: usize, n: usize| {
-                        // If we already have this vertex/normal pair, just reuse it
-                        
-                        let mut new_idx = None;
-                        for i in 0..indices.len() {
-                            let idx = indices[i] as usize;
-                            if (vertices[v], normals[n]) == (vertices_out[idx], normals_out[idx]) {
-                                new_idx = Some(indices[i]);
-                                break;
-                            }
-                        }
-                        
-                         
-
-                        if new_idx.is_none() {
-                            // Make a new pair and add it
-                            vertices_out.push(vertices[v]);
-                            normals_out.push(normals[n]);
-                            // Leaving this here for testing
-                            assert_eq!(vertices_out.len(), normals_out.len());
-                            new_idx = Some(vertices_out.len() as u32 - 1);
-                        }
-                        indices.push(new_idx.unwrap());
-                    };
-
-                    let tok = tokens.next().unwrap();
-
-                    let mut tok = tok.split("/");
-                    let v1: usize = tok.next().unwrap().parse().unwrap();
-                    let n1: usize = tok.skip(1).next().unwrap().parse().unwrap();
- 
-                    let mut tok2 = tokens.next().unwrap().split("/");
-                    let v2: usize = tok2.next().unwrap().parse().unwrap();
-                    let n2: usize = tok2.skip(1).next().unwrap().parse().unwrap();
-
-                    let mut tok3 = tokens.next().unwrap().split("/");
-                    let v3: usize = tok3.next().unwrap().parse().unwrap();
-                    let n3: usize = tok3.skip(1).next().unwrap().parse().unwrap();
-
-                    add_idx(v1 - 1, n1 - 1);
-                    add_idx(v2 - 1, n2 - 1);
-                    add_idx(v3 - 1, n3 - 1);
-
-                    // Are there actually more vertices
-                    if let Some(tok) = tokens.next() {
-                        // If this line isn't a comment, then there are more faces
-                        if &tok[0..1] != "#" {
-                            // We don't handle polgyons at this point, so return an error
-                            println!("{}{:?}", tok, tokens);
-                            return Err(ImportError::UnexpectedPolygon);
-                        }
-                    }                    
-                },
-                "o" => {
-                    // Have we actually parsed a shape yet?
-                    if false {
-                        let (vao, vao_lock) = VertexArrayObject::new().unwrap();
-
-                        let positions = VertexBuffer::new(&vertices_out, &vao_lock);
-                        let normals_buffer = NormalBuffer::new(&normals_out, &vao_lock);
-                        let indices_buffer = IndexBuffer::new(&indices, &vao_lock); 
-
-                        // Make sure program attributes are set up correctly
-                        //program_setup(&program, &vao_lock);
-
-                        let new_animation = if let Some(b) = &animation {
-                            Some(b.clone_box())
-                        } else { None };
-
-                        // Is there a material for this object?
-                        let material = *mat_map.get(&material_name.unwrap_or_default()).unwrap_or(&Material::default());
-
-                        let s = Shape {
-                            vao,
-                            positions, 
-                            normals: normals_buffer, 
-                            indices: indices_buffer, 
-                            transform: transform.unwrap_or_default(), 
-                            animation: new_animation, shader_type, 
-                            material
-                        };
-
-                        // We don't clear the vertices or normals lists
-                        vertices_out.clear();
-                        normals_out.clear();
-                        indices.clear();
-                    }
-                    
-                    eprintln!("WARNING: Loading two objects from the same .obj file can cause problems");
-                    material_name = None;
-                },
-                "usemtl" => {
-                    material_name = Some(String::from(tokens.next().unwrap()));
-                },
-                "" => {},
-
-                _ => { println!("Found line {line}, skipping") },
-            }
+
+        if let Some(continued) = line.strip_suffix('\\') {
+            current.push_str(continued);
+            current.push(' ');
+        } else {
+            current.push_str(line);
+            lines.push((start.take().unwrap(), std::mem::take(&mut current)));
         }
+    }
+    // A continuation on the very last line just ends the line
+    if let Some(start) = start {
+        lines.push((start, current));
+    }
 
-        let (vao, vao_lock) = VertexArrayObject::new().unwrap();
+    for (_, line) in lines.iter_mut() {
+        if let Some(comment) = line.find('#') {
+            line.truncate(comment);
+        }
+    }
+    lines
+}
 
-        let positions = VertexBuffer::new(&vertices_out, &vao_lock);
-        let normals_buffer = NormalBuffer::new(&normals_out, &vao_lock);
-        let indices_buffer = IndexBuffer::new(&indices, &vao_lock); 
+/// Turns a 1-based OBJ index into a 0-based one
+/// Negative indices count backwards from the last element defined so far, so -1 is the most recent one
+fn resolve_index(idx: isize, len: usize) -> Option<usize> {
+    if idx > 0 && idx as usize <= len {
+        Some(idx as usize - 1)
+    } else if idx < 0 && idx.unsigned_abs() <= len {
+        Some(len - idx.unsigned_abs())
+    } else {
+        None
+    }
+}
 
-        // Make sure program attributes are set up correctly
-        //program_setup(&program, &vao_lock);
+/// A corner of a face, line or point as it's written, before its indices are resolved
+#[derive(Clone, Copy)]
+struct RawCorner {
+    position: isize,
+    tex_coord: Option<isize>,
+    normal: Option<isize>,
+}
 
-        let new_animation = if let Some(b) = &animation {
-            Some(b.clone_box())
-        } else { None };
+/// Parses one corner of a face, line or point, in any of the forms `v`, `v/vt`, `v//vn` or `v/vt/vn`
+fn parse_corner(tok: &str, tokens: &Tokens) -> Result<RawCorner, ImportError> {
+    let mut parts = tok.split('/');
+    let index = |part: Option<&str>| -> Result<Option<isize>, ImportError> {
+        match part.filter(|s| !s.is_empty()) {
+            Some(part) => tokens.parse_token(part, "a vertex index").map(Some),
+            None => Ok(None),
+        }
+    };
 
-        // Is there a material for this object?
-        let material = *mat_map.get(&material_name.unwrap_or_default()).unwrap_or(&Material::default());
+    let position = index(parts.next())?.ok_or_else(|| tokens.error(format!("vertex '{tok}' has no position")))?;
+    let tex_coord = index(parts.next())?;
+    let normal = index(parts.next())?;
+    if parts.next().is_some() {
+        return Err(tokens.error(format!("too many '/' in vertex '{tok}'")));
+    }
 
-        let s = Shape {
-            vao,
-            positions, 
-            normals: normals_buffer, 
-            indices: indices_buffer, 
-            transform: transform.unwrap_or_default(), 
-            animation: new_animation, shader_type, 
-            material
-        };
+    Ok(RawCorner { position, tex_coord, normal })
+}
 
-        Ok(s)
+/// Parses every remaining token on the line as a corner
+fn parse_corners(tokens: &mut Tokens) -> Result<Vec<RawCorner>, ImportError> {
+    let mut corners = Vec::new();
+    while let Some(tok) = tokens.next() {
+        corners.push(parse_corner(tok, tokens)?);
     }
-        
+    Ok(corners)
 }
 
-#[derive(Clone, Copy, Debug)]
-// This could be done better as multiple settings instead of one list
-pub enum IlluminationModel {
-    COnAmOff,
-    COnAmOn,
-    HighOn,
-    ReOnRTOn,
-    TransGlassOnReRTOn,
-    ReFresOnRTOn,
-    TransRefracOnReFresOffRtOn,
-    TransRefracOnReFresOnRtOn,
-    ReOnRTOff,
-    TransGlassOnReRTOff,
-    InvisShadow,
+/// Resolves corners against everything defined before them, and adds them to the builder
+fn add_corners(corners: &[RawCorner], file: &str, line: usize, data: &ObjData, builder: &mut MeshBuilder) -> Result<Vec<u32>, ImportError> {
+    let resolve = |index: isize, len: usize| {
+        resolve_index(index, len).ok_or_else(|| ImportError::IndexOutOfRange { file: file.to_string(), line, index, len })
+    };
+
+    corners.iter().map(|corner| {
+        let key = VertexKey {
+            position: resolve(corner.position, data.positions.len())?,
+            tex_coord: corner.tex_coord.map(|idx| resolve(idx, data.tex_coords.len())).transpose()?,
+            normal: corner.normal.map(|idx| resolve(idx, data.normals.len())).transpose()?,
+        };
+        Ok(builder.add_vertex(key, data))
+    }).collect()
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Material {
-    pub ambient_color: [f32; 3],
-    pub diffuse_color: [f32; 3],
-    pub emission_color: [f32; 3],
-    pub specular_color: [f32; 3],
-    pub specular_exp: f32,
-    pub transparency: f32,
-    pub transmission_filter_color: Option<[f32; 3]>,
-    pub optical_density: Option<f32>,
-    pub illum_model: Option<IlluminationModel>,
+/// One logical line of an OBJ, with its numbers parsed
+/// This doesn't depend on any other line, so it can be done on many threads at once,
+/// and only putting the statements together has to happen in order
+enum Statement {
+    Position(Vertex, Option<Color>),
+    TexCoord(TexCoord),
+    Normal(Normal),
+    ParamVertex([f32; 3]),
+    Face(Vec<RawCorner>),
+    Line(Vec<RawCorner>),
+    Points(Vec<RawCorner>),
+    Object(Option<String>),
+    Group(Option<String>),
+    MaterialLibs(Vec<String>),
+    UseMaterial(String),
+    Empty,
+    Unknown,
 }
 
-pub const DEFAULT_SPEC_EXP: f32 = 16.0;
+fn parse_statement(tokens: &mut Tokens) -> Result<Statement, ImportError> {
+    Ok(match tokens.next().unwrap_or("") {
+        // Vertex coords
+        "v" => {
+            // Either x y z [w], or x y z followed by a color
+            let mut values: Vec<f32> = Vec::with_capacity(7);
+            for expected in ["an x coordinate", "a y coordinate", "a z coordinate"] {
+                values.push(tokens.parse(expected)?);
+            }
+            while let Some(value) = tokens.parse_opt("a number")? {
+                values.push(value);
+            }
+
+            // We don't care about w, so skip it if it's there
+            let color = match values.len() {
+                3 | 4 => None,
+                6 => Some([values[3], values[4], values[5]]),
+                7 => Some([values[4], values[5], values[6]]),
+                _ => return Err(tokens.error(format!("expected 3, 4, 6 or 7 numbers, found {}", values.len()))),
+            };
+            Statement::Position([values[0], values[1], values[2]], color)
+        },
+        "vt" => {
+            // v is optional, and we don't use w
+            let u = tokens.parse("a u coordinate")?;
+            let v = tokens.parse_opt("a v coordinate")?.unwrap_or(0.0);
+
+            Statement::TexCoord([u, v])
+        },
+        "vn" => {
+            // Normal
+            // We will assume these are unit vectors for now
+            // TODO: Fix this assumption
+            let x = tokens.parse("an x coordinate")?;
+            let y = tokens.parse("a y coordinate")?;
+            let z = tokens.parse("a z coordinate")?;
+
+            Statement::Normal([x, y, z])
+        },
+        "vp" => {
+            // u [v [w]]
+            let u = tokens.parse("a u coordinate")?;
+            let v = tokens.parse_opt("a v coordinate")?.unwrap_or(0.0);
+            let w = tokens.parse_opt("a weight")?.unwrap_or(1.0);
+
+            Statement::ParamVertex([u, v, w])
+        },
+        "f" => {
+            let corners = parse_corners(tokens)?;
+            if corners.len() < 3 {
+                return Err(tokens.error(format!("a face needs at least 3 vertices, found {}", corners.len())));
+            }
+            Statement::Face(corners)
+        },
+        "l" => {
+            let corners = parse_corners(tokens)?;
+            if corners.len() < 2 {
+                return Err(tokens.error(format!("a line needs at least 2 vertices, found {}", corners.len())));
+            }
+            Statement::Line(corners)
+        },
+        "p" => Statement::Points(parse_corners(tokens)?),
+        "o" => Statement::Object(Some(tokens.rest().to_string()).filter(|name| !name.is_empty())),
+        "g" => {
+            // A bare `g` goes back to the default group
+            let names: Vec<&str> = std::iter::from_fn(|| tokens.next()).collect();
+            Statement::Group(if names.is_empty() { None } else { Some(names.join(" ")) })
+        },
+        "mtllib" => {
+            // Any number of libraries can be listed, and they all get merged together
+            let first = tokens.expect("a material library")?;
+            let libs = std::iter::once(first).chain(std::iter::from_fn(|| tokens.next())).map(String::from).collect();
+            Statement::MaterialLibs(libs)
+        },
+        "usemtl" => {
+            // Names can have spaces in them, the same as in `newmtl`
+            let name = tokens.rest();
+            if name.is_empty() {
+                return Err(tokens.missing("a material name"));
+            }
+            Statement::UseMaterial(name.to_string())
+        },
+        "" => Statement::Empty,
+        _ => Statement::Unknown,
+    })
+}
 
-impl Default for Material {
+/// Files with fewer lines than this are parsed on the calling thread, since starting threads would take longer
+const PARALLEL_LINES: usize = 50_000;
+/// How many lines are parsed between progress updates
+const PROGRESS_STEP: usize = 4096;
+
+/// Parses every line, splitting big files across all the cores we have
+fn parse_statements(lines: &[(usize, String)], name: &str, progress: Option<&Progress>) -> Vec<Result<Statement, ImportError>> {
+    let parse_chunk = |chunk: &[(usize, String)]| {
+        let mut statements = Vec::with_capacity(chunk.len());
+        for step in chunk.chunks(PROGRESS_STEP) {
+            statements.extend(step.iter().map(|(line_no, line)| parse_statement(&mut Tokens::new(name, *line_no, line))));
+            if let Some(progress) = progress {
+                progress.advance(step.len());
+            }
+        }
+        statements
+    };
+
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    if lines.len() < PARALLEL_LINES || threads == 1 {
+        return parse_chunk(lines);
+    }
+    thread::scope(|scope| {
+        let workers: Vec<_> = lines.chunks(lines.len().div_ceil(threads))
+            .map(|chunk| scope.spawn(move || parse_chunk(chunk)))
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().expect("an OBJ parsing thread panicked")).collect()
+    })
+}
+
+/// Hands out one index per distinct vertex key, reusing the index of any key it has already seen
+/// This is what lets faces that share a corner share a vertex in the buffers
+pub struct VertexWelder<K> {
+    indices: HashMap<K, u32>,
+}
+
+impl<K: Hash + Eq> VertexWelder<K> {
+    pub fn new() -> VertexWelder<K> {
+        VertexWelder { indices: HashMap::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> VertexWelder<K> {
+        VertexWelder { indices: HashMap::with_capacity(capacity) }
+    }
+
+    /// Returns the index for `key`, and whether this is the first time we've seen it
+    pub fn weld(&mut self, key: K) -> (u32, bool) {
+        let next = self.indices.len() as u32;
+        match self.indices.entry(key) {
+            Entry::Occupied(e) => (*e.get(), false),
+            Entry::Vacant(e) => {
+                e.insert(next);
+                (next, true)
+            },
+        }
+    }
+
+    /// The number of distinct vertices seen so far
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+impl<K: Hash + Eq> Default for VertexWelder<K> {
     fn default() -> Self {
-        Material { ambient_color: [0.0; 3], diffuse_color: [1.0; 3], emission_color: [0.0; 3],
-            specular_color: [0.0; 3], specular_exp: DEFAULT_SPEC_EXP, 
-            transparency: 1.0, transmission_filter_color: None, optical_density: None, illum_model: None}
+        VertexWelder::new()
     }
 }
 
-impl Material {
-    pub fn from_mtl(path: &str) -> Result<HashMap<String, Material>, ImportError> {
-        let bytes = fs::read(path);
-        if let Err(err) = bytes {
-            return Err(ImportError::FileError(Box::new(err)));
+/// The elements listed under one `g`, `o` or `usemtl` statement
+#[derive(Default)]
+struct ElementGroup {
+    name: Option<String>,
+    material: Option<String>,
+    triangles: Vec<u32>,
+    lines: Vec<u32>,
+    points: Vec<u32>,
+}
+
+impl ElementGroup {
+    fn is_empty(&self) -> bool {
+        self.triangles.is_empty() && self.lines.is_empty() && self.points.is_empty()
+    }
+}
+
+/// Collects the deduplicated vertices and the indices that get uploaded to the GPU
+#[derive(Default)]
+struct MeshBuilder {
+    welder: VertexWelder<VertexKey>,
+    keys: Vec<VertexKey>,
+    positions: Vec<Vertex>,
+    normals: Vec<Normal>,
+    colors: Vec<Color>,
+    tex_coords: Vec<TexCoord>,
+
+    // The last one is the group elements are currently being added to
+    groups: Vec<ElementGroup>,
+}
+
+impl MeshBuilder {
+    fn group(&mut self) -> &mut ElementGroup {
+        if self.groups.is_empty() {
+            self.groups.push(ElementGroup::default());
         }
-        let f = String::from_utf8_lossy(bytes.as_ref().unwrap());
-        let lines = f.split("\n");
-
-        let mut materials = HashMap::new();
-        let mut current_mat: Option<Material> = None;
-        let mut current_mat_name: Option<String> = None;
-
-        'lines_loop: for line in lines {
-            let mut tokens = line.split_ascii_whitespace();
-            println!("{current_mat:?}");
-
-            match tokens.next().unwrap_or("") {
-                "newmtl" => {
-                    // Insert old material
-                    if let Some(mat) = current_mat {
-                        materials.insert(current_mat_name.unwrap().clone(), mat.clone());
-                    }
+        self.groups.last_mut().unwrap()
+    }
 
-                    // Create new material
-                    current_mat = Some(Material::default());
-                    current_mat_name = tokens.next().map(|x| x.to_string());
-                }
-                "Ka" => {
-                    let r: f32 = tokens.next().unwrap().parse().unwrap();
-                    let g: f32 = tokens.next().unwrap().parse().unwrap();
-                    let b: f32 = tokens.next().unwrap().parse().unwrap();
-
-                    // TODO: Blender exports with white ambient light, but that's wrong
-                    // Fix it
-                    println!("Overriding ambient color as black!");
-                    current_mat.as_mut().unwrap().ambient_color = [0.0; 3];
-                },
-                "Kd" => {
-                    let r = tokens.next().unwrap().parse().unwrap();
-                    let g = tokens.next().unwrap().parse().unwrap();
-                    let b = tokens.next().unwrap().parse().unwrap();
-
-                    current_mat.as_mut().unwrap().diffuse_color = [r, g, b];
-                },
-                "Ks" => {
-                    let r = tokens.next().unwrap().parse().unwrap();
-                    let g = tokens.next().unwrap().parse().unwrap();
-                    let b = tokens.next().unwrap().parse().unwrap();
-
-                    current_mat.as_mut().unwrap().specular_color = [r, g, b];
-                },
-                "Ke" => {
-                    let r = tokens.next().unwrap().parse().unwrap();
-                    let g = tokens.next().unwrap().parse().unwrap();
-                    let b = tokens.next().unwrap().parse().unwrap();
-
-                    // I've seen this in a file and it does weird things
-                    if r > 1.0 || g > 1.0 || b > 1.0 {
-                        println!("WARNING: Emission color values greater than 1");
-                        continue 'lines_loop;
-                    }
+    /// Everything added after this goes in a new group
+    fn start_group(&mut self, name: Option<String>, material: Option<String>) {
+        let group = self.group();
+        if group.is_empty() {
+            group.name = name;
+            group.material = material;
+        } else {
+            self.groups.push(ElementGroup { name, material, ..Default::default() });
+        }
+    }
 
-                    current_mat.as_mut().unwrap().emission_color = [r, g, b];
-                }
-                "Ns" => {
-                    let exp = tokens.next().unwrap().parse().unwrap();
+    fn add_vertex(&mut self, key: VertexKey, data: &ObjData) -> u32 {
+        // If we already have this vertex, just reuse it
+        let (idx, is_new) = self.welder.weld(key);
+        if !is_new {
+            return idx;
+        }
 
-                    current_mat.as_mut().unwrap().specular_exp = exp;
-                },
-                "d" => {
-                    let transparency = tokens.next().unwrap().parse().unwrap();
+        self.keys.push(key);
+        self.positions.push(data.positions[key.position]);
+        self.colors.push(data.colors[key.position]);
+        self.normals.push(key.normal.map(|n| data.normals[n]).unwrap_or([0.0; 3]));
+        self.tex_coords.push(key.tex_coord.map(|t| data.tex_coords[t]).unwrap_or([0.0; 2]));
+        idx
+    }
 
-                    current_mat.as_mut().unwrap().transparency = transparency;
+    /// Vertices that weren't given a normal get the average of the normals of the faces around them
+    fn generate_missing_normals(&mut self) {
+        for tri in self.groups.iter().flat_map(|group| group.triangles.chunks_exact(3)) {
+            let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            let (p0, p1, p2) = (self.positions[a], self.positions[b], self.positions[c]);
+            let e1 = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+            let e2 = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+            // Not normalized, so bigger faces count for more
+            let n = [e1[1] * e2[2] - e1[2] * e2[1],
+                     e1[2] * e2[0] - e1[0] * e2[2],
+                     e1[0] * e2[1] - e1[1] * e2[0]];
+
+            for v in [a, b, c] {
+                if self.keys[v].normal.is_none() {
+                    for (sum, x) in self.normals[v].iter_mut().zip(n) {
+                        *sum += x;
+                    }
                 }
-                "Tr" => {
-                    let tr = 1.0 - tokens.next().unwrap().parse::<f32>().unwrap();
+            }
+        }
 
-                    current_mat.as_mut().unwrap().transparency = tr;
-                }
-                "Tf" => {
-                    // TODO: Parse CIEXYZ format
-                    // This code will crash if given CIEXYZ
-
-                    let r = tokens.next().unwrap().parse().unwrap();
-                    let g = tokens.next().unwrap().parse().unwrap();
-                    let b = tokens.next().unwrap().parse().unwrap();
-
-                    current_mat.as_mut().unwrap().transmission_filter_color = Some([r, g, b]);
-                },
-                "Ni" => {
-                    let density = tokens.next().unwrap().parse().unwrap();
-
-                    current_mat.as_mut().unwrap().optical_density = Some(density);
-                },
-                "illum" => {
-                    current_mat.as_mut().unwrap().illum_model = match tokens.next().unwrap().parse().unwrap() {
-                        0 => {Some(IlluminationModel::COnAmOff)},
-                        1 => {Some(IlluminationModel::COnAmOn)},
-                        2 => {Some(IlluminationModel::HighOn)},
-                        3 => {Some(IlluminationModel::ReOnRTOn)},
-                        4 => {Some(IlluminationModel::TransGlassOnReRTOn)},
-                        5 => {Some(IlluminationModel::ReFresOnRTOn)},
-                        6 => {Some(IlluminationModel::TransRefracOnReFresOffRtOn)},
-                        7 => {Some(IlluminationModel::TransRefracOnReFresOnRtOn)},
-                        8 => {Some(IlluminationModel::ReOnRTOff)},
-                        9 => {Some(IlluminationModel::TransGlassOnReRTOff)},
-                        10 => {Some(IlluminationModel::InvisShadow)}, 
-                        _ => None,
-                    };
+        for (key, normal) in self.keys.iter().zip(self.normals.iter_mut()) {
+            let len = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+            if key.normal.is_none() && len > 0.0 {
+                *normal = [normal[0] / len, normal[1] / len, normal[2] / len];
+            }
+        }
+    }
+
+    /// Lays the groups out one after another in a single index buffer
+    /// Also gives back the material each of the mesh's ranges uses
+    fn build(mut self, has_colors: bool) -> (Mesh, Vec<Option<String>>) {
+        self.generate_missing_normals();
+        let has_tex_coords = self.keys.iter().any(|k| k.tex_coord.is_some());
+
+        let mut indices = Vec::new();
+        let mut ranges = Vec::new();
+        let mut range_materials = Vec::new();
+        for group in self.groups {
+            for (primitive, elements) in [(Primitive::Triangles, group.triangles), (Primitive::Lines, group.lines), (Primitive::Points, group.points)] {
+                if elements.is_empty() {
+                    continue;
                 }
+                ranges.push(DrawRange { primitive, start: indices.len(), count: elements.len(), group: group.name.clone() });
+                range_materials.push(group.material.clone());
+                indices.extend(elements);
+            }
+        }
 
-                "" => {},
+        let mesh = Mesh {
+            positions: self.positions,
+            normals: self.normals,
+            colors: if has_colors { Some(self.colors) } else { None },
+            tex_coords: if has_tex_coords { Some(self.tex_coords) } else { None },
+            tangents: None,
+            indices,
+            ranges,
+        };
+        (mesh, range_materials)
+    }
+}
 
-                _ => { println!("Found line {line}, skipping") },
-            }
+/// What's in an OBJ: all of its geometry in one mesh, and the materials its ranges use
+struct ObjContents {
+    mesh: Mesh,
+    /// In the order the file first uses them, there's always at least one
+    materials: Vec<Material>,
+    /// Which of `materials` each of the mesh's ranges uses
+    range_materials: Vec<usize>,
+}
+
+impl ObjContents {
+    /// One mesh for each material, so every range gets drawn with the material the file gave it
+    fn split(self) -> Vec<(Mesh, Material)> {
+        if self.materials.len() == 1 {
+            return vec![(self.mesh, self.materials.into_iter().next().unwrap())];
+        }
+        self.materials.into_iter().enumerate()
+            .map(|(i, material)| (self.mesh.select_ranges(|range| self.range_materials[range] == i), material))
+            .collect()
+    }
+}
+
+impl Shape {
+    pub fn from_obj(
+        path: &str,
+        shader_type: shaders::ShaderType,
+        // FIXME: We shouldn't have to specify a shader type and the program itself!
+        transform: Option<Transform>, 
+        animation: Option<Box<dyn Animation>>, 
+        options: &ImportOptions,) -> Result<Shape, ImportError> 
+    {
+        let path = Path::new(path);
+        if path.extension() != Some("obj".as_ref()) { return Err(ImportError::IncorrectExtension); }
+        let file = fs::File::open(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+        // Anything the file refers to is looked up next to it
+        let resolver = FileResolver::new(path.parent().unwrap_or(Path::new("")));
+        Shape::from_obj_reader(BufReader::new(file), &path.to_string_lossy(), &resolver, 
+            shader_type, transform, animation, options)
+    }
+
+    /// Like `from_obj`, but reads the OBJ from any reader
+    /// `name` is only used in error messages, and `resolver` finds the material libraries it lists
+    pub fn from_obj_reader<R: BufRead>(
+        reader: R,
+        name: &str,
+        resolver: &dyn Resolver,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>, 
+        animation: Option<Box<dyn Animation>>, 
+        options: &ImportOptions,) -> Result<Shape, ImportError> 
+    {
+        let f = read_text(reader)?;
+        Shape::from_obj_str(&f, name, resolver, shader_type, transform, animation, options)
+    }
+
+    /// Like `from_obj`, but the OBJ is already in memory
+    /// `name` is only used in error messages, and `resolver` finds the material libraries it lists
+    pub fn from_obj_str(
+        f: &str,
+        name: &str,
+        resolver: &dyn Resolver,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>, 
+        animation: Option<Box<dyn Animation>>, 
+        options: &ImportOptions,) -> Result<Shape, ImportError> 
+    {
+        let mut report = ImportReport::default();
+        let contents = parse_obj(f, name, resolver, options, None, &mut report)?;
+        if contents.materials.len() > 1 {
+            report.warn(name, None, format!("a shape has one material, so only the first of the {} this file uses is kept, load_file gives a shape for each",
+                contents.materials.len()));
         }
+        let (mut mesh, material) = (contents.mesh, contents.materials.into_iter().next().unwrap());
+        check_meshes([&mut mesh], name, options, &mut report);
+        report.log();
+        let lods = process_meshes([&mut mesh], options).remove(0);
+        let mut shape = Shape::from_mesh(mesh, shader_type, transform, animation, material);
+        shape.set_lods(lods);
+        Ok(shape)
+    }
+}
+
+/// Reads an OBJ into a mesh for each material it uses, without touching the GPU
+/// The meshes are in the order the file first uses their materials, and there's always at least one
+/// `resolver` finds the material libraries it lists, and anything skipped goes in `report`
+/// The import options that change the geometry aren't applied, pass the meshes to `check_meshes` and `process_meshes` for those
+pub fn read_obj<R: BufRead>(reader: R, name: &str, resolver: &dyn Resolver, options: &ImportOptions, report: &mut ImportReport) 
+    -> Result<Vec<(Mesh, Material)>, ImportError> 
+{
+    Ok(parse_obj(&read_text(reader)?, name, resolver, options, None, report)?.split())
+}
 
-        // Insert old material
-        if let Some(mat) = current_mat {
-            materials.insert(current_mat_name.unwrap().clone(), mat.clone());
+/// Like `read_obj` for an OBJ that's already in memory
+/// The lines are parsed in parallel, then put together in order
+fn parse_obj(f: &str, name: &str, resolver: &dyn Resolver, options: &ImportOptions, progress: Option<&Progress>, report: &mut ImportReport) 
+    -> Result<ObjContents, ImportError> 
+{
+    let lines = logical_lines(f);
+    if let Some(progress) = progress {
+        // Each line is parsed and then put together
+        progress.set_total(lines.len() * 2);
+    }
+    let statements = parse_statements(&lines, name, progress);
+
+    // Filled in as mtllib statements are found
+    let mut mat_map = HashMap::new();
+
+    let mut data = ObjData::default();
+    let mut builder = MeshBuilder::default();
+
+    // Where each material is first used, for the error if the libraries don't have it
+    let mut material_lines = HashMap::new();
+    let mut seen_object = false;
+
+    for (i, ((line_no, line), statement)) in lines.iter().zip(statements).enumerate() {
+        let line_no = *line_no;
+        match statement? {
+            Statement::Position(position, color) => {
+                data.positions.push(position);
+                data.has_colors |= color.is_some();
+                data.colors.push(color.unwrap_or([1.0; 3]));
+            },
+            Statement::TexCoord(tex_coord) => data.tex_coords.push(tex_coord),
+            Statement::Normal(normal) => data.normals.push(normal),
+            Statement::ParamVertex(param) => data.param_vertices.push(param),
+            Statement::Face(corners) => {
+                let corners = add_corners(&corners, name, line_no, &data, &mut builder)?;
+                // Polygons are split into a fan of triangles, which assumes they're convex
+                for i in 2..corners.len() {
+                    builder.group().triangles.extend_from_slice(&[corners[0], corners[i - 1], corners[i]]);
+                }
+            },
+            Statement::Line(corners) => {
+                let corners = add_corners(&corners, name, line_no, &data, &mut builder)?;
+                // A polyline becomes a segment between each pair of neighbors
+                for pair in corners.windows(2) {
+                    builder.group().lines.extend_from_slice(pair);
+                }
+            },
+            Statement::Points(corners) => {
+                let corners = add_corners(&corners, name, line_no, &data, &mut builder)?;
+                builder.group().points.extend_from_slice(&corners);
+            },
+            Statement::Object(object) => {
+                if seen_object {
+                    report.warn(name, Some(line_no), "loading several objects from one file puts them all in the same shape".to_string());
+                }
+                seen_object = true;
+                builder.start_group(object, None);
+            },
+            Statement::Group(group) => {
+                let material = builder.group().material.clone();
+                builder.start_group(group, material);
+            },
+            Statement::MaterialLibs(libs) => {
+                for lib in libs {
+                    match resolver.open(&lib) {
+                        Ok(reader) => mat_map.extend(Material::read_mtl(reader, &lib, options, report)?),
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => 
+                            report.warn(name, Some(line_no), format!("couldn't find material library {lib}")),
+                        Err(err) => return Err(ImportError::FileError(Box::new(err))),
+                    }
+                }
+            },
+            Statement::UseMaterial(mat_name) => {
+                material_lines.entry(mat_name.clone()).or_insert(line_no);
+                let group = builder.group().name.clone();
+                builder.start_group(group, Some(mat_name));
+            },
+            Statement::Empty => {},
+            Statement::Unknown => report.warn(name, Some(line_no), format!("skipping unknown statement '{}'", line.trim())),
         }
 
-        Ok(materials)
+        if let Some(progress) = progress.filter(|_| (i + 1) % PROGRESS_STEP == 0) {
+            progress.advance(PROGRESS_STEP);
+        }
+    }
+    if let Some(progress) = progress {
+        progress.finish();
     }
-}
\ No newline at end of file
+
+    if !data.param_vertices.is_empty() {
+        report.warn(name, None, format!("skipping {} parameter space vertices, free-form geometry isn't supported", data.param_vertices.len()));
+    }
+
+    let (mesh, range_names) = builder.build(data.has_colors);
+
+    // Ranges with the same material share it, in the order the file first uses them
+    let mut names: Vec<Option<String>> = Vec::new();
+    let mut range_materials = Vec::with_capacity(range_names.len());
+    for range_name in range_names {
+        let i = names.iter().position(|n| *n == range_name).unwrap_or_else(|| {
+            names.push(range_name);
+            names.len() - 1
+        });
+        range_materials.push(i);
+    }
+    if names.is_empty() {
+        names.push(None);
+    }
+
+    // Is there a material for each of them?
+    let materials = names.into_iter().map(|mat_name| match mat_name {
+        Some(mat_name) if !mat_map.is_empty() => mat_map.get(&mat_name).cloned().ok_or_else(|| 
+            ImportError::MissingMaterial { file: name.to_string(), line: material_lines[&mat_name], name: mat_name }),
+        _ => Ok(Material::default()),
+    }).collect::<Result<_, _>>()?;
+
+    Ok(ObjContents { mesh, materials, range_materials })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obj_warnings(obj: &str) -> Vec<ImportWarning> {
+        let mut report = ImportReport::default();
+        read_obj(obj.as_bytes(), "test.obj", &NoResolver, &ImportOptions::default(), &mut report).unwrap();
+        report.warnings
+    }
+
+    #[test]
+    fn material_names_can_have_spaces() {
+        let resolver = |name: &str| match name {
+            "lib.mtl" => Ok(&b"newmtl My Mat\nKd 1 0 0\n"[..]),
+            _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+        };
+        let obj = "mtllib lib.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl My Mat\nf 1 2 3\n";
+        let shapes = read_obj(obj.as_bytes(), "test.obj", &resolver, &ImportOptions::default(), &mut ImportReport::default()).unwrap();
+        assert_eq!(shapes[0].1.name.as_deref(), Some("My Mat"));
+    }
+
+    #[test]
+    fn one_object_loads_quietly() {
+        let triangle = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        assert!(obj_warnings(&format!("o Triangle\n{triangle}")).is_empty());
+        let warnings = obj_warnings(&format!("o First\n{triangle}o Second\nf 1 3 2\n"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, Some(6));
+    }
+}
@@ -0,0 +1,519 @@
+use std::{cell::RefCell, fs, io::{self, BufRead}, path::{Path, PathBuf}, time::UNIX_EPOCH};
+
+use memmap2::Mmap;
+
+use crate::three_d::shape::exporting::write_material;
+use super::*;
+
+// A cache file is laid out as:
+//   header: magic, version, options fingerprint
+//   stamps: the size and modification time of the source and every file it pulled in
+//   warnings: everything the import reported, so loading from the cache reports it too
+//   materials: one MTL, with the real names kept separately since materials don't have to have one
+//   shapes: counts, bounds, transform and ranges, then the vertex and index data, then the same for each level of detail
+// Everything is little endian, and the vertex data is 4 byte aligned
+
+const MAGIC: &[u8; 8] = b"GLMCACHE";
+/// Bump this whenever the layout changes, so old caches get rebuilt instead of misread
+const VERSION: u32 = 4;
+
+const HAS_COLORS: u32 = 1;
+const HAS_TEX_COORDS: u32 = 1 << 1;
+const HAS_TRANSFORM: u32 = 1 << 2;
+const HAS_TANGENTS: u32 = 1 << 3;
+/// Stands in for a missing string or line number
+const NONE: u32 = u32::MAX;
+
+/// Where the cache for `path` goes: next to it, with `.meshcache` added on
+pub fn cache_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut name = path.as_ref().as_os_str().to_owned();
+    name.push(".meshcache");
+    PathBuf::from(name)
+}
+
+/// Sums up the options so that changing them invalidates the cache, using FNV-1a since it's stable between builds
+fn fingerprint(options: &ImportOptions) -> u64 {
+    format!("{options:?}").bytes().fold(0xcbf29ce484222325, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// A file's size and modification time, which is what we check to see if it's changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Stamp {
+    path: String,
+    len: u64,
+    secs: u64,
+    nanos: u32,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> Option<Stamp> {
+        let meta = fs::metadata(path).ok()?;
+        let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Stamp { path: path.to_string_lossy().into_owned(), len: meta.len(), secs: modified.as_secs(), nanos: modified.subsec_nanos() })
+    }
+
+    fn is_current(&self) -> bool {
+        Stamp::of(Path::new(&self.path)).as_ref() == Some(self)
+    }
+}
+
+/// Passes through to a `FileResolver`, remembering every file it opens so the cache can check them too
+struct RecordingResolver {
+    inner: FileResolver,
+    opened: RefCell<Vec<PathBuf>>,
+}
+
+impl Resolver for RecordingResolver {
+    fn open(&self, name: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        let reader = self.inner.open(name)?;
+        self.opened.borrow_mut().push(self.inner.path(name));
+        Ok(reader)
+    }
+}
+
+/// Loads `path` from its cache if the cache is up to date, otherwise imports it and writes a new cache
+/// A cache that can't be read or written is only logged, since the file itself is still there to load
+pub fn load_cached(path: &str, options: &ImportOptions, progress: Option<&Progress>) -> Result<LoadedFile, ImportError> {
+    let source = Path::new(path);
+    let cache = cache_path(source);
+    match read_cache(&cache, options) {
+        Ok(Some(loaded)) => {
+            if let Some(progress) = progress {
+                progress.finish();
+            }
+            return Ok(loaded);
+        },
+        Ok(None) => {},
+        // A broken cache is no reason to fail the load, it just gets rebuilt
+        Err(err) => log::warn!("Ignoring mesh cache {}: {err}", cache.display()),
+    }
+
+    let resolver = RecordingResolver { inner: FileResolver::new(source.parent().unwrap_or(Path::new(""))), opened: RefCell::default() };
+    let loaded = load_with(source, &resolver, options, progress)?;
+
+    let mut files = vec![source.to_path_buf()];
+    files.extend(resolver.opened.into_inner());
+    if let Err(err) = write_cache(&cache, &loaded, &files, options) {
+        log::warn!("Couldn't write mesh cache {}: {err}", cache.display());
+    }
+    Ok(loaded)
+}
+
+/// Writes `loaded` to a cache at `path`, which stays valid while all of `files` are unchanged
+/// The cache is written next to its final name and moved into place, so a crash can't leave half of one behind
+pub fn write_cache(path: &Path, loaded: &LoadedFile, files: &[PathBuf], options: &ImportOptions) -> io::Result<()> {
+    let shapes = &loaded.shapes;
+    let mut out = CacheWriter::default();
+    out.bytes.extend_from_slice(MAGIC);
+    out.u32(VERSION);
+    out.u64(fingerprint(options));
+
+    let stamps = files.iter()
+        .map(|file| Stamp::of(file).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("can't stat {}", file.display()))))
+        .collect::<io::Result<Vec<_>>>()?;
+    out.u32(stamps.len() as u32);
+    for stamp in &stamps {
+        out.str(&stamp.path);
+        out.u64(stamp.len);
+        out.u64(stamp.secs);
+        out.u32(stamp.nanos);
+    }
+
+    out.u32(loaded.report.warnings.len() as u32);
+    for warning in &loaded.report.warnings {
+        out.str(&warning.file);
+        out.u32(warning.line.map_or(NONE, |line| line as u32));
+        out.str(&warning.message);
+    }
+
+    // Shapes that share a material share an entry in the table
+    let mut materials: Vec<&Material> = Vec::new();
+    let material_indices: Vec<u32> = shapes.iter().map(|shape| {
+        let idx = materials.iter().position(|m| **m == shape.material).unwrap_or_else(|| {
+            materials.push(&shape.material);
+            materials.len() - 1
+        });
+        idx as u32
+    }).collect();
+
+    let mut mtl = Vec::new();
+    for (i, material) in materials.iter().enumerate() {
+        write_material(&mut mtl, &format!("material{i}"), material)?;
+    }
+    out.u32(materials.len() as u32);
+    for material in &materials {
+        out.opt_str(material.name.as_deref());
+    }
+    out.u32(mtl.len() as u32);
+    out.bytes.extend_from_slice(&mtl);
+    out.pad();
+
+    out.u32(shapes.len() as u32);
+    for (shape, material) in shapes.iter().zip(material_indices) {
+        let mesh = &shape.mesh;
+        let mut flags = 0;
+        if mesh.colors.is_some() { flags |= HAS_COLORS; }
+        if mesh.tex_coords.is_some() { flags |= HAS_TEX_COORDS; }
+        if shape.transform.is_some() { flags |= HAS_TRANSFORM; }
+        if mesh.tangents.is_some() { flags |= HAS_TANGENTS; }
+        out.u32(flags);
+        out.u32(material);
+        out.u32(mesh.positions.len() as u32);
+        out.u32(mesh.indices.len() as u32);
+
+        let bounds = mesh.bounds().unwrap_or(Bounds { min: [0.0; 3], max: [0.0; 3] });
+        out.floats(&[bounds.min, bounds.max]);
+        if let Some(transform) = &shape.transform {
+            out.floats(&transform.transform_matrix.inner);
+        }
+
+        write_mesh(&mut out, mesh);
+
+        out.u32(shape.lods.len() as u32);
+        for lod in &shape.lods {
+            out.u32(lod.positions.len() as u32);
+            out.u32(lod.indices.len() as u32);
+            write_mesh(&mut out, lod);
+        }
+    }
+
+    let temp = path.with_extension("meshcache-tmp");
+    fs::write(&temp, &out.bytes)?;
+    fs::rename(&temp, path)
+}
+
+/// Writes a mesh's ranges and data, its flags and counts go before this
+fn write_mesh(out: &mut CacheWriter, mesh: &Mesh) {
+    out.u32(mesh.ranges.len() as u32);
+    for range in &mesh.ranges {
+        out.u32(match range.primitive {
+            Primitive::Triangles => 0,
+            Primitive::Lines => 1,
+            Primitive::Points => 2,
+        });
+        out.u32(range.start as u32);
+        out.u32(range.count as u32);
+        match &range.group {
+            Some(group) => out.str(group),
+            None => out.u32(NONE),
+        }
+    }
+    out.pad();
+
+    out.floats(&mesh.positions);
+    out.floats(&mesh.normals);
+    if let Some(colors) = &mesh.colors {
+        out.floats(colors);
+    }
+    if let Some(tex_coords) = &mesh.tex_coords {
+        out.floats(tex_coords);
+    }
+    if let Some(tangents) = &mesh.tangents {
+        out.floats(tangents);
+    }
+    for idx in &mesh.indices {
+        out.u32(*idx);
+    }
+}
+
+/// Reads the shapes back out of a cache, memory mapping it so it isn't read into a buffer first
+/// The meshes own their data, so every array is still copied out of the map into them
+/// Gives back `None` if there's no cache, or it's out of date, from another version, or made with other options
+pub fn read_cache(path: &Path, options: &ImportOptions) -> Result<Option<LoadedFile>, ImportError> {
+    let Ok(file) = fs::File::open(path) else { return Ok(None) };
+    // Safety: the map is only read while it's alive, and another process truncating the cache under us is
+    // the same risk as any memory mapped file. The cache is only ever replaced by renaming a new one over it
+    let map = unsafe { Mmap::map(&file) }.map_err(|err| ImportError::FileError(Box::new(err)))?;
+    let name = path.to_string_lossy();
+    let mut reader = CacheReader { bytes: &map, pos: 0, file: &name };
+
+    if !read_header(&mut reader, options)? {
+        return Ok(None);
+    }
+
+    let mut report = ImportReport::default();
+    for _ in 0..reader.u32()? {
+        let file = reader.str()?;
+        let line = reader.u32()?;
+        let message = reader.str()?;
+        report.warnings.push(ImportWarning { file, line: (line != NONE).then_some(line as usize), message });
+    }
+
+    let material_count = reader.u32()? as usize;
+    let names = (0..material_count).map(|_| reader.opt_str()).collect::<Result<Vec<_>, _>>()?;
+    let mtl_len = reader.u32()? as usize;
+    let mut mtl = Material::from_mtl_reader(reader.take(mtl_len)?, &name, &ImportOptions::default())?;
+    reader.pad();
+    let materials = names.into_iter().enumerate().map(|(i, material_name)| {
+        let mut material = mtl.remove(&format!("material{i}")).ok_or_else(|| reader.invalid("a material is missing"))?;
+        material.name = material_name;
+        Ok(material)
+    }).collect::<Result<Vec<_>, ImportError>>()?;
+
+    // Counts come from the file, so nothing is allocated up front from them in case it's corrupt
+    let mut shapes = Vec::new();
+    for _ in 0..reader.u32()? {
+        let flags = reader.u32()?;
+        let material = materials.get(reader.u32()? as usize).cloned().ok_or_else(|| reader.invalid("a material index is out of range"))?;
+        let vertices = reader.u32()? as usize;
+        let indices = reader.u32()? as usize;
+        reader.floats::<3>(2)?;
+        let transform = if flags & HAS_TRANSFORM != 0 {
+            let rows = reader.floats::<4>(4)?;
+            Some(Transform::from_matrix(Mat4::new([rows[0], rows[1], rows[2], rows[3]])))
+        } else {
+            None
+        };
+
+        let mesh = read_mesh(&mut reader, flags, vertices, indices)?;
+        let lods = (0..reader.u32()?).map(|_| {
+            let vertices = reader.u32()? as usize;
+            let indices = reader.u32()? as usize;
+            read_mesh(&mut reader, flags, vertices, indices)
+        }).collect::<Result<Vec<_>, _>>()?;
+        shapes.push(LoadedShape { mesh, lods, material, transform, animation: None });
+    }
+    Ok(Some(LoadedFile { shapes, report }))
+}
+
+/// Reads back what `write_mesh` wrote, checking the ranges and indices stay inside the mesh
+fn read_mesh(reader: &mut CacheReader, flags: u32, vertices: usize, indices: usize) -> Result<Mesh, ImportError> {
+    let mut ranges = Vec::new();
+    for _ in 0..reader.u32()? {
+        let primitive = match reader.u32()? {
+            0 => Primitive::Triangles,
+            1 => Primitive::Lines,
+            2 => Primitive::Points,
+            _ => return Err(reader.invalid("a range has an unknown primitive")),
+        };
+        let start = reader.u32()? as usize;
+        let count = reader.u32()? as usize;
+        let group = reader.opt_str()?;
+        if start + count > indices {
+            return Err(reader.invalid("a range goes past the end of the indices"));
+        }
+        ranges.push(DrawRange { primitive, start, count, group });
+    }
+    reader.pad();
+
+    let mesh = Mesh {
+        positions: reader.floats(vertices)?,
+        normals: reader.floats(vertices)?,
+        colors: if flags & HAS_COLORS != 0 { Some(reader.floats(vertices)?) } else { None },
+        tex_coords: if flags & HAS_TEX_COORDS != 0 { Some(reader.floats(vertices)?) } else { None },
+        tangents: if flags & HAS_TANGENTS != 0 { Some(reader.floats(vertices)?) } else { None },
+        indices: reader.u32s(indices)?,
+        ranges,
+    };
+    if mesh.indices.iter().any(|idx| *idx as usize >= vertices) {
+        return Err(reader.invalid("an index is out of range"));
+    }
+    Ok(mesh)
+}
+
+/// Skips over what `write_mesh` wrote
+fn skip_mesh(reader: &mut CacheReader, flags: u32, vertices: usize, indices: usize) -> Result<(), ImportError> {
+    for _ in 0..reader.u32()? {
+        reader.take(12)?;
+        reader.opt_str()?;
+    }
+    reader.pad();
+
+    let per_vertex = 6 
+        + if flags & HAS_COLORS != 0 { 3 } else { 0 } 
+        + if flags & HAS_TEX_COORDS != 0 { 2 } else { 0 } 
+        + if flags & HAS_TANGENTS != 0 { 4 } else { 0 };
+    reader.take((vertices * per_vertex + indices) * 4)?;
+    Ok(())
+}
+
+/// The bounds of every shape in a cache, without reading any of their vertices
+pub fn read_cache_bounds(path: &Path) -> Result<Vec<Bounds>, ImportError> {
+    let file = fs::File::open(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+    // Safety: see `read_cache`
+    let map = unsafe { Mmap::map(&file) }.map_err(|err| ImportError::FileError(Box::new(err)))?;
+    let name = path.to_string_lossy();
+    let mut reader = CacheReader { bytes: &map, pos: 0, file: &name };
+
+    // Bounds are fine to read from an outdated cache, as long as it's our version
+    if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != VERSION {
+        return Err(reader.invalid("this isn't a mesh cache from this version"));
+    }
+    reader.u64()?;
+    for _ in 0..reader.u32()? {
+        reader.str()?;
+        reader.take(20)?;
+    }
+    for _ in 0..reader.u32()? {
+        reader.str()?;
+        reader.u32()?;
+        reader.str()?;
+    }
+    for _ in 0..reader.u32()? {
+        reader.opt_str()?;
+    }
+    let mtl_len = reader.u32()? as usize;
+    reader.take(mtl_len)?;
+    reader.pad();
+
+    let mut bounds = Vec::new();
+    for _ in 0..reader.u32()? {
+        let flags = reader.u32()?;
+        reader.u32()?;
+        let vertices = reader.u32()? as usize;
+        let indices = reader.u32()? as usize;
+        let b = reader.floats::<3>(2)?;
+        bounds.push(Bounds { min: b[0], max: b[1] });
+        if flags & HAS_TRANSFORM != 0 {
+            reader.floats::<4>(4)?;
+        }
+        // Skip straight over the vertex data, and any levels of detail
+        skip_mesh(&mut reader, flags, vertices, indices)?;
+        for _ in 0..reader.u32()? {
+            let vertices = reader.u32()? as usize;
+            let indices = reader.u32()? as usize;
+            skip_mesh(&mut reader, flags, vertices, indices)?;
+        }
+    }
+    Ok(bounds)
+}
+
+/// Checks the cache is ours, made with the same options, and that nothing it was made from has changed
+fn read_header(reader: &mut CacheReader, options: &ImportOptions) -> Result<bool, ImportError> {
+    if reader.bytes.len() < MAGIC.len() + 4 || reader.take(MAGIC.len())? != MAGIC || reader.u32()? != VERSION {
+        return Ok(false);
+    }
+    if reader.u64()? != fingerprint(options) {
+        return Ok(false);
+    }
+    let stamp_count = reader.u32()?;
+    let mut current = true;
+    for _ in 0..stamp_count {
+        let stamp = Stamp { path: reader.str()?, len: reader.u64()?, secs: reader.u64()?, nanos: reader.u32()? };
+        current &= stamp.is_current();
+    }
+    Ok(current)
+}
+
+#[derive(Default)]
+struct CacheWriter {
+    bytes: Vec<u8>,
+}
+
+impl CacheWriter {
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn floats<const N: usize>(&mut self, values: &[[f32; N]]) {
+        for x in values.iter().flatten() {
+            self.bytes.extend_from_slice(&x.to_le_bytes());
+        }
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => self.str(value),
+            None => self.u32(NONE),
+        }
+    }
+
+    /// Lines the next thing written up to 4 bytes
+    fn pad(&mut self) {
+        while !self.bytes.len().is_multiple_of(4) {
+            self.bytes.push(0);
+        }
+    }
+}
+
+struct CacheReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    file: &'a str,
+}
+
+impl<'a> CacheReader<'a> {
+    fn invalid(&self, message: &str) -> ImportError {
+        invalid(self.file, format!("corrupt mesh cache, {message}"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ImportError> {
+        let bytes = self.bytes.get(self.pos..self.pos + len).ok_or_else(|| self.invalid("it ends too early"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ImportError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, ImportError> {
+        let (low, high) = (self.u32()? as u64, self.u32()? as u64);
+        Ok(low | high << 32)
+    }
+
+    fn u32s(&mut self, count: usize) -> Result<Vec<u32>, ImportError> {
+        let bytes = self.take(count * 4)?;
+        Ok(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+    }
+
+    fn floats<const N: usize>(&mut self, count: usize) -> Result<Vec<[f32; N]>, ImportError> {
+        let bytes = self.take(count * N * 4)?;
+        Ok(bytes.chunks_exact(N * 4).map(|item| {
+            std::array::from_fn(|i| f32::from_le_bytes([item[i * 4], item[i * 4 + 1], item[i * 4 + 2], item[i * 4 + 3]]))
+        }).collect())
+    }
+
+    fn str(&mut self) -> Result<String, ImportError> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn opt_str(&mut self) -> Result<Option<String>, ImportError> {
+        let len = self.u32()?;
+        if len == NONE {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(self.take(len as usize)?).into_owned()))
+    }
+
+    fn pad(&mut self) {
+        self.pos = self.pos.next_multiple_of(4);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_corrupt_count_is_an_error() {
+        // Four billion ranges claimed, and nothing after
+        let bytes = u32::MAX.to_le_bytes();
+        let mut reader = CacheReader { bytes: &bytes, pos: 0, file: "test.meshcache" };
+        assert!(read_mesh(&mut reader, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn meshes_survive_the_cache() {
+        let mesh = Mesh::cube(1.0, 2);
+        let mut out = CacheWriter::default();
+        write_mesh(&mut out, &mesh);
+        let mut reader = CacheReader { bytes: &out.bytes, pos: 0, file: "test.meshcache" };
+        let read = read_mesh(&mut reader, HAS_TEX_COORDS, mesh.positions.len(), mesh.indices.len()).unwrap();
+        assert_eq!(read.positions, mesh.positions);
+        assert_eq!(read.tex_coords, mesh.tex_coords);
+        assert_eq!(read.indices, mesh.indices);
+        assert_eq!(read.ranges.len(), mesh.ranges.len());
+    }
+}
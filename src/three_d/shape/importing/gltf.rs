@@ -0,0 +1,434 @@
+use std::{fs, borrow::Cow, collections::HashMap, io::Read, path::Path};
+
+use ::gltf::{Gltf, Node, buffer, image, mesh::Mode, animation::util::ReadOutputs, texture::WrappingMode};
+
+use crate::three_d::animation::{Interpolation, Keyframes, KeyframedNode, Track};
+use super::*;
+
+/// Reads a URI from the file, either data embedded in it or the name of another file
+fn read_uri(uri: &str, file: &str, resolver: &dyn Resolver) -> Result<Vec<u8>, ImportError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, data) = data.split_once(";base64,")
+            .ok_or_else(|| invalid(file, "only base64 data URIs are supported".to_string()))?;
+        return base64::decode(data).map_err(|err| ImportError::FileError(Box::new(err)));
+    }
+
+    let name = urlencoding::decode(uri).unwrap_or(Cow::Borrowed(uri));
+    let mut bytes = Vec::new();
+    resolver.open(&name)
+        .and_then(|mut reader| reader.read_to_end(&mut bytes))
+        .map_err(|err| ImportError::FileError(Box::new(err)))?;
+    Ok(bytes)
+}
+
+fn load_buffers(gltf: &Gltf, file: &str, resolver: &dyn Resolver) -> Result<Vec<Vec<u8>>, ImportError> {
+    let mut buffers = Vec::new();
+    for buffer in gltf.buffers() {
+        let data = match buffer.source() {
+            buffer::Source::Bin => gltf.blob.clone()
+                .ok_or_else(|| invalid(file, "a buffer uses the binary chunk, but there isn't one".to_string()))?,
+            buffer::Source::Uri(uri) => read_uri(uri, file, resolver)?,
+        };
+        if data.len() < buffer.length() {
+            return Err(invalid(file, format!("buffer {} should be {} bytes, but only {} were found", buffer.index(), buffer.length(), data.len())));
+        }
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+/// Turns a texture into a map that points at its file
+/// Images stored inside the glTF have no file to point at, so those get skipped
+fn texture_map(texture: ::gltf::Texture, file: &str, report: &mut ImportReport) -> Option<TextureMap> {
+    match texture.source().source() {
+        image::Source::Uri { uri, .. } if !uri.starts_with("data:") => {
+            let mut options = TextureOptions::default();
+            let sampler = texture.sampler();
+            options.clamp = sampler.wrap_s() == WrappingMode::ClampToEdge && sampler.wrap_t() == WrappingMode::ClampToEdge;
+            let file = urlencoding::decode(uri).map(Cow::into_owned).unwrap_or_else(|_| uri.to_string());
+            Some(TextureMap { file, options })
+        },
+        _ => {
+            report.warn(file, None, format!("texture {} is embedded, skipping it", texture.index()));
+            None
+        },
+    }
+}
+
+fn with_channel(mut map: TextureMap, channel: TextureChannel) -> TextureMap {
+    map.options.channel = Some(channel);
+    map
+}
+
+fn convert_material(material: ::gltf::Material, file: &str, report: &mut ImportReport) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let (metallic, roughness) = (pbr.metallic_factor(), pbr.roughness_factor());
+
+    // Fill in the Phong colors too, so that shaders without PBR get something close
+    // Metals have no diffuse and tint their reflections, everything else reflects about 4% untinted
+    let mix = |c: f32| 0.04 + (c - 0.04) * metallic;
+    let alpha = roughness * roughness;
+    let specular_exp = if alpha > 0.0 { (2.0 / (alpha * alpha) - 2.0).clamp(1.0, 1000.0) } else { 1000.0 };
+
+    let mut textures = MaterialTextures::new();
+    textures.diffuse = pbr.base_color_texture().and_then(|info| texture_map(info.texture(), file, report));
+    textures.emission = material.emissive_texture().and_then(|info| texture_map(info.texture(), file, report));
+    // Occlusion is what ambient light gets multiplied by
+    textures.ambient = material.occlusion_texture()
+        .and_then(|occlusion| texture_map(occlusion.texture(), file, report))
+        .map(|map| with_channel(map, TextureChannel::Red));
+
+    let metallic_roughness = pbr.metallic_roughness_texture().and_then(|info| texture_map(info.texture(), file, report));
+    let normal_map = material.normal_texture().and_then(|normal| {
+        let scale = normal.scale();
+        texture_map(normal.texture(), file, report).map(|mut map| {
+            map.options.bump_multiplier = scale;
+            map
+        })
+    });
+
+    Material {
+        name: material.name().map(String::from),
+        ambient_color: [0.0; 3],
+        diffuse_color: [r * (1.0 - metallic), g * (1.0 - metallic), b * (1.0 - metallic)],
+        emission_color: material.emissive_factor(),
+        specular_color: [mix(r), mix(g), mix(b)],
+        specular_exp,
+        transparency: if material.alpha_mode() == ::gltf::material::AlphaMode::Opaque { 1.0 } else { a },
+        transmission_filter_color: None,
+        optical_density: None,
+        illum_model: None,
+        textures,
+        pbr: Some(PbrMaterial {
+            roughness,
+            metallic,
+            // Both factors come from one texture, roughness in green and metallic in blue
+            roughness_map: metallic_roughness.clone().map(|map| with_channel(map, TextureChannel::Green)),
+            metallic_map: metallic_roughness.map(|map| with_channel(map, TextureChannel::Blue)),
+            normal_map,
+            ..Default::default()
+        }),
+    }
+}
+
+fn read_primitive(primitive: &::gltf::Primitive, group: Option<&str>, buffers: &[Vec<u8>], file: &str) -> Result<Mesh, ImportError> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+
+    let positions: Vec<Vertex> = reader.read_positions()
+        .ok_or_else(|| invalid(file, "a primitive has no positions".to_string()))?
+        .collect();
+    let normals: Option<Vec<Normal>> = reader.read_normals().map(|normals| normals.collect());
+    // glTF puts the origin of textures in the top left, OpenGL puts it in the bottom left
+    let tex_coords: Option<Vec<TexCoord>> = reader.read_tex_coords(0)
+        .map(|tex_coords| tex_coords.into_f32().map(|[u, v]| [u, 1.0 - v]).collect());
+    let colors: Option<Vec<Color>> = reader.read_colors(0).map(|colors| colors.into_rgb_f32().collect());
+    // Flipping V turns the bitangents around too, and tangents only mean anything alongside the file's own normals
+    let tangents: Option<Vec<Tangent>> = reader.read_tangents()
+        .filter(|_| normals.is_some())
+        .map(|tangents| tangents.map(|[x, y, z, w]| [x, y, z, -w]).collect());
+
+    let lengths = [normals.as_ref().map(Vec::len), tex_coords.as_ref().map(Vec::len), colors.as_ref().map(Vec::len), 
+        tangents.as_ref().map(Vec::len)];
+    if lengths.into_iter().flatten().any(|len| len != positions.len()) {
+        return Err(invalid(file, "a primitive's attributes don't all have the same number of vertices".to_string()));
+    }
+
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    if let Some(index) = indices.iter().find(|&&index| index as usize >= positions.len()) {
+        return Err(invalid(file, format!("index {index} is out of range, only {} vertices are defined", positions.len())));
+    }
+
+    // We only draw lists, so strips, loops and fans get unrolled
+    let n = indices.len();
+    let (kind, indices) = match primitive.mode() {
+        Mode::Points => (Primitive::Points, indices),
+        Mode::Lines => (Primitive::Lines, indices[..n - n % 2].to_vec()),
+        Mode::LineStrip => (Primitive::Lines, indices.windows(2).flatten().copied().collect()),
+        Mode::LineLoop => {
+            let mut lines: Vec<u32> = indices.windows(2).flatten().copied().collect();
+            if n > 2 {
+                lines.extend([indices[n - 1], indices[0]]);
+            }
+            (Primitive::Lines, lines)
+        },
+        Mode::Triangles => (Primitive::Triangles, indices[..n - n % 3].to_vec()),
+        Mode::TriangleStrip => {
+            // Every other triangle is listed backwards, this swaps it so they all face the same way
+            let tris = (0..n.saturating_sub(2))
+                .flat_map(|i| [indices[i], indices[i + 1 + i % 2], indices[i + 2 - i % 2]])
+                .collect();
+            (Primitive::Triangles, tris)
+        },
+        Mode::TriangleFan => {
+            let tris = (1..n.saturating_sub(1))
+                .flat_map(|i| [indices[0], indices[i], indices[i + 1]])
+                .collect();
+            (Primitive::Triangles, tris)
+        },
+    };
+
+    let has_normals = normals.is_some();
+    let mut mesh = Mesh {
+        normals: normals.unwrap_or_else(|| vec![[0.0; 3]; positions.len()]),
+        positions,
+        colors,
+        tex_coords,
+        tangents,
+        ranges: vec![DrawRange { primitive: kind, start: 0, count: indices.len(), group: group.map(String::from) }],
+        indices,
+    };
+    if !has_normals {
+        mesh.compute_normals();
+    }
+    Ok(mesh)
+}
+
+fn rest_pose(node: &Node) -> KeyframedNode {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    KeyframedNode::new(translation, rotation, scale)
+}
+
+fn make_track<const N: usize>(interpolation: Interpolation, times: &[f32], values: Vec<[f32; N]>, file: &str) -> Result<Track<N>, ImportError> {
+    let per_key = if interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+    if times.is_empty() || values.len() != times.len() * per_key {
+        return Err(invalid(file, format!("an animation channel has {} keyframes but {} values", times.len(), values.len())));
+    }
+    Ok(Track { interpolation, times: times.to_vec(), values })
+}
+
+/// Reads one of the file's animations, as the nodes it moves by index
+fn read_animation(gltf: &Gltf, index: usize, buffers: &[Vec<u8>], file: &str, report: &mut ImportReport) 
+    -> Result<HashMap<usize, KeyframedNode>, ImportError> 
+{
+    let animation = gltf.animations().nth(index)
+        .ok_or_else(|| invalid(file, format!("there's no animation {index}, the file only has {}", gltf.animations().count())))?;
+
+    let mut nodes = HashMap::new();
+    for channel in animation.channels() {
+        let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+        let times: Vec<f32> = reader.read_inputs()
+            .ok_or_else(|| invalid(file, "an animation channel has no keyframe times".to_string()))?
+            .collect();
+        let interpolation = match channel.sampler().interpolation() {
+            ::gltf::animation::Interpolation::Step => Interpolation::Step,
+            ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+            ::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+        };
+
+        let target = channel.target().node();
+        let node = nodes.entry(target.index()).or_insert_with(|| rest_pose(&target));
+        match reader.read_outputs() {
+            Some(ReadOutputs::Translations(values)) => 
+                node.translation_track = Some(make_track(interpolation, &times, values.collect(), file)?),
+            Some(ReadOutputs::Rotations(values)) => 
+                node.rotation_track = Some(make_track(interpolation, &times, values.into_f32().collect(), file)?),
+            Some(ReadOutputs::Scales(values)) => 
+                node.scale_track = Some(make_track(interpolation, &times, values.collect(), file)?),
+            Some(ReadOutputs::MorphTargetWeights(_)) =>
+                report.warn(file, None, "morph target animations aren't supported, skipping a channel".to_string()),
+            None => return Err(invalid(file, "an animation channel has no values".to_string())),
+        }
+    }
+    Ok(nodes)
+}
+
+/// What every node in a scene needs to turn its primitives into shapes
+struct SceneData<'a> {
+    file: &'a str,
+    buffers: Vec<Vec<u8>>,
+    animated: HashMap<usize, KeyframedNode>,
+    /// Converted up front, so shapes that share a material don't convert it (and warn about it) again
+    materials: Vec<Material>,
+}
+
+/// Walks down from `node`, picking up primitives and the transforms they're under
+fn visit_node<'a>(node: Node<'a>, parent: Mat4, chain: &mut Vec<Node<'a>>, scene: &SceneData, out: &mut Vec<LoadedShape>) 
+    -> Result<(), ImportError>
+{
+    let animated = &scene.animated;
+    // glTF matrices are column major for column vectors, which is laid out the same as our row major for row vectors
+    let world = Mat4::new(node.transform().matrix()) * parent;
+    chain.push(node.clone());
+
+    if let Some(mesh) = node.mesh() {
+        // Only shapes under an animated node get an animation
+        let animation = chain.iter().any(|n| animated.contains_key(&n.index())).then(|| {
+            let nodes = chain.iter()
+                .map(|n| animated.get(&n.index()).cloned().unwrap_or_else(|| rest_pose(n)))
+                .collect();
+            Keyframes { nodes, looping: true }
+        });
+
+        for primitive in mesh.primitives() {
+            out.push(LoadedShape {
+                mesh: read_primitive(&primitive, mesh.name(), &scene.buffers, scene.file)?,
+                lods: Vec::new(),
+                // The default material has no textures, so there's never anything to warn about
+                material: match primitive.material().index() {
+                    Some(idx) => scene.materials[idx].clone(),
+                    None => convert_material(primitive.material(), scene.file, &mut ImportReport::default()),
+                },
+                transform: Some(Transform::from_matrix(world)),
+                animation: animation.clone(),
+            });
+        }
+    }
+
+    for child in node.children() {
+        visit_node(child, world, chain, scene, out)?;
+    }
+    chain.pop();
+    Ok(())
+}
+
+/// Reads every primitive in the default scene, without touching the GPU
+/// Anything that had to be skipped goes in `report`
+pub fn load_gltf(bytes: &[u8], name: &str, resolver: &dyn Resolver, animation: Option<usize>, report: &mut ImportReport) 
+    -> Result<Vec<LoadedShape>, ImportError> 
+{
+    let gltf = Gltf::from_slice(bytes).map_err(|err| ImportError::FileError(Box::new(err)))?;
+    let buffers = load_buffers(&gltf, name, resolver)?;
+    let animated = match animation {
+        Some(index) => read_animation(&gltf, index, &buffers, name, report)?,
+        None => HashMap::new(),
+    };
+    let materials = gltf.materials().map(|material| convert_material(material, name, report)).collect();
+    let data = SceneData { file: name, buffers, animated, materials };
+
+    let scene = gltf.default_scene().or_else(|| gltf.scenes().next())
+        .ok_or_else(|| invalid(name, "the file has no scenes".to_string()))?;
+    let mut out = Vec::new();
+    for node in scene.nodes() {
+        visit_node(node, IDENTITY, &mut Vec::new(), &data, &mut out)?;
+    }
+    Ok(out)
+}
+
+impl Shape {
+    /// Loads the default scene of a `.gltf` or `.glb` file, as one shape per primitive
+    /// Each shape is placed by the nodes above it, and plays the animation at index `animation` if it's given
+    /// Buffers and textures in other files are looked up next to this one
+    pub fn from_gltf(
+        path: &str,
+        shader_type: shaders::ShaderType,
+        animation: Option<usize>,
+        options: &ImportOptions,) -> Result<Vec<Shape>, ImportError>
+    {
+        let path = Path::new(path);
+        if path.extension() != Some("gltf".as_ref()) && path.extension() != Some("glb".as_ref()) {
+            return Err(ImportError::IncorrectExtension);
+        }
+        let bytes = fs::read(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+        let resolver = FileResolver::new(path.parent().unwrap_or(Path::new("")));
+        Shape::from_gltf_slice(&bytes, &path.to_string_lossy(), &resolver, shader_type, animation, options)
+    }
+
+    /// Like `from_gltf`, but the file is already in memory
+    /// `name` is only used in error messages, and `resolver` finds the files it refers to
+    pub fn from_gltf_slice(
+        bytes: &[u8],
+        name: &str,
+        resolver: &dyn Resolver,
+        shader_type: shaders::ShaderType,
+        animation: Option<usize>,
+        options: &ImportOptions,) -> Result<Vec<Shape>, ImportError>
+    {
+        let mut report = ImportReport::default();
+        let mut shapes = load_gltf(bytes, name, resolver, animation, &mut report)?;
+        check_meshes(shapes.iter_mut().map(|shape| &mut shape.mesh), name, options, &mut report);
+        report.log();
+        let lods = process_meshes(shapes.iter_mut().map(|shape| &mut shape.mesh), options);
+        for (shape, lods) in shapes.iter_mut().zip(lods) {
+            shape.lods = lods;
+        }
+        Ok(shapes.into_iter().map(|shape| shape.upload(shader_type)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square in the XY plane, drawn as a triangle strip, a triangle fan and a line loop, under a moved node
+    /// Its corners go (0, 0), (1, 0), (0, 1), (1, 1), and the fan and loop go around them as 0, 1, 3, 2
+    fn square() -> String {
+        let mut buffer = Vec::new();
+        let floats = |buffer: &mut Vec<u8>, values: &[f32]| buffer.extend(values.iter().flat_map(|x| x.to_le_bytes()));
+        floats(&mut buffer, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        floats(&mut buffer, &[0.0, 0.0, 1.0].repeat(4));
+        floats(&mut buffer, &[1.0, 0.0, 0.0, 1.0].repeat(4));
+        floats(&mut buffer, &[0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+        buffer.extend([0u16, 1, 3, 2].iter().flat_map(|i| i.to_le_bytes()));
+
+        let views = [(0, 48), (48, 48), (96, 64), (160, 32), (192, 8)]
+            .map(|(offset, len)| format!(r#"{{"buffer": 0, "byteOffset": {offset}, "byteLength": {len}}}"#))
+            .join(", ");
+        let attributes = r#"{"POSITION": 0, "NORMAL": 1, "TANGENT": 2, "TEXCOORD_0": 3}"#;
+        format!(r#"{{
+            "asset": {{"version": "2.0"}},
+            "scene": 0,
+            "scenes": [{{"nodes": [0]}}],
+            "nodes": [{{"mesh": 0, "translation": [1, 2, 3]}}],
+            "meshes": [{{"name": "square", "primitives": [
+                {{"attributes": {attributes}, "mode": 5}},
+                {{"attributes": {attributes}, "indices": 4, "mode": 6}},
+                {{"attributes": {{"POSITION": 0, "TANGENT": 2}}, "indices": 4, "mode": 2}}
+            ]}}],
+            "accessors": [
+                {{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]}},
+                {{"bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC3"}},
+                {{"bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC4"}},
+                {{"bufferView": 3, "componentType": 5126, "count": 4, "type": "VEC2"}},
+                {{"bufferView": 4, "componentType": 5123, "count": 4, "type": "SCALAR"}}
+            ],
+            "bufferViews": [{views}],
+            "buffers": [{{"byteLength": {}, "uri": "data:application/octet-stream;base64,{}"}}]
+        }}"#, buffer.len(), base64::encode(&buffer))
+    }
+
+    fn load() -> Vec<LoadedShape> {
+        load_gltf(square().as_bytes(), "square.gltf", &NoResolver, None, &mut ImportReport::default()).unwrap()
+    }
+
+    #[test]
+    fn strips_and_fans_are_unrolled_facing_the_same_way() {
+        let shapes = load();
+        assert_eq!(shapes.len(), 3);
+        for shape in &shapes[..2] {
+            let mesh = &shape.mesh;
+            assert_eq!(mesh.ranges[0].primitive, Primitive::Triangles);
+            assert_eq!(mesh.ranges[0].group.as_deref(), Some("square"));
+            assert_eq!(mesh.triangles().count(), 2);
+            for tri in mesh.triangles() {
+                assert_eq!(face_normal(&tri.map(|v| mesh.positions[v as usize])), [0.0, 0.0, 1.0]);
+            }
+        }
+        assert_eq!(shapes[0].mesh.indices, vec![0, 1, 2, 1, 3, 2]);
+        assert_eq!(shapes[1].mesh.indices, vec![0, 1, 3, 0, 3, 2]);
+
+        // A loop closes back on its first vertex
+        let lines = &shapes[2].mesh;
+        assert_eq!(lines.ranges[0].primitive, Primitive::Lines);
+        assert_eq!(lines.indices, vec![0, 1, 1, 3, 3, 2, 2, 0]);
+    }
+
+    #[test]
+    fn textures_and_tangents_are_turned_for_opengl() {
+        let shapes = load();
+        let mesh = &shapes[0].mesh;
+        // V counts up from the bottom, which turns the bitangents around
+        assert_eq!(mesh.tex_coords, Some(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]));
+        assert_eq!(mesh.tangents, Some(vec![[1.0, 0.0, 0.0, -1.0]; 4]));
+        assert_eq!(mesh.normals, vec![[0.0, 0.0, 1.0]; 4]);
+        // The loop has tangents but no normals, and tangents mean nothing alongside normals we worked out
+        assert!(shapes[2].mesh.tangents.is_none());
+
+        let transform = shapes[0].transform.as_ref().unwrap();
+        assert_eq!(transform.transform_matrix.inner[3], [1.0, 2.0, 3.0, 1.0]);
+    }
+}
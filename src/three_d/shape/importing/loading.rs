@@ -0,0 +1,245 @@
+use std::{fs, future::Future, io::BufReader, path::Path, pin::Pin, thread};
+use std::sync::{Arc, Mutex, mpsc, atomic::{AtomicUsize, Ordering}};
+use std::task::{Context, Poll, Waker};
+
+use crate::three_d::animation::Keyframes;
+use super::*;
+
+/// A shape that's been read from a file but not uploaded to the GPU yet
+/// Unlike `Shape` this can be sent between threads, so files can be read away from the GL thread
+#[derive(Clone)]
+pub struct LoadedShape {
+    pub mesh: Mesh,
+    /// Simpler versions of the mesh from the most detailed down, with the same attributes as it
+    pub lods: Vec<Mesh>,
+    pub material: Material,
+    /// Where the file placed the shape, if the format can say
+    pub transform: Option<Transform>,
+    /// The file's own animation for the shape, if it has one
+    pub animation: Option<Keyframes>,
+}
+
+impl LoadedShape {
+    /// Creates the shape's buffers, this has to happen on the thread that owns the GL context
+    pub fn upload(self, shader_type: shaders::ShaderType) -> Shape {
+        let animation = self.animation.map(|a| Box::new(a) as Box<dyn Animation>);
+        let mut shape = Shape::from_mesh(self.mesh, shader_type, self.transform, animation, self.material);
+        shape.set_lods(self.lods);
+        shape
+    }
+}
+
+/// Everything read from one file
+#[derive(Clone, Default)]
+pub struct LoadedFile {
+    pub shapes: Vec<LoadedShape>,
+    pub report: ImportReport,
+}
+
+/// How far along a load is, shared between the thread doing it and whoever is waiting on it
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl Progress {
+    /// From 0 to 1, or 0 if the loader hasn't worked out how much there is to do yet
+    pub fn fraction(&self) -> f32 {
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        (self.done.load(Ordering::Relaxed) as f32 / total as f32).min(1.0)
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, amount: usize) {
+        self.done.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        let total = self.total.load(Ordering::Relaxed).max(1);
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(total, Ordering::Relaxed);
+    }
+}
+
+/// Reads any file we have an importer for, picking the importer by extension
+/// Nothing here touches the GPU, so it can run on any thread. OBJs are also parsed in parallel
+/// Anything that had to be skipped or changed goes in the report instead of failing the load
+pub fn load_file(path: &str, options: &ImportOptions, progress: Option<&Progress>) -> Result<LoadedFile, ImportError> {
+    if options.cache {
+        return load_cached(path, options, progress);
+    }
+    let path = Path::new(path);
+    // Anything the file refers to is looked up next to it
+    load_with(path, &FileResolver::new(path.parent().unwrap_or(Path::new(""))), options, progress)
+}
+
+/// Like `load_file`, but looks up the files `path` refers to with `resolver`
+pub(super) fn load_with(path: &Path, resolver: &dyn Resolver, options: &ImportOptions, progress: Option<&Progress>)
+    -> Result<LoadedFile, ImportError>
+{
+    let name = path.to_string_lossy();
+    let mut report = ImportReport::default();
+    let open = || fs::File::open(path).map(BufReader::new).map_err(|err| ImportError::FileError(Box::new(err)));
+    let single = |mesh: Mesh, material: Material| vec![LoadedShape { mesh, lods: Vec::new(), material, transform: None, animation: None }];
+
+    let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase);
+    let mut shapes = match extension.as_deref() {
+        Some("obj") => {
+            let f = read_text(open()?)?;
+            let meshes = parse_obj(&f, &name, resolver, options, progress, &mut report)?.split();
+            // Each material the file uses gets its own shape
+            meshes.into_iter().map(|(mesh, material)| LoadedShape { mesh, lods: Vec::new(), material, transform: None, animation: None }).collect()
+        },
+        Some("ply") => single(ply::read_ply(open()?, &name)?, Material::default()),
+        Some("stl") => single(stl::read_stl(open()?, &name, options)?, Material::default()),
+        Some("gltf") | Some("glb") => {
+            let bytes = fs::read(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+            gltf::load_gltf(&bytes, &name, resolver, None, &mut report)?
+        },
+        _ => return Err(ImportError::IncorrectExtension),
+    };
+
+    check_meshes(shapes.iter_mut().map(|shape| &mut shape.mesh), &name, options, &mut report);
+    let lods = process_meshes(shapes.iter_mut().map(|shape| &mut shape.mesh), options);
+    for (shape, lods) in shapes.iter_mut().zip(lods) {
+        shape.lods = lods;
+    }
+
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+    Ok(LoadedFile { shapes, report })
+}
+
+/// Loads a file on a new thread
+/// Poll it from the render loop, or wait on it, then upload the shapes it gives back on the GL thread
+pub fn load_in_background(path: &str, options: &ImportOptions) -> LoadHandle {
+    let progress = Arc::new(Progress::default());
+    let waker: Arc<Mutex<Option<Waker>>> = Arc::default();
+    let (sender, receiver) = mpsc::channel();
+
+    let path = path.to_string();
+    let options = options.clone();
+    let (thread_progress, thread_waker) = (progress.clone(), waker.clone());
+    thread::spawn(move || {
+        let result = load_file(&path, &options, Some(&thread_progress));
+        // The handle might have been dropped, in which case nobody wants the result anyway
+        let _ = sender.send(result);
+        if let Some(waker) = thread_waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    });
+
+    LoadHandle { progress, receiver, waker, finished: false }
+}
+
+/// A load running on another thread
+pub struct LoadHandle {
+    progress: Arc<Progress>,
+    receiver: mpsc::Receiver<Result<LoadedFile, ImportError>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    finished: bool,
+}
+
+impl LoadHandle {
+    /// From 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.progress.fraction()
+    }
+
+    /// Whether the result has been handed out already
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The result if the load is done, without blocking
+    /// It's only handed out once, after that this always gives back `None`
+    pub fn poll(&mut self) -> Option<Result<LoadedFile, ImportError>> {
+        if self.finished {
+            return None;
+        }
+        let result = match self.receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => Err(loader_panicked()),
+        };
+        self.finished = true;
+        Some(result)
+    }
+
+    /// Blocks until the load is done
+    /// If `poll` already handed the result out, this gives back `ImportError::ResultTaken`
+    pub fn wait(self) -> Result<LoadedFile, ImportError> {
+        if self.finished {
+            return Err(ImportError::ResultTaken);
+        }
+        self.receiver.recv().unwrap_or_else(|_| Err(loader_panicked()))
+    }
+}
+
+fn loader_panicked() -> ImportError {
+    ImportError::FileError("the loading thread panicked".into())
+}
+
+/// Awaiting the handle gives back the result without blocking the executor
+/// Polling it again once it's ready gives back `ImportError::ResultTaken`
+impl Future for LoadHandle {
+    type Output = Result<LoadedFile, ImportError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let handle = self.get_mut();
+        if handle.finished {
+            return Poll::Ready(Err(ImportError::ResultTaken));
+        }
+        if let Some(result) = LoadHandle::poll(handle) {
+            return Poll::Ready(result);
+        }
+        *handle.waker.lock().unwrap() = Some(cx.waker().clone());
+        // The load could have finished before the waker was stored, in which case nobody would wake us
+        match LoadHandle::poll(handle) {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polls `handle` until the load is done
+    fn poll_until_done(handle: &mut LoadHandle) -> Result<LoadedFile, ImportError> {
+        loop {
+            if let Some(result) = handle.poll() {
+                return result;
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn results_are_handed_out_once() {
+        let mut handle = load_in_background("media/square.obj", &ImportOptions::default());
+        assert_eq!(poll_until_done(&mut handle).unwrap().shapes.len(), 1);
+        assert!(handle.is_finished());
+        assert_eq!(handle.progress(), 1.0);
+        assert!(handle.poll().is_none());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(matches!(Pin::new(&mut handle).poll(&mut cx), Poll::Ready(Err(ImportError::ResultTaken))));
+        assert!(matches!(handle.wait(), Err(ImportError::ResultTaken)));
+    }
+
+    #[test]
+    fn failed_loads_come_back_as_errors() {
+        let mut handle = load_in_background("media/missing.obj", &ImportOptions::default());
+        assert!(matches!(poll_until_done(&mut handle), Err(ImportError::FileError(_))));
+    }
+}
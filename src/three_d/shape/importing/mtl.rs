@@ -0,0 +1,482 @@
+use std::{fs, fmt, collections::HashMap, io::{BufRead, BufReader, Read}};
+
+use super::*;
+
+pub const MAT: Material = Material {
+    name: None,
+    ambient_color: [0.2, 0.0, 0.0],
+    diffuse_color: [0.6, 0.0, 0.0],
+    emission_color: [0.0; 3],
+    specular_color: [1.0, 1.0, 1.0],
+    specular_exp: 16.0,
+    transparency: 1.0,
+    transmission_filter_color: None,
+    optical_density: None,
+    illum_model: None,
+    textures: MaterialTextures::new(),
+    pbr: None,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+// This could be done better as multiple settings instead of one list
+pub enum IlluminationModel {
+    COnAmOff,
+    COnAmOn,
+    HighOn,
+    ReOnRTOn,
+    TransGlassOnReRTOn,
+    ReFresOnRTOn,
+    TransRefracOnReFresOffRtOn,
+    TransRefracOnReFresOnRtOn,
+    ReOnRTOff,
+    TransGlassOnReRTOff,
+    InvisShadow,
+}
+
+impl IlluminationModel {
+    const ALL: [IlluminationModel; 11] = [
+        IlluminationModel::COnAmOff,
+        IlluminationModel::COnAmOn,
+        IlluminationModel::HighOn,
+        IlluminationModel::ReOnRTOn,
+        IlluminationModel::TransGlassOnReRTOn,
+        IlluminationModel::ReFresOnRTOn,
+        IlluminationModel::TransRefracOnReFresOffRtOn,
+        IlluminationModel::TransRefracOnReFresOnRtOn,
+        IlluminationModel::ReOnRTOff,
+        IlluminationModel::TransGlassOnReRTOff,
+        IlluminationModel::InvisShadow,
+    ];
+
+    /// The model with the number `illum` uses for it
+    pub fn from_index(idx: u32) -> Option<IlluminationModel> {
+        IlluminationModel::ALL.get(idx as usize).copied()
+    }
+
+    /// The number `illum` uses for this model
+    pub fn index(&self) -> u32 {
+        *self as u32
+    }
+}
+
+/// Which channel of a texture a scalar map (like a bump map) reads from, set by `-imfchan`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureChannel {
+    Red,
+    Green,
+    Blue,
+    Matte,
+    Luminance,
+    Depth,
+}
+
+/// The options that can come before the file name in a texture map statement
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureOptions {
+    /// `-blendu` and `-blendv`, horizontal and vertical texture blending
+    pub blend_u: bool,
+    pub blend_v: bool,
+    /// `-bm`, only used by bump maps
+    pub bump_multiplier: f32,
+    /// `-boost`, sharpens mip-mapped textures
+    pub boost: Option<f32>,
+    /// `-cc`, color correction
+    pub color_correction: bool,
+    /// `-clamp`, clamps texture coordinates to 0 to 1 instead of repeating
+    pub clamp: bool,
+    /// `-imfchan`
+    pub channel: Option<TextureChannel>,
+    /// `-mm`, the base value and gain applied to the texture's values
+    pub range: (f32, f32),
+    /// `-o`, `-s` and `-t`, in uvw
+    pub offset: [f32; 3],
+    pub scale: [f32; 3],
+    pub turbulence: [f32; 3],
+    /// `-texres`
+    pub resolution: Option<u32>,
+    /// `-type`, only used by reflection maps (sphere, cube_top, ...)
+    pub reflection_type: Option<String>,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        TextureOptions { blend_u: true, blend_v: true, bump_multiplier: 1.0, boost: None, color_correction: false, 
+            clamp: false, channel: None, range: (0.0, 1.0), offset: [0.0; 3], scale: [1.0; 3], turbulence: [0.0; 3], 
+            resolution: None, reflection_type: None }
+    }
+}
+
+/// A reference to a texture file from a material
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureMap {
+    /// The file name, exactly as it's written in the MTL
+    pub file: String,
+    pub options: TextureOptions,
+}
+
+/// Writes the map the way it appears after the statement in an MTL, leaving out default options
+impl fmt::Display for TextureMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let defaults = TextureOptions::default();
+        let opts = &self.options;
+        let on_off = |b: bool| if b { "on" } else { "off" };
+
+        if opts.blend_u != defaults.blend_u { write!(f, "-blendu {} ", on_off(opts.blend_u))?; }
+        if opts.blend_v != defaults.blend_v { write!(f, "-blendv {} ", on_off(opts.blend_v))?; }
+        if opts.bump_multiplier != defaults.bump_multiplier { write!(f, "-bm {} ", opts.bump_multiplier)?; }
+        if let Some(boost) = opts.boost { write!(f, "-boost {boost} ")?; }
+        if opts.color_correction != defaults.color_correction { write!(f, "-cc {} ", on_off(opts.color_correction))?; }
+        if opts.clamp != defaults.clamp { write!(f, "-clamp {} ", on_off(opts.clamp))?; }
+        if let Some(channel) = opts.channel {
+            let c = match channel {
+                TextureChannel::Red => "r",
+                TextureChannel::Green => "g",
+                TextureChannel::Blue => "b",
+                TextureChannel::Matte => "m",
+                TextureChannel::Luminance => "l",
+                TextureChannel::Depth => "z",
+            };
+            write!(f, "-imfchan {c} ")?;
+        }
+        if opts.range != defaults.range { write!(f, "-mm {} {} ", opts.range.0, opts.range.1)?; }
+        for (flag, value, default) in [("-o", opts.offset, defaults.offset), ("-s", opts.scale, defaults.scale), ("-t", opts.turbulence, defaults.turbulence)] {
+            if value != default { write!(f, "{flag} {} {} {} ", value[0], value[1], value[2])?; }
+        }
+        if let Some(res) = opts.resolution { write!(f, "-texres {res} ")?; }
+        if let Some(ty) = &opts.reflection_type { write!(f, "-type {ty} ")?; }
+
+        write!(f, "{}", self.file)
+    }
+}
+
+impl TextureMap {
+    /// Loads the image this map refers to, finding it through `resolver`
+    pub fn load(&self, resolver: &dyn Resolver) -> Result<image::DynamicImage, ImportError> {
+        let mut bytes = Vec::new();
+        resolver.open(&self.file)
+            .and_then(|mut reader| reader.read_to_end(&mut bytes))
+            .map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+        image::load_from_memory(&bytes).map_err(|err| ImportError::FileError(Box::new(err)))
+    }
+}
+
+/// Every texture map a material can have, none of them are required
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialTextures {
+    /// `map_Ka`
+    pub ambient: Option<TextureMap>,
+    /// `map_Kd`
+    pub diffuse: Option<TextureMap>,
+    /// `map_Ks`
+    pub specular: Option<TextureMap>,
+    /// `map_Ns`
+    pub specular_exp: Option<TextureMap>,
+    /// `map_Ke`
+    pub emission: Option<TextureMap>,
+    /// `map_d`
+    pub dissolve: Option<TextureMap>,
+    /// `map_bump` or `bump`
+    pub bump: Option<TextureMap>,
+    /// `disp`
+    pub displacement: Option<TextureMap>,
+    /// `decal`
+    pub decal: Option<TextureMap>,
+    /// `refl`, which can be given once per cube face
+    pub reflection: Vec<TextureMap>,
+}
+
+impl MaterialTextures {
+    pub const fn new() -> MaterialTextures {
+        MaterialTextures { ambient: None, diffuse: None, specular: None, specular_exp: None, emission: None, 
+            dissolve: None, bump: None, displacement: None, decal: None, reflection: Vec::new() }
+    }
+}
+
+/// The physically based rendering extension to MTL
+#[derive(Clone, Debug, PartialEq)]
+pub struct PbrMaterial {
+    /// `Pr`
+    pub roughness: f32,
+    /// `Pm`
+    pub metallic: f32,
+    /// `Ps`
+    pub sheen: f32,
+    /// `Pc`
+    pub clearcoat_thickness: f32,
+    /// `Pcr`
+    pub clearcoat_roughness: f32,
+    /// `aniso`
+    pub anisotropy: f32,
+    /// `anisor`
+    pub anisotropy_rotation: f32,
+
+    /// `map_Pr`
+    pub roughness_map: Option<TextureMap>,
+    /// `map_Pm`
+    pub metallic_map: Option<TextureMap>,
+    /// `map_Ps`
+    pub sheen_map: Option<TextureMap>,
+    /// `norm`, a tangent space normal map
+    pub normal_map: Option<TextureMap>,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        PbrMaterial { roughness: 1.0, metallic: 0.0, sheen: 0.0, clearcoat_thickness: 0.0, clearcoat_roughness: 0.0,
+            anisotropy: 0.0, anisotropy_rotation: 0.0, roughness_map: None, metallic_map: None, sheen_map: None, normal_map: None }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    /// The name given by `newmtl`, if this material came from a file
+    pub name: Option<String>,
+    pub ambient_color: [f32; 3],
+    pub diffuse_color: [f32; 3],
+    /// This is HDR, so channels can go above 1 and should be tone mapped after lighting
+    pub emission_color: [f32; 3],
+    pub specular_color: [f32; 3],
+    pub specular_exp: f32,
+    pub transparency: f32,
+    pub transmission_filter_color: Option<[f32; 3]>,
+    pub optical_density: Option<f32>,
+    pub illum_model: Option<IlluminationModel>,
+    pub textures: MaterialTextures,
+    /// Only there if the material used any of the PBR statements
+    pub pbr: Option<PbrMaterial>,
+}
+
+pub const DEFAULT_SPEC_EXP: f32 = 16.0;
+
+impl Default for Material {
+    fn default() -> Self {
+        Material { name: None, ambient_color: [0.0; 3], diffuse_color: [1.0; 3], emission_color: [0.0; 3],
+            specular_color: [0.0; 3], specular_exp: DEFAULT_SPEC_EXP, 
+            transparency: 1.0, transmission_filter_color: None, optical_density: None, illum_model: None,
+            textures: MaterialTextures::new(), pbr: None}
+    }
+}
+
+/// Reads an MTL color, given either as `r g b` or as a single value used for all three channels
+fn parse_color(tokens: &mut Tokens) -> Result<[f32; 3], ImportError> {
+    let first = tokens.expect("a color")?;
+    if first == "xyz" || first == "spectral" {
+        // TODO: Parse CIEXYZ and spectral curve colors
+        return Err(tokens.error(format!("'{first}' colors aren't supported")));
+    }
+    let r = tokens.parse_token(first, "a red value")?;
+    let g = tokens.parse_opt("a green value")?;
+    let b = tokens.parse_opt("a blue value")?;
+
+    match (g, b) {
+        (Some(g), Some(b)) => Ok([r, g, b]),
+        (None, None) => Ok([r; 3]),
+        _ => Err(tokens.missing("a blue value")),
+    }
+}
+
+fn parse_on_off(tokens: &mut Tokens) -> Result<bool, ImportError> {
+    match tokens.expect("on or off")? {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        tok => Err(tokens.error(format!("expected on or off, found '{tok}'"))),
+    }
+}
+
+/// Reads `u [v [w]]`, where anything left out gets `default`
+fn parse_uvw(tokens: &mut Tokens, default: f32) -> Result<[f32; 3], ImportError> {
+    let u = tokens.parse("a u value")?;
+    let v = tokens.parse_if().unwrap_or(default);
+    let w = tokens.parse_if().unwrap_or(default);
+    Ok([u, v, w])
+}
+
+/// Reads the options and file name of a texture map statement, like `map_Kd -s 2 2 wood.png`
+fn parse_texture_map(tokens: &mut Tokens) -> Result<TextureMap, ImportError> {
+    let mut options = TextureOptions::default();
+
+    // Options all start with a dash, but so can a negative number
+    while let Some(opt) = tokens.peek().filter(|tok| tok.starts_with('-') && tok.parse::<f32>().is_err()) {
+        tokens.next();
+        match opt {
+            "-blendu" => options.blend_u = parse_on_off(tokens)?,
+            "-blendv" => options.blend_v = parse_on_off(tokens)?,
+            "-bm" => options.bump_multiplier = tokens.parse("a bump multiplier")?,
+            "-boost" => options.boost = Some(tokens.parse("a boost value")?),
+            "-cc" => options.color_correction = parse_on_off(tokens)?,
+            "-clamp" => options.clamp = parse_on_off(tokens)?,
+            "-imfchan" => {
+                options.channel = Some(match tokens.expect("a channel")? {
+                    "r" => TextureChannel::Red,
+                    "g" => TextureChannel::Green,
+                    "b" => TextureChannel::Blue,
+                    "m" => TextureChannel::Matte,
+                    "l" => TextureChannel::Luminance,
+                    "z" => TextureChannel::Depth,
+                    tok => return Err(tokens.error(format!("expected one of r, g, b, m, l or z, found '{tok}'"))),
+                });
+            },
+            "-mm" => options.range = (tokens.parse("a base value")?, tokens.parse("a gain value")?),
+            "-o" => options.offset = parse_uvw(tokens, 0.0)?,
+            "-s" => options.scale = parse_uvw(tokens, 1.0)?,
+            "-t" => options.turbulence = parse_uvw(tokens, 0.0)?,
+            "-texres" => options.resolution = Some(tokens.parse("a texture resolution")?),
+            "-type" => options.reflection_type = Some(tokens.expect("a reflection type")?.to_string()),
+            _ => return Err(tokens.error(format!("unknown texture option '{opt}'"))),
+        }
+    }
+
+    // File names can have spaces in them, so take the rest of the line
+    let file = tokens.rest();
+    if file.is_empty() {
+        return Err(tokens.missing("a texture file name"));
+    }
+    Ok(TextureMap { file: file.to_string(), options })
+}
+
+impl Material {
+    /// The PBR block, which gets created the first time it's needed
+    fn pbr_mut(&mut self) -> &mut PbrMaterial {
+        self.pbr.get_or_insert_with(PbrMaterial::default)
+    }
+
+    pub fn from_mtl(path: &str, options: &ImportOptions) -> Result<HashMap<String, Material>, ImportError> {
+        let file = fs::File::open(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+        Material::from_mtl_reader(BufReader::new(file), path, options)
+    }
+
+    /// Reads a material library from any reader, `name` is only used in error messages
+    /// Anything skipped or clamped is logged as a warning
+    pub fn from_mtl_reader<R: BufRead>(reader: R, name: &str, options: &ImportOptions) 
+        -> Result<HashMap<String, Material>, ImportError> 
+    {
+        let mut report = ImportReport::default();
+        let materials = Material::read_mtl(reader, name, options, &mut report)?;
+        report.log();
+        Ok(materials)
+    }
+
+    /// Like `from_mtl_reader`, but warnings go in `report`
+    pub(super) fn read_mtl<R: BufRead>(reader: R, name: &str, options: &ImportOptions, report: &mut ImportReport) 
+        -> Result<HashMap<String, Material>, ImportError> 
+    {
+        let f = read_text(reader)?;
+        // Values outside of what the format allows are pulled back in, with a warning
+        let clamp = |report: &mut ImportReport, value: f32, (min, max): (f32, f32), what: &str, line_no: usize| {
+            if !(min..=max).contains(&value) {
+                report.warn(name, Some(line_no), format!("{what} {value} is outside of {min} to {max}, clamping it"));
+            }
+            value.clamp(min, max)
+        };
+
+        let mut materials = HashMap::new();
+        let mut current_mat: Option<Material> = None;
+        let mut current_mat_name: Option<String> = None;
+
+        for (line_no, line) in logical_lines(&f) {
+            let mut tokens = Tokens::new(name, line_no, &line);
+
+            let statement = tokens.next().unwrap_or("");
+            if statement == "newmtl" {
+                // Insert old material
+                if let Some(mat) = current_mat {
+                    materials.insert(current_mat_name.unwrap(), mat);
+                }
+
+                // Create new material
+                current_mat_name = Some(tokens.rest().to_string()).filter(|name| !name.is_empty());
+                if current_mat_name.is_none() {
+                    return Err(tokens.missing("a material name"));
+                }
+                current_mat = Some(Material { name: current_mat_name.clone(), ..Default::default() });
+                continue;
+            }
+            if statement.is_empty() {
+                continue;
+            }
+
+            // Everything else sets a property of the current material, so there has to be one
+            let Some(mat) = current_mat.as_mut() else {
+                return Err(tokens.error(format!("'{statement}' comes before any newmtl")));
+            };
+
+            match statement {
+                "Ka" => {
+                    let color = parse_color(&mut tokens)?;
+
+                    // Blender exports white ambient, which washes everything out with our lighting
+                    mat.ambient_color = if options.zero_ambient { [0.0; 3] } else { color };
+                },
+                "Kd" => {
+                    mat.diffuse_color = parse_color(&mut tokens)?;
+                },
+                "Ks" => {
+                    mat.specular_color = parse_color(&mut tokens)?;
+                },
+                "Ke" => {
+                    // Kept as is, even above 1, since exporters use that for bright emitters
+                    mat.emission_color = parse_color(&mut tokens)?;
+                }
+                "Ns" => {
+                    mat.specular_exp = clamp(report, tokens.parse("a specular exponent")?, (0.0, 1000.0), "specular exponent", line_no);
+                },
+                "d" => {
+                    // Some exporters write `d -halo factor`
+                    let mut tok = tokens.expect("a dissolve factor")?;
+                    if tok == "-halo" {
+                        tok = tokens.expect("a dissolve factor")?;
+                    }
+                    mat.transparency = clamp(report, tokens.parse_token(tok, "a dissolve factor")?, (0.0, 1.0), "dissolve factor", line_no);
+                }
+                "Tr" => {
+                    mat.transparency = 1.0 - clamp(report, tokens.parse("a transparency")?, (0.0, 1.0), "transparency", line_no);
+                }
+                "Tf" => {
+                    mat.transmission_filter_color = Some(parse_color(&mut tokens)?);
+                },
+                "Ni" => {
+                    mat.optical_density = Some(tokens.parse("an optical density")?);
+                },
+                "map_Ka" => mat.textures.ambient = Some(parse_texture_map(&mut tokens)?),
+                "map_Kd" => mat.textures.diffuse = Some(parse_texture_map(&mut tokens)?),
+                "map_Ks" => mat.textures.specular = Some(parse_texture_map(&mut tokens)?),
+                "map_Ns" => mat.textures.specular_exp = Some(parse_texture_map(&mut tokens)?),
+                "map_Ke" => mat.textures.emission = Some(parse_texture_map(&mut tokens)?),
+                "map_d" => mat.textures.dissolve = Some(parse_texture_map(&mut tokens)?),
+                "map_bump" | "map_Bump" | "bump" => mat.textures.bump = Some(parse_texture_map(&mut tokens)?),
+                "disp" => mat.textures.displacement = Some(parse_texture_map(&mut tokens)?),
+                "decal" => mat.textures.decal = Some(parse_texture_map(&mut tokens)?),
+                "refl" => mat.textures.reflection.push(parse_texture_map(&mut tokens)?),
+                // PBR extension
+                "Pr" => mat.pbr_mut().roughness = tokens.parse("a roughness")?,
+                "Pm" => mat.pbr_mut().metallic = tokens.parse("a metalness")?,
+                "Ps" => mat.pbr_mut().sheen = tokens.parse("a sheen")?,
+                "Pc" => mat.pbr_mut().clearcoat_thickness = tokens.parse("a clearcoat thickness")?,
+                "Pcr" => mat.pbr_mut().clearcoat_roughness = tokens.parse("a clearcoat roughness")?,
+                "aniso" => mat.pbr_mut().anisotropy = tokens.parse("an anisotropy")?,
+                "anisor" => mat.pbr_mut().anisotropy_rotation = tokens.parse("an anisotropy rotation")?,
+                "map_Pr" => mat.pbr_mut().roughness_map = Some(parse_texture_map(&mut tokens)?),
+                "map_Pm" => mat.pbr_mut().metallic_map = Some(parse_texture_map(&mut tokens)?),
+                "map_Ps" => mat.pbr_mut().sheen_map = Some(parse_texture_map(&mut tokens)?),
+                "norm" => mat.pbr_mut().normal_map = Some(parse_texture_map(&mut tokens)?),
+                "illum" => {
+                    let index = tokens.parse("an illumination model")?;
+                    mat.illum_model = IlluminationModel::from_index(index);
+                    if mat.illum_model.is_none() {
+                        report.warn(name, Some(line_no), format!("unknown illumination model {index}, ignoring it"));
+                    }
+                }
+
+                _ => report.warn(name, Some(line_no), format!("skipping unknown statement '{statement}'")),
+            }
+        }
+
+        // Insert old material
+        if let Some(mat) = current_mat {
+            materials.insert(current_mat_name.unwrap(), mat);
+        }
+
+        Ok(materials)
+    }
+}
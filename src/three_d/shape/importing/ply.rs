@@ -0,0 +1,435 @@
+use std::{fs, io::{BufRead, BufReader}, path::Path, str::Lines, iter::Enumerate};
+
+use super::*;
+
+/// How the body of a PLY file is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+impl PlyFormat {
+    /// The name used for the format in the header
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScalarType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        Some(match name {
+            "char" | "int8" => ScalarType::Char,
+            "uchar" | "uint8" => ScalarType::UChar,
+            "short" | "int16" => ScalarType::Short,
+            "ushort" | "uint16" => ScalarType::UShort,
+            "int" | "int32" => ScalarType::Int,
+            "uint" | "uint32" => ScalarType::UInt,
+            "float" | "float32" => ScalarType::Float,
+            "double" | "float64" => ScalarType::Double,
+            _ => return None,
+        })
+    }
+
+    /// What a full intensity color is stored as
+    fn max(&self) -> f64 {
+        match self {
+            ScalarType::Char => i8::MAX as f64,
+            ScalarType::UChar => u8::MAX as f64,
+            ScalarType::Short => i16::MAX as f64,
+            ScalarType::UShort => u16::MAX as f64,
+            ScalarType::Int => i32::MAX as f64,
+            ScalarType::UInt => u32::MAX as f64,
+            ScalarType::Float | ScalarType::Double => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    /// A count, followed by that many items
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    /// The index of the first property with one of `names`
+    fn find(&self, names: &[&str]) -> Option<usize> {
+        names.iter().find_map(|name| self.properties.iter().position(|prop| prop.name == *name))
+    }
+}
+
+/// Hands out the values in the body one at a time, whichever format it's in
+enum Body<'a> {
+    Ascii { file: &'a str, lines: Enumerate<Lines<'a>>, first_line: usize, tokens: Tokens<'a> },
+    Binary { file: &'a str, bytes: &'a [u8], pos: usize, big_endian: bool },
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, ty: ScalarType) -> Result<f64, ImportError> {
+        match self {
+            Body::Ascii { file, lines, first_line, tokens } => loop {
+                if let Some(tok) = tokens.next() {
+                    return tokens.parse_token(tok, "a number");
+                }
+                let (i, line) = lines.next()
+                    .ok_or_else(|| invalid(file, "the file ended before all of its elements were read".to_string()))?;
+                *tokens = Tokens::new(file, *first_line + i, line);
+            },
+            Body::Binary { file, bytes, pos, big_endian } => {
+                // Reads a number of type `$t` that takes up `$n` bytes
+                macro_rules! num {
+                    ($t:ty, $n:expr) => {{
+                        let b: [u8; $n] = bytes.get(*pos..*pos + $n)
+                            .and_then(|b| b.try_into().ok())
+                            .ok_or_else(|| invalid(file, "the file ended before all of its elements were read".to_string()))?;
+                        *pos += $n;
+                        (if *big_endian { <$t>::from_be_bytes(b) } else { <$t>::from_le_bytes(b) }) as f64
+                    }};
+                }
+                Ok(match ty {
+                    ScalarType::Char => num!(i8, 1),
+                    ScalarType::UChar => num!(u8, 1),
+                    ScalarType::Short => num!(i16, 2),
+                    ScalarType::UShort => num!(u16, 2),
+                    ScalarType::Int => num!(i32, 4),
+                    ScalarType::UInt => num!(u32, 4),
+                    ScalarType::Float => num!(f32, 4),
+                    ScalarType::Double => num!(f64, 8),
+                })
+            },
+        }
+    }
+}
+
+/// Reads the header, up to and including `end_header`
+/// Returns the format, the elements in order, and how many lines the header took
+fn read_header<R: BufRead>(reader: &mut R, name: &str) -> Result<(PlyFormat, Vec<Element>, usize), ImportError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut bytes = Vec::new();
+    let mut line_no = 0;
+
+    loop {
+        bytes.clear();
+        let read = reader.read_until(b'\n', &mut bytes).map_err(|err| ImportError::FileError(Box::new(err)))?;
+        if read == 0 {
+            return Err(invalid(name, "the header never ended, 'end_header' is missing".to_string()));
+        }
+        line_no += 1;
+        let line = String::from_utf8_lossy(&bytes);
+        let mut tokens = Tokens::new(name, line_no, &line);
+
+        if line_no == 1 {
+            if line.trim() != "ply" {
+                return Err(invalid(name, "this isn't a PLY file, the first line should be 'ply'".to_string()));
+            }
+            continue;
+        }
+
+        let Some(statement) = tokens.next() else { continue };
+        match statement {
+            "format" => {
+                format = Some(match tokens.expect("a format")? {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    other => return Err(tokens.error(format!("unknown format '{other}'"))),
+                });
+                let version = tokens.expect("a version")?;
+                if version != "1.0" {
+                    return Err(tokens.error(format!("only version 1.0 is supported, found '{version}'")));
+                }
+            },
+            "comment" | "obj_info" => {},
+            "element" => {
+                let name = tokens.expect("an element name")?.to_string();
+                let count = tokens.parse("an element count")?;
+                elements.push(Element { name, count, properties: Vec::new() });
+            },
+            "property" => {
+                let scalar = |tokens: &mut Tokens| {
+                    let tok = tokens.expect("a property type")?;
+                    ScalarType::parse(tok).ok_or_else(|| tokens.error(format!("unknown property type '{tok}'")))
+                };
+                let kind = if tokens.peek() == Some("list") {
+                    tokens.next();
+                    PropertyKind::List { count: scalar(&mut tokens)?, item: scalar(&mut tokens)? }
+                } else {
+                    PropertyKind::Scalar(scalar(&mut tokens)?)
+                };
+                let name = tokens.expect("a property name")?.to_string();
+                let element = elements.last_mut().ok_or_else(|| tokens.error("a property came before any element".to_string()))?;
+                element.properties.push(Property { name, kind });
+            },
+            "end_header" => break,
+            _ => return Err(tokens.error(format!("unknown header statement '{statement}'"))),
+        }
+    }
+
+    let format = format.ok_or_else(|| invalid(name, "the header has no format".to_string()))?;
+    Ok((format, elements, line_no))
+}
+
+/// Reads a PLY into a mesh
+/// Vertices can have normals, colors and texture coordinates, and faces and edges become triangles and lines
+/// Other elements and properties are read past, and files with neither faces nor edges become point clouds
+pub fn read_ply<R: BufRead>(mut reader: R, name: &str) -> Result<Mesh, ImportError> {
+    let (format, elements, header_lines) = read_header(&mut reader, name)?;
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+    let text;
+    let mut body = match format {
+        PlyFormat::Ascii => {
+            text = String::from_utf8_lossy(&data);
+            Body::Ascii { file: name, lines: text.lines().enumerate(), first_line: header_lines + 1, tokens: Tokens::new(name, header_lines, "") }
+        },
+        PlyFormat::BinaryLittleEndian => Body::Binary { file: name, bytes: &data, pos: 0, big_endian: false },
+        PlyFormat::BinaryBigEndian => Body::Binary { file: name, bytes: &data, pos: 0, big_endian: true },
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut triangles = Vec::new();
+    let mut lines = Vec::new();
+
+    for element in &elements {
+        // Which properties hold what we need, only looked for in the elements they belong to
+        let (vertex, faces, edges) = (element.name == "vertex", element.name == "face", element.name == "edge");
+        let find_all = |names: [&[&str]; 3]| match names.map(|n| element.find(n)) {
+            [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+            _ => None,
+        };
+        let position = find_all([&["x"], &["y"], &["z"]]).filter(|_| vertex);
+        if vertex && position.is_none() {
+            return Err(invalid(name, "vertices need x, y and z properties".to_string()));
+        }
+        let normal = find_all([&["nx"], &["ny"], &["nz"]]).filter(|_| vertex);
+        let color = find_all([&["red", "r", "diffuse_red"], &["green", "g", "diffuse_green"], &["blue", "b", "diffuse_blue"]])
+            .filter(|_| vertex);
+        let tex_coord = match (element.find(&["s", "u", "texture_u"]), element.find(&["t", "v", "texture_v"])) {
+            (Some(s), Some(t)) if vertex => Some([s, t]),
+            _ => None,
+        };
+        let face = element.find(&["vertex_indices", "vertex_index"]).filter(|_| faces);
+        let edge = match (element.find(&["vertex1"]), element.find(&["vertex2"])) {
+            (Some(a), Some(b)) if edges => Some([a, b]),
+            _ => None,
+        };
+        if let Some(face) = face {
+            if !matches!(element.properties[face].kind, PropertyKind::List { .. }) {
+                return Err(invalid(name, "a face's vertex indices should be a list".to_string()));
+            }
+        }
+
+        let mut values = vec![0.0; element.properties.len()];
+        let mut face_indices = Vec::new();
+        for _ in 0..element.count {
+            face_indices.clear();
+            for (i, prop) in element.properties.iter().enumerate() {
+                match prop.kind {
+                    PropertyKind::Scalar(ty) => values[i] = body.read(ty)?,
+                    PropertyKind::List { count, item } => {
+                        let count = body.read(count)? as usize;
+                        for _ in 0..count {
+                            let value = body.read(item)?;
+                            if face == Some(i) {
+                                face_indices.push(value);
+                            }
+                        }
+                    },
+                }
+            }
+
+            if let Some([x, y, z]) = position {
+                positions.push([values[x] as f32, values[y] as f32, values[z] as f32]);
+            }
+            if let Some([x, y, z]) = normal {
+                normals.push([values[x] as f32, values[y] as f32, values[z] as f32]);
+            }
+            if let Some(channels) = color {
+                // Integer colors go from 0 to their type's max, float colors from 0 to 1
+                colors.push(channels.map(|c| match element.properties[c].kind {
+                    PropertyKind::Scalar(ty) => (values[c] / ty.max()) as f32,
+                    PropertyKind::List { .. } => 0.0,
+                }));
+            }
+            if let Some([s, t]) = tex_coord {
+                tex_coords.push([values[s] as f32, values[t] as f32]);
+            }
+            if face.is_some() {
+                if face_indices.len() < 3 {
+                    return Err(invalid(name, format!("a face has {} vertices, it needs at least 3", face_indices.len())));
+                }
+                // Polygons are split into a fan of triangles
+                for i in 1..face_indices.len() - 1 {
+                    triangles.extend([face_indices[0], face_indices[i], face_indices[i + 1]]);
+                }
+            }
+            if let Some([a, b]) = edge {
+                lines.extend([values[a], values[b]]);
+            }
+        }
+    }
+
+    let len = positions.len();
+    let check = |index: f64| {
+        if index < 0.0 || index as usize >= len {
+            Err(invalid(name, format!("index {index} is out of range, only {len} vertices are defined")))
+        } else {
+            Ok(index as u32)
+        }
+    };
+    let triangles = triangles.into_iter().map(check).collect::<Result<Vec<_>, _>>()?;
+    let lines = lines.into_iter().map(check).collect::<Result<Vec<_>, _>>()?;
+    // With nothing connecting them, the vertices are a point cloud
+    let points = if triangles.is_empty() && lines.is_empty() { (0..len as u32).collect() } else { Vec::new() };
+
+    let mut indices = Vec::new();
+    let mut ranges = Vec::new();
+    for (primitive, elements) in [(Primitive::Triangles, triangles), (Primitive::Lines, lines), (Primitive::Points, points)] {
+        if !elements.is_empty() {
+            ranges.push(DrawRange { primitive, start: indices.len(), count: elements.len(), group: None });
+            indices.extend(elements);
+        }
+    }
+
+    let has_normals = !normals.is_empty();
+    let mut mesh = Mesh {
+        normals: if has_normals { normals } else { vec![[0.0; 3]; len] },
+        positions,
+        colors: if colors.is_empty() { None } else { Some(colors) },
+        tex_coords: if tex_coords.is_empty() { None } else { Some(tex_coords) },
+        tangents: None,
+        indices,
+        ranges,
+    };
+    if !has_normals {
+        mesh.compute_normals();
+    }
+    Ok(mesh)
+}
+
+impl Shape {
+    pub fn from_ply(
+        path: &str,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,
+        options: &ImportOptions,) -> Result<Shape, ImportError>
+    {
+        let path = Path::new(path);
+        if path.extension() != Some("ply".as_ref()) { return Err(ImportError::IncorrectExtension); }
+        let file = fs::File::open(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+        Shape::from_ply_reader(BufReader::new(file), &path.to_string_lossy(), shader_type, transform, animation, options)
+    }
+
+    /// Like `from_ply`, but reads from anything buffered
+    /// `name` is only used in error messages
+    pub fn from_ply_reader<R: BufRead>(
+        reader: R,
+        name: &str,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,
+        options: &ImportOptions,) -> Result<Shape, ImportError>
+    {
+        let mut mesh = read_ply(reader, name)?;
+        let mut report = ImportReport::default();
+        check_meshes([&mut mesh], name, options, &mut report);
+        report.log();
+        let lods = process_meshes([&mut mesh], options).remove(0);
+        let mut shape = Shape::from_mesh(mesh, shader_type, transform, animation, Material::default());
+        shape.set_lods(lods);
+        Ok(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(data: &[u8]) -> Mesh {
+        read_ply(data, "test.ply").unwrap()
+    }
+
+    #[test]
+    fn vertices_alone_are_a_point_cloud() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\n\
+            property uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n0 0 0 255 0 0\n1 2 3 0 0 255\n";
+        let mesh = read(ply.as_bytes());
+        assert_eq!(mesh.positions, vec![[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]]);
+        assert_eq!(mesh.colors, Some(vec![[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]));
+        assert_eq!(mesh.ranges.len(), 1);
+        assert_eq!(mesh.ranges[0].primitive, Primitive::Points);
+        assert_eq!(mesh.indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn binary_bodies_are_read_in_either_order() {
+        // A square as one quad, after an element that isn't ours and with doubles and ints to read past
+        let header = |format: &str| format!("ply\nformat {format} 1.0\ncomment made by hand\nelement material 1\nproperty double shine\n\
+            element vertex 4\nproperty float x\nproperty float y\nproperty float z\nproperty int flags\n\
+            element face 1\nproperty list uchar int vertex_indices\nend_header\n");
+        let corners = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut data = header(format).into_bytes();
+            let f32s = |x: f32| if big_endian { x.to_be_bytes() } else { x.to_le_bytes() };
+            let i32s = |x: i32| if big_endian { x.to_be_bytes() } else { x.to_le_bytes() };
+            data.extend(if big_endian { 0.5f64.to_be_bytes() } else { 0.5f64.to_le_bytes() });
+            for p in corners {
+                data.extend(p.into_iter().flat_map(f32s));
+                data.extend(i32s(7));
+            }
+            data.push(4);
+            data.extend([0, 1, 2, 3].into_iter().flat_map(i32s));
+
+            let mesh = read(&data);
+            assert_eq!(mesh.positions, corners);
+            // The quad is split into a fan, and normals are worked out since the file has none
+            assert_eq!(mesh.triangles().collect::<Vec<_>>(), vec![[0, 1, 2], [0, 2, 3]]);
+            assert!(mesh.normals.iter().all(|n| *n == [0.0, 0.0, 1.0]));
+        }
+    }
+
+    #[test]
+    fn bad_indices_are_errors() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\n\
+            element face 1\nproperty list uchar uint vertex_indices\nend_header\n0 0 0\n3 0 0 5\n";
+        assert!(read_ply(ply.as_bytes(), "test.ply").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+/// Checks meshes read from `name` for problems if `options` asks to, reporting them and repairing what it can
+/// This should go before `process_meshes`, since the processing assumes the meshes are sound
+pub fn check_meshes<'a>(meshes: impl IntoIterator<Item = &'a mut Mesh>, name: &str, options: &ImportOptions, report: &mut ImportReport) {
+    if !options.validate && !options.repair {
+        return;
+    }
+    for mesh in meshes {
+        for problem in mesh.validate().problems() {
+            report.warn(name, None, format!("the mesh has {problem}"));
+        }
+        if options.repair {
+            mesh.repair();
+            for problem in mesh.validate().problems() {
+                report.warn(name, None, format!("after repairing, the mesh still has {problem}"));
+            }
+        }
+    }
+}
+
+/// Applies the geometry options in `options` to every mesh from one file
+/// Bounds for recentering and normalizing are taken over all the meshes together, so they keep their places
+/// relative to each other. Positions are used as they are in the file, without any node transforms
+/// Gives back the levels of detail `options.lod_levels` asks for, for each mesh in order, made from the processed meshes
+pub fn process_meshes<'a>(meshes: impl IntoIterator<Item = &'a mut Mesh>, options: &ImportOptions) -> Vec<Vec<Mesh>> {
+    let mut meshes: Vec<&mut Mesh> = meshes.into_iter().collect();
+    transform_meshes(&mut meshes, options);
+
+    meshes.iter().map(|mesh| {
+        let mut lods = if options.lod_levels > 0 { mesh.lod_chain(options.lod_levels) } else { Vec::new() };
+        if options.optimize {
+            lods.iter_mut().for_each(Mesh::optimize);
+        }
+        lods
+    }).collect()
+}
+
+fn transform_meshes(meshes: &mut [&mut Mesh], options: &ImportOptions) {
+    for mesh in meshes.iter_mut() {
+        if let Some(distance) = options.merge_distance {
+            mesh.merge_by_distance(distance);
+        }
+        if options.flip_winding {
+            mesh.flip_winding();
+        }
+        if options.subdivisions > 0 {
+            **mesh = mesh.subdivide(options.subdivisions, options.crease_angle);
+        }
+        // Normals are worked out after the winding is settled, since that's what decides which way they face
+        match options.normals {
+            NormalSource::File => {},
+            NormalSource::Smooth => mesh.compute_normals(),
+            NormalSource::Flat => mesh.compute_flat_normals(),
+        }
+        // Tangents are worked out from the normals, so the file's don't go with new ones
+        if options.normals != NormalSource::File {
+            mesh.tangents = None;
+        }
+        if options.flip_v {
+            mesh.flip_v();
+        }
+        if options.z_up {
+            // A quarter turn around X, which keeps the winding the same
+            let turn = |v: &mut [f32; 3]| *v = [v[0], v[2], -v[1]];
+            mesh.positions.iter_mut().for_each(turn);
+            mesh.normals.iter_mut().for_each(turn);
+            for tangent in mesh.tangents.iter_mut().flatten() {
+                *tangent = [tangent[0], tangent[2], -tangent[1], tangent[3]];
+            }
+        }
+        if let Some(scale) = options.scale {
+            for p in mesh.positions.iter_mut() {
+                *p = p.map(|x| x * scale);
+            }
+            // A negative scale mirrors the mesh, which would turn it inside out
+            if scale < 0.0 {
+                mesh.flip_winding();
+                mesh.normals.iter_mut().for_each(|n| *n = n.map(|x| -x));
+                mesh.tangents = None;
+            }
+        }
+        if options.optimize {
+            let before = mesh.acmr(ACMR_CACHE_SIZE);
+            mesh.optimize();
+            log::info!("optimized a mesh of {} triangles, ACMR {before:.3} before and {:.3} after",
+                mesh.triangles().count(), mesh.acmr(ACMR_CACHE_SIZE));
+        }
+    }
+
+    if !options.recenter && !options.normalize {
+        return;
+    }
+    let Some(bounds) = meshes.iter().filter_map(|mesh| mesh.bounds()).reduce(|a, b| Bounds {
+        min: [a.min[0].min(b.min[0]), a.min[1].min(b.min[1]), a.min[2].min(b.min[2])],
+        max: [a.max[0].max(b.max[0]), a.max[1].max(b.max[1]), a.max[2].max(b.max[2])],
+    }) else { return };
+
+    let center = if options.recenter { bounds.center() } else { [0.0; 3] };
+    let size = bounds.size().into_iter().fold(0.0, f32::max);
+    let scale = if options.normalize && size > 0.0 { 1.0 / size } else { 1.0 };
+    for mesh in meshes.iter_mut() {
+        for p in mesh.positions.iter_mut() {
+            *p = [(p[0] - center[0]) * scale, (p[1] - center[1]) * scale, (p[2] - center[2]) * scale];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lods_come_from_any_importer() {
+        let obj = include_str!("../../../../media/monkey.obj");
+        let options = ImportOptions { lod_levels: 2, optimize: true, ..ImportOptions::default() };
+        let mut report = ImportReport::default();
+        let (mut mesh, _) = read_obj(obj.as_bytes(), "monkey.obj", &NoResolver, &options, &mut report).unwrap().remove(0);
+        check_meshes([&mut mesh], "monkey.obj", &options, &mut report);
+
+        let lods = process_meshes([&mut mesh], &options).remove(0);
+        assert_eq!(lods.len(), 2);
+        assert!(lods[0].triangles().count() < mesh.triangles().count());
+        assert!(lods[1].triangles().count() < lods[0].triangles().count());
+
+        let lods = process_meshes([&mut mesh], &ImportOptions::default()).remove(0);
+        assert!(lods.is_empty());
+    }
+}
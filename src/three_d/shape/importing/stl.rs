@@ -0,0 +1,235 @@
+use std::{fs, io::{BufRead, BufReader}, path::Path};
+
+use super::*;
+
+/// One triangle as STL stores it, with its own copy of each corner
+struct Facet {
+    normal: Normal,
+    corners: [Vertex; 3],
+}
+
+/// Ascii files can hold several named solids, binary files are always one
+struct Solid {
+    name: Option<String>,
+    facets: Vec<Facet>,
+}
+
+/// Binary STLs have an 80 byte header, a triangle count, then 50 bytes per triangle
+const BINARY_HEADER_LEN: usize = 84;
+const BINARY_FACET_LEN: usize = 50;
+
+/// The UTF-8 byte order mark some editors put at the start of text files
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Skips what can come before an ascii file's `solid`, a byte order mark and then whitespace
+fn skip_preamble(data: &[u8]) -> &[u8] {
+    let data = data.strip_prefix(BOM).unwrap_or(data);
+    let start = data.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(data.len());
+    &data[start..]
+}
+
+/// Binary files are allowed to start with `solid` too, so the size is checked first since that's the only
+/// reliable way to tell them apart. Files whose size doesn't match are ascii if they start with `solid`
+fn is_binary(data: &[u8]) -> bool {
+    if data.len() >= BINARY_HEADER_LEN {
+        let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+        if data.len() == BINARY_HEADER_LEN + count * BINARY_FACET_LEN {
+            return true;
+        }
+    }
+    data.len() >= BINARY_HEADER_LEN && !skip_preamble(data).starts_with(b"solid")
+}
+
+fn read_binary(data: &[u8], name: &str) -> Result<Vec<Solid>, ImportError> {
+    let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+    let body = &data[BINARY_HEADER_LEN..];
+    if body.len() < count * BINARY_FACET_LEN {
+        return Err(invalid(name, format!("the file says it has {count} triangles, but only has room for {}", body.len() / BINARY_FACET_LEN)));
+    }
+
+    let facets = body.chunks_exact(BINARY_FACET_LEN).take(count).map(|facet| {
+        let float = |i: usize| f32::from_le_bytes([facet[i * 4], facet[i * 4 + 1], facet[i * 4 + 2], facet[i * 4 + 3]]);
+        let vec = |i: usize| [float(i), float(i + 1), float(i + 2)];
+        // The last two bytes are an attribute count that nothing agrees on the meaning of
+        Facet { normal: vec(0), corners: [vec(3), vec(6), vec(9)] }
+    }).collect();
+    Ok(vec![Solid { name: None, facets }])
+}
+
+fn read_ascii(text: &str, name: &str) -> Result<Vec<Solid>, ImportError> {
+    let mut solids = Vec::new();
+    let mut facet: Option<Facet> = None;
+    let mut corners = 0;
+    let vec = |tokens: &mut Tokens| -> Result<[f32; 3], ImportError> {
+        Ok([tokens.parse("an x coordinate")?, tokens.parse("a y coordinate")?, tokens.parse("a z coordinate")?])
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let mut tokens = Tokens::new(name, i + 1, line);
+        let Some(statement) = tokens.next() else { continue };
+
+        match statement {
+            "solid" => {
+                let solid_name = tokens.rest();
+                solids.push(Solid { name: if solid_name.is_empty() { None } else { Some(solid_name.to_string()) }, facets: Vec::new() });
+            },
+            "facet" => {
+                if tokens.expect("'normal'")? != "normal" {
+                    return Err(tokens.error("expected 'normal'".to_string()));
+                }
+                facet = Some(Facet { normal: vec(&mut tokens)?, corners: [[0.0; 3]; 3] });
+                corners = 0;
+            },
+            "vertex" => {
+                let f = facet.as_mut().ok_or_else(|| tokens.error("a vertex came before any facet".to_string()))?;
+                if corners == 3 {
+                    return Err(tokens.error("facets can only have 3 vertices".to_string()));
+                }
+                f.corners[corners] = vec(&mut tokens)?;
+                corners += 1;
+            },
+            "endfacet" => {
+                let f = facet.take().ok_or_else(|| tokens.error("'endfacet' without a facet".to_string()))?;
+                if corners != 3 {
+                    return Err(tokens.error(format!("a facet has {corners} vertices, it needs 3")));
+                }
+                let solid = solids.last_mut().ok_or_else(|| tokens.error("a facet came before any solid".to_string()))?;
+                solid.facets.push(f);
+            },
+            "outer" | "endloop" | "endsolid" => {},
+            _ => return Err(tokens.error(format!("unknown statement '{statement}'"))),
+        }
+    }
+    Ok(solids)
+}
+
+/// Reads an STL into a mesh, with a range for each solid
+/// Without welding every triangle gets its own corners, so it's flat shaded by its facet normal
+/// With welding corners at the same position are shared, and normals are smoothed across them
+pub fn read_stl<R: BufRead>(mut reader: R, name: &str, options: &ImportOptions) -> Result<Mesh, ImportError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).map_err(|err| ImportError::FileError(Box::new(err)))?;
+    let solids = if is_binary(&data) {
+        read_binary(&data, name)?
+    } else {
+        read_ascii(&String::from_utf8_lossy(data.strip_prefix(BOM).unwrap_or(&data)), name)?
+    };
+
+    let mut mesh = Mesh::default();
+    // Positions are welded by their exact bits, adding 0 first so that -0.0 and 0.0 end up the same
+    let mut welder = VertexWelder::new();
+    for solid in solids {
+        let start = mesh.indices.len();
+        for facet in solid.facets {
+            // Plenty of exporters leave the normal as zeroes, so we work it out from the winding instead
+            let normal = if facet.normal == [0.0; 3] { face_normal(&facet.corners) } else { facet.normal };
+            for corner in facet.corners {
+                let idx = if options.weld_vertices {
+                    let (idx, new) = welder.weld(corner.map(|x| (x + 0.0).to_bits()));
+                    if new {
+                        mesh.positions.push(corner);
+                    }
+                    idx
+                } else {
+                    mesh.positions.push(corner);
+                    mesh.normals.push(normal);
+                    mesh.positions.len() as u32 - 1
+                };
+                mesh.indices.push(idx);
+            }
+        }
+        if mesh.indices.len() > start {
+            mesh.ranges.push(DrawRange { primitive: Primitive::Triangles, start, count: mesh.indices.len() - start, group: solid.name });
+        }
+    }
+    if options.weld_vertices {
+        mesh.compute_normals();
+    }
+    Ok(mesh)
+}
+
+impl Shape {
+    pub fn from_stl(
+        path: &str,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,
+        options: &ImportOptions,) -> Result<Shape, ImportError>
+    {
+        let path = Path::new(path);
+        if path.extension() != Some("stl".as_ref()) { return Err(ImportError::IncorrectExtension); }
+        let file = fs::File::open(path).map_err(|err| ImportError::FileError(Box::new(err)))?;
+
+        Shape::from_stl_reader(BufReader::new(file), &path.to_string_lossy(), shader_type, transform, animation, options)
+    }
+
+    /// Like `from_stl`, but reads from anything buffered
+    /// `name` is only used in error messages
+    pub fn from_stl_reader<R: BufRead>(
+        reader: R,
+        name: &str,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,
+        options: &ImportOptions,) -> Result<Shape, ImportError>
+    {
+        let mut mesh = read_stl(reader, name, options)?;
+        let mut report = ImportReport::default();
+        check_meshes([&mut mesh], name, options, &mut report);
+        report.log();
+        let lods = process_meshes([&mut mesh], options).remove(0);
+        let mut shape = Shape::from_mesh(mesh, shader_type, transform, animation, Material::default());
+        shape.set_lods(lods);
+        Ok(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: &str = "solid tri\n  facet normal 0 0 1\n    outer loop\n      vertex 0 0 0\n      vertex 1 0 0\n      vertex 0 1 0\n    endloop\n  endfacet\nendsolid tri\n";
+
+    /// A binary STL of the same triangle, with `header` at the start of its header
+    fn binary_triangle(header: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 80];
+        data[..header.len()].copy_from_slice(header);
+        data.extend(1u32.to_le_bytes());
+        for x in [0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend(x.to_le_bytes());
+        }
+        data.extend([0, 0]);
+        data
+    }
+
+    fn read(data: &[u8]) -> Mesh {
+        read_stl(data, "test.stl", &ImportOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn ascii_and_binary_are_told_apart() {
+        for data in [TRIANGLE.to_string(), format!("\n  {TRIANGLE}"), format!("\u{FEFF}{TRIANGLE}")] {
+            assert!(!is_binary(data.as_bytes()));
+            let mesh = read(data.as_bytes());
+            assert_eq!(mesh.ranges[0].group.as_deref(), Some("tri"));
+            assert_eq!(mesh.triangles().count(), 1);
+        }
+        // Some exporters start binary headers with "solid" anyway
+        for header in [&b"binary"[..], b"solid but binary"] {
+            let data = binary_triangle(header);
+            assert!(is_binary(&data));
+            let mesh = read(&data);
+            assert_eq!(mesh.positions, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+            assert_eq!(mesh.normals, vec![[0.0, 0.0, 1.0]; 3]);
+        }
+    }
+
+    #[test]
+    fn welding_shares_corners() {
+        let square = TRIANGLE.replace("endsolid tri", "  facet normal 0 0 0\n    outer loop\n      vertex 1 0 0\n      vertex 1 1 0\n      vertex 0 1 0\n    endloop\n  endfacet\nendsolid tri");
+        assert_eq!(read(square.as_bytes()).positions.len(), 6);
+        let welded = read_stl(square.as_bytes(), "test.stl", &ImportOptions { weld_vertices: true, ..ImportOptions::default() }).unwrap();
+        assert_eq!(welded.positions.len(), 4);
+        assert!(welded.normals.iter().all(|n| (n[2] - 1.0).abs() < 1e-6));
+    }
+}
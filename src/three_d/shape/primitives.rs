@@ -0,0 +1,107 @@
+use super::*;
+
+// Shapes made straight from the generated meshes, see `Mesh` for what each one looks like
+
+impl Shape {
+    pub fn plane(
+        width: f32,
+        depth: f32,
+        x_segments: u32,
+        z_segments: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::plane(width, depth, x_segments, z_segments), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn cube(
+        size: f32,
+        segments: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::cube(size, segments), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn uv_sphere(
+        radius: f32,
+        segments: u32,
+        rings: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::uv_sphere(radius, segments, rings), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn icosphere(
+        radius: f32,
+        subdivisions: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::icosphere(radius, subdivisions), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn cylinder(
+        radius: f32,
+        height: f32,
+        segments: u32,
+        height_segments: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::cylinder(radius, height, segments, height_segments), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn cone(
+        radius: f32,
+        height: f32,
+        segments: u32,
+        height_segments: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::cone(radius, height, segments, height_segments), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        segments: u32,
+        sides: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::torus(major_radius, minor_radius, segments, sides), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn capsule(
+        radius: f32,
+        height: f32,
+        segments: u32,
+        rings: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::capsule(radius, height, segments, rings), shader_type, transform, animation, Material::default())
+    }
+
+    pub fn disk(
+        radius: f32,
+        segments: u32,
+        rings: u32,
+        shader_type: shaders::ShaderType,
+        transform: Option<Transform>,
+        animation: Option<Box<dyn Animation>>,) -> Shape
+    {
+        Shape::from_mesh(Mesh::disk(radius, segments, rings), shader_type, transform, animation, Material::default())
+    }
+}
@@ -536,538 +536,538 @@ pub const VERTICES: [Vertex; 531] = [
 ];
 
 pub const NORMALS: [Normal; 531] = [
-    Normal { normal: (0.0, 0.0, 0.0)},     // dummy vector because in the original model indices
+    [0.0, 0.0, 0.0],     // dummy vector because in the original model indices
                                             // start at 1
-    Normal { normal: (-0.966742, -0.255752, 0.0)},
-    Normal { normal: (-0.966824, 0.255443, 0.0)},
-    Normal { normal: (-0.092052, 0.995754, 0.0)},
-    Normal { normal: (0.68205, 0.731305, 0.0)},
-    Normal { normal: (0.870301, 0.492521, -0.0)},
-    Normal { normal: (-0.893014, -0.256345, -0.369882)},
-    Normal { normal: (-0.893437, 0.255997, -0.369102)},
-    Normal { normal: (-0.0838771, 0.995843, -0.0355068)},
-    Normal { normal: (0.629724, 0.73186, 0.260439)},
-    Normal { normal: (0.803725, 0.49337, 0.332584)},
-    Normal { normal: (-0.683407, -0.256729, -0.683407)},
-    Normal { normal: (-0.683531, 0.256067, -0.683531)},
-    Normal { normal: (-0.0649249, 0.995776, -0.0649248)},
-    Normal { normal: (0.481398, 0.732469, 0.481398)},
-    Normal { normal: (0.614804, 0.493997, 0.614804)},
-    Normal { normal: (-0.369882, -0.256345, -0.893014)},
-    Normal { normal: (-0.369102, 0.255997, -0.893437)},
-    Normal { normal: (-0.0355067, 0.995843, -0.0838772) },
-    Normal { normal: (0.260439, 0.73186, 0.629724) },
-    Normal { normal: (0.332584, 0.49337, 0.803725) },
-    Normal { normal: (-0.00284834, -0.257863, -0.966177) },
-    Normal { normal: (-0.00192311, 0.254736, -0.967009) },
-    Normal { normal: (-0.000266114, 0.995734, -0.0922702) },
-    Normal { normal: (0.0, 0.731295, 0.682061) },
-    Normal { normal: (0.0, 0.492521, 0.870301) },
-    Normal { normal: (0.379058, -0.3593, -0.852771) },
-    Normal { normal: (0.37711, 0.149086, -0.914091) },
-    Normal { normal: (0.0275022, 0.992081, -0.122551) },
-    Normal { normal: (-0.26101, 0.726762, 0.635367) },
-    Normal { normal: (-0.332485, 0.492546, 0.804271) },
-    Normal { normal: (0.663548, -0.410791, -0.625264) },
-    Normal { normal: (0.712664, 0.0737216, -0.697621) },
-    Normal { normal: (0.0997268, 0.987509, -0.121984) },
-    Normal { normal: (-0.48732, 0.723754, 0.488568) },
-    Normal { normal: (-0.615242, 0.492602, 0.615484) },
-    Normal { normal: (0.880028, -0.332908, -0.338709) },
-    Normal { normal: (0.917276, 0.167113, -0.361493) },
-    Normal { normal: (0.113584, 0.992365, -0.0480695) },
-    Normal { normal: (-0.63415, 0.727508, 0.261889) },
-    Normal { normal: (-0.804126, 0.492634, 0.332705) },
-    Normal { normal: (0.96669, -0.255738, 0.0104537) },
-    Normal { normal: (0.967442, 0.252962, 0.00810329) },
-    Normal { normal: (0.0934365, 0.995624, 0.00128063) },
-    Normal { normal: (-0.682167, 0.731196, -0.00034353) },
-    Normal { normal: (-0.870322, 0.492483, -0.0) },
-    Normal { normal: (0.893014, -0.256345, 0.369882) },
-    Normal { normal: (0.893437, 0.255997, 0.369102) },
-    Normal { normal: (0.0838768, 0.995843, 0.0355066) },
-    Normal { normal: (-0.629724, 0.73186, -0.260439) },
-    Normal { normal: (-0.803725, 0.49337, -0.332584) },
-    Normal { normal: (0.683407, -0.256729, 0.683407) },
-    Normal { normal: (0.683531, 0.256067, 0.683531) },
-    Normal { normal: (0.0649249, 0.995776, 0.0649249) },
-    Normal { normal: (-0.481398, 0.732469, -0.481398) },
-    Normal { normal: (-0.614804, 0.493997, -0.614804) },
-    Normal { normal: (0.369882, -0.256345, 0.893014) },
-    Normal { normal: (0.369102, 0.255997, 0.893437) },
-    Normal { normal: (0.0355067, 0.995843, 0.083877) },
-    Normal { normal: (-0.260439, 0.73186, -0.629724) },
-    Normal { normal: (-0.332584, 0.49337, -0.803725) },
-    Normal { normal: (0.0, -0.255752, 0.966742) },
-    Normal { normal: (0.0, 0.255443, 0.966824) },
-    Normal { normal: (0.0, 0.995754, 0.092052) },
-    Normal { normal: (0.0, 0.731305, -0.68205) },
-    Normal { normal: (-0.0, 0.492521, -0.870301) },
-    Normal { normal: (-0.369882, -0.256345, 0.893014) },
-    Normal { normal: (-0.369102, 0.255996, 0.893437) },
-    Normal { normal: (-0.0355068, 0.995843, 0.0838771) },
-    Normal { normal: (0.260439, 0.73186, -0.629724) },
-    Normal { normal: (0.332584, 0.49337, -0.803725) },
-    Normal { normal: (-0.683407, -0.256729, 0.683407) },
-    Normal { normal: (-0.683531, 0.256067, 0.683531) },
-    Normal { normal: (-0.0649249, 0.995776, 0.064925) },
-    Normal { normal: (0.481398, 0.732469, -0.481398) },
-    Normal { normal: (0.614804, 0.493997, -0.614804) },
-    Normal { normal: (-0.893014, -0.256345, 0.369882) },
-    Normal { normal: (-0.893437, 0.255997, 0.369102) },
-    Normal { normal: (-0.0838767, 0.995843, 0.0355066) },
-    Normal { normal: (0.629724, 0.73186, -0.260439) },
-    Normal { normal: (0.803725, 0.49337, -0.332584) },
-    Normal { normal: (0.915321, 0.402725, 0.0) },
-    Normal { normal: (0.941808, 0.336151, -0.0) },
-    Normal { normal: (0.97869, 0.205342, 0.0) },
-    Normal { normal: (0.997804, -0.0662397, 0.0) },
-    Normal { normal: (0.845438, 0.403546, 0.349835) },
-    Normal { normal: (0.869996, 0.336859, 0.360047) },
-    Normal { normal: (0.904193, 0.205791, 0.37428) },
-    Normal { normal: (0.921879, -0.0663697, 0.381752) },
-    Normal { normal: (0.646802, 0.404096, 0.646802) },
-    Normal { normal: (0.665655, 0.337351, 0.665655) },
-    Normal { normal: (0.691923, 0.20612, 0.691923) },
-    Normal { normal: (0.705542, -0.0664796, 0.705543) },
-    Normal { normal: (0.349835, 0.403546, 0.845438) },
-    Normal { normal: (0.360047, 0.336859, 0.869996) },
-    Normal { normal: (0.37428, 0.205791, 0.904193) },
-    Normal { normal: (0.381752, -0.0663697, 0.921879) },
-    Normal { normal: (-0.0, 0.402725, 0.915321) },
-    Normal { normal: (0.0, 0.336151, 0.941808) },
-    Normal { normal: (-0.0, 0.205342, 0.97869) },
-    Normal { normal: (-0.0, -0.0662397, 0.997804) },
-    Normal { normal: (-0.349835, 0.403546, 0.845438) },
-    Normal { normal: (-0.360047, 0.336859, 0.869996) },
-    Normal { normal: (-0.37428, 0.205791, 0.904193) },
-    Normal { normal: (-0.381752, -0.0663697, 0.921879) },
-    Normal { normal: (-0.646802, 0.404096, 0.646802) },
-    Normal { normal: (-0.665655, 0.337351, 0.665655) },
-    Normal { normal: (-0.691923, 0.20612, 0.691923) },
-    Normal { normal: (-0.705543, -0.0664796, 0.705543) },
-    Normal { normal: (-0.845438, 0.403546, 0.349835) },
-    Normal { normal: (-0.869996, 0.336859, 0.360047) },
-    Normal { normal: (-0.904193, 0.205791, 0.37428) },
-    Normal { normal: (-0.921879, -0.0663697, 0.381752) },
-    Normal { normal: (-0.915321, 0.402725, -0.0) },
-    Normal { normal: (-0.941808, 0.336151, -0.0) },
-    Normal { normal: (-0.97869, 0.205342, -0.0) },
-    Normal { normal: (-0.997804, -0.0662397, -0.0) },
-    Normal { normal: (-0.845438, 0.403546, -0.349835) },
-    Normal { normal: (-0.869996, 0.336859, -0.360047) },
-    Normal { normal: (-0.904193, 0.205791, -0.37428) },
-    Normal { normal: (-0.921879, -0.0663697, -0.381752) },
-    Normal { normal: (-0.646802, 0.404096, -0.646802) },
-    Normal { normal: (-0.665655, 0.337351, -0.665655) },
-    Normal { normal: (-0.691923, 0.20612, -0.691923) },
-    Normal { normal: (-0.705542, -0.0664796, -0.705543) },
-    Normal { normal: (-0.349835, 0.403546, -0.845438) },
-    Normal { normal: (-0.360047, 0.336859, -0.869996) },
-    Normal { normal: (-0.37428, 0.205791, -0.904193) },
-    Normal { normal: (-0.381752, -0.0663697, -0.921879) },
-    Normal { normal: (0.0, 0.402725, -0.915321) },
-    Normal { normal: (-0.0, 0.336151, -0.941808) },
-    Normal { normal: (0.0, 0.205342, -0.97869) },
-    Normal { normal: (0.0, -0.0662397, -0.997804) },
-    Normal { normal: (0.349835, 0.403546, -0.845438) },
-    Normal { normal: (0.360047, 0.336859, -0.869996],
-    Normal { normal: (0.37428, 0.205791, -0.904193],
-    Normal { normal: (0.381752, -0.0663697, -0.921879],
-    Normal { normal: (0.646802, 0.404096, -0.646802],
-    Normal { normal: (0.665655, 0.337351, -0.665655],
-    Normal { normal: (0.691923, 0.20612, -0.691923],
-    Normal { normal: (0.705543, -0.0664796, -0.705542],
-    Normal { normal: (0.845438, 0.403546, -0.349835],
-    Normal { normal: (0.869996, 0.336859, -0.360047],
-    Normal { normal: (0.904193, 0.205791, -0.37428],
-    Normal { normal: (0.921879, -0.0663697, -0.381752],
-    Normal { normal: (0.900182, -0.435513, -0.0],
-    Normal { normal: (0.729611, -0.683863, -0.0],
-    Normal { normal: (0.693951, -0.720022, -0.0],
-    Normal { normal: (0.79395, -0.607984, 0.0],
-    Normal { normal: (0.831437, -0.43618, 0.344179],
-    Normal { normal: (0.673512, -0.684665, 0.278594],
-    Normal { normal: (0.640399, -0.720924, 0.264874],
-    Normal { normal: (0.732949, -0.608996, 0.303166],
-    Normal { normal: (0.636092, -0.436777, 0.636092],
-    Normal { normal: (0.514965, -0.685289, 0.514965],
-    Normal { normal: (0.489651, -0.721446, 0.489651],
-    Normal { normal: (0.560555, -0.609554, 0.560555],
-    Normal { normal: (0.344179, -0.43618, 0.831437],
-    Normal { normal: (0.278594, -0.684665, 0.673512],
-    Normal { normal: (0.264874, -0.720924, 0.640399],
-    Normal { normal: (0.303166, -0.608996, 0.732949],
-    Normal { normal: (0.0, -0.435513, 0.900182],
-    Normal { normal: (-0.0, -0.683863, 0.729611],
-    Normal { normal: (0.0, -0.720022, 0.693951],
-    Normal { normal: (-0.0, -0.607984, 0.79395],
-    Normal { normal: (-0.344179, -0.43618, 0.831437],
-    Normal { normal: (-0.278594, -0.684665, 0.673512],
-    Normal { normal: (-0.264874, -0.720924, 0.640399],
-    Normal { normal: (-0.303166, -0.608996, 0.732949],
-    Normal { normal: (-0.636092, -0.436777, 0.636092],
-    Normal { normal: (-0.514965, -0.685289, 0.514965],
-    Normal { normal: (-0.489651, -0.721446, 0.489651],
-    Normal { normal: (-0.560555, -0.609554, 0.560555],
-    Normal { normal: (-0.831437, -0.43618, 0.344179],
-    Normal { normal: (-0.673512, -0.684665, 0.278595],
-    Normal { normal: (-0.640399, -0.720924, 0.264874],
-    Normal { normal: (-0.732949, -0.608996, 0.303166],
-    Normal { normal: (-0.900182, -0.435513, -0.0],
-    Normal { normal: (-0.729611, -0.683863, -0.0],
-    Normal { normal: (-0.693951, -0.720022, 0.0],
-    Normal { normal: (-0.79395, -0.607983, -0.0],
-    Normal { normal: (-0.831437, -0.43618, -0.344179],
-    Normal { normal: (-0.673512, -0.684665, -0.278594],
-    Normal { normal: (-0.640399, -0.720924, -0.264874],
-    Normal { normal: (-0.732949, -0.608996, -0.303166],
-    Normal { normal: (-0.636092, -0.436777, -0.636092],
-    Normal { normal: (-0.514965, -0.685289, -0.514965],
-    Normal { normal: (-0.489651, -0.721446, -0.489651],
-    Normal { normal: (-0.560555, -0.609554, -0.560555],
-    Normal { normal: (-0.344179, -0.43618, -0.831437],
-    Normal { normal: (-0.278594, -0.684665, -0.673512],
-    Normal { normal: (-0.264874, -0.720924, -0.640399],
-    Normal { normal: (-0.303166, -0.608996, -0.732949],
-    Normal { normal: (-0.0, -0.435513, -0.900182],
-    Normal { normal: (0.0, -0.683863, -0.729611],
-    Normal { normal: (-0.0, -0.720022, -0.693951],
-    Normal { normal: (0.0, -0.607984, -0.79395],
-    Normal { normal: (0.344179, -0.43618, -0.831437],
-    Normal { normal: (0.278594, -0.684665, -0.673512],
-    Normal { normal: (0.264874, -0.720924, -0.640399],
-    Normal { normal: (0.303167, -0.608996, -0.732949],
-    Normal { normal: (0.636092, -0.436777, -0.636092],
-    Normal { normal: (0.514965, -0.685289, -0.514965],
-    Normal { normal: (0.489651, -0.721446, -0.489651],
-    Normal { normal: (0.560555, -0.609554, -0.560555],
-    Normal { normal: (0.831437, -0.43618, -0.344179],
-    Normal { normal: (0.673512, -0.684665, -0.278595],
-    Normal { normal: (0.640399, -0.720924, -0.264874],
-    Normal { normal: (0.732949, -0.608996, -0.303166],
-    Normal { normal: (0.62386, -0.781536, 0.0],
-    Normal { normal: (0.177291, -0.984159, -0.0],
-    Normal { normal: (0.0492072, -0.998789, 0.0],
-    Normal { normal: (0.0, -1.0, -0.0],
-    Normal { normal: (0.576229, -0.781801, 0.238217],
-    Normal { normal: (0.163629, -0.984208, 0.0675273],
-    Normal { normal: (0.0454217, -0.998792, 0.0187357],
-    Normal { normal: (0.440416, -0.782348, 0.440416],
-    Normal { normal: (0.124903, -0.984276, 0.124903],
-    Normal { normal: (0.0346621, -0.998798, 0.0346621],
-    Normal { normal: (0.238217, -0.781801, 0.576229],
-    Normal { normal: (0.0675273, -0.984208, 0.163629],
-    Normal { normal: (0.0187357, -0.998792, 0.0454217],
-    Normal { normal: (-0.0, -0.781536, 0.62386],
-    Normal { normal: (0.0, -0.984159, 0.177291],
-    Normal { normal: (-0.0, -0.998789, 0.0492072],
-    Normal { normal: (-0.238216, -0.781801, 0.576229],
-    Normal { normal: (-0.0675273, -0.984208, 0.163629],
-    Normal { normal: (-0.0187357, -0.998792, 0.0454217],
-    Normal { normal: (-0.440416, -0.782348, 0.440416],
-    Normal { normal: (-0.124903, -0.984276, 0.124903],
-    Normal { normal: (-0.0346621, -0.998798, 0.0346621],
-    Normal { normal: (-0.576229, -0.781801, 0.238217],
-    Normal { normal: (-0.163629, -0.984208, 0.0675273],
-    Normal { normal: (-0.0454217, -0.998792, 0.0187357],
-    Normal { normal: (-0.62386, -0.781536, -0.0],
-    Normal { normal: (-0.177291, -0.984159, 0.0],
-    Normal { normal: (-0.0492072, -0.998789, -0.0],
-    Normal { normal: (-0.576229, -0.781801, -0.238217],
-    Normal { normal: (-0.163629, -0.984208, -0.0675273],
-    Normal { normal: (-0.0454217, -0.998792, -0.0187357],
-    Normal { normal: (-0.440416, -0.782348, -0.440416],
-    Normal { normal: (-0.124903, -0.984276, -0.124903],
-    Normal { normal: (-0.0346621, -0.998798, -0.0346621],
-    Normal { normal: (-0.238217, -0.781801, -0.576229],
-    Normal { normal: (-0.0675273, -0.984208, -0.163629],
-    Normal { normal: (-0.0187357, -0.998792, -0.0454217],
-    Normal { normal: (0.0, -0.781536, -0.62386],
-    Normal { normal: (-0.0, -0.984159, -0.177291],
-    Normal { normal: (0.0, -0.998789, -0.0492072],
-    Normal { normal: (0.238217, -0.781801, -0.576229],
-    Normal { normal: (0.0675273, -0.984208, -0.163629],
-    Normal { normal: (0.0187357, -0.998792, -0.0454217],
-    Normal { normal: (0.440416, -0.782348, -0.440416],
-    Normal { normal: (0.124903, -0.984276, -0.124903],
-    Normal { normal: (0.0346621, -0.998798, -0.0346621],
-    Normal { normal: (0.576229, -0.781801, -0.238217],
-    Normal { normal: (0.163629, -0.984208, -0.0675273],
-    Normal { normal: (0.0454217, -0.998792, -0.0187357],
-    Normal { normal: (0.00778619, -0.99997, -0.000215809],
-    Normal { normal: (0.0391385, -0.999233, -0.000988567],
-    Normal { normal: (0.179511, -0.983746, -0.00436856],
-    Normal { normal: (0.6123, -0.790556, -0.0104598],
-    Normal { normal: (0.986152, -0.165707, -0.00666949],
-    Normal { normal: (0.00703893, -0.812495, 0.582926],
-    Normal { normal: (0.0361273, -0.837257, 0.545614],
-    Normal { normal: (0.161845, -0.810421, 0.563048],
-    Normal { normal: (0.482365, -0.595148, 0.642746],
-    Normal { normal: (0.73872, -0.114593, 0.664199],
-    Normal { normal: (-0.00190867, 0.162121, 0.986769],
-    Normal { normal: (0.0027616, 0.0171073, 0.99985],
-    Normal { normal: (0.0105326, 0.0733989, 0.997247],
-    Normal { normal: (-0.0660406, 0.130069, 0.989303],
-    Normal { normal: (-0.0944272, 0.0165946, 0.995393],
-    Normal { normal: (-0.009203, 0.871509, 0.490293],
-    Normal { normal: (-0.0486064, 0.840609, 0.539457],
-    Normal { normal: (-0.223298, 0.802881, 0.552739],
-    Normal { normal: (-0.596365, 0.559971, 0.575135],
-    Normal { normal: (-0.803337, 0.0682361, 0.591602],
-    Normal { normal: (-0.0105609, 0.999944, 0.000103364],
-    Normal { normal: (-0.0587986, 0.99827, 0.000709759],
-    Normal { normal: (-0.28071, 0.959787, 0.00326876],
-    Normal { normal: (-0.749723, 0.661738, 0.0042684],
-    Normal { normal: (-0.997351, 0.0727144, 0.00205923],
-    Normal { normal: (-0.00879197, 0.871493, -0.49033],
-    Normal { normal: (-0.0464937, 0.841178, -0.538756],
-    Normal { normal: (-0.217909, 0.806807, -0.549161],
-    Normal { normal: (-0.597291, 0.560026, -0.574121],
-    Normal { normal: (-0.804, 0.0629127, -0.591291],
-    Normal { normal: (-0.00180555, 0.161691, -0.98684],
-    Normal { normal: (0.00203087, 0.014555, -0.999892],
-    Normal { normal: (0.00921499, 0.0600698, -0.998152],
-    Normal { normal: (-0.0593333, 0.113865, -0.991723],
-    Normal { normal: (-0.0868992, 0.0122903, -0.996141],
-    Normal { normal: (0.00641779, -0.812379, -0.583094],
-    Normal { normal: (0.0337833, -0.837512, -0.545373],
-    Normal { normal: (0.157112, -0.811947, -0.56219],
-    Normal { normal: (0.484407, -0.589365, -0.646528],
-    Normal { normal: (0.73887, -0.10132, -0.666187],
-    Normal { normal: (0.946512, 0.32265, -0.0033571],
-    Normal { normal: (0.82583, 0.56387, -0.00745213],
-    Normal { normal: (0.650011, 0.759893, -0.00693681],
-    Normal { normal: (0.532429, 0.846458, -0.00524544],
-    Normal { normal: (0.725608, 0.259351, 0.637362],
-    Normal { normal: (0.645945, 0.461988, 0.607719],
-    Normal { normal: (0.531614, 0.63666, 0.558615],
-    Normal { normal: (0.424964, 0.681717, 0.59554],
-    Normal { normal: (-0.0495616, -0.019755, 0.998576],
-    Normal { normal: (-0.0378162, -0.0356243, 0.99865],
-    Normal { normal: (-0.0379139, -0.0365122, 0.998614],
-    Normal { normal: (-0.168854, -0.297946, 0.93953],
-    Normal { normal: (-0.742342, -0.299166, 0.599523],
-    Normal { normal: (-0.619602, -0.529406, 0.579503],
-    Normal { normal: (-0.483708, -0.685761, 0.543837],
-    Normal { normal: (-0.445293, -0.794355, 0.413176],
-    Normal { normal: (-0.926513, -0.376257, 0.00199587],
-    Normal { normal: (-0.75392, -0.656952, 0.00431723],
-    Normal { normal: (-0.566224, -0.824244, 0.00346105],
-    Normal { normal: (-0.481804, -0.876277, 0.00185047],
-    Normal { normal: (-0.744675, -0.294424, -0.598977],
-    Normal { normal: (-0.621949, -0.528114, -0.578165],
-    Normal { normal: (-0.481171, -0.68834, -0.542828],
-    Normal { normal: (-0.438055, -0.797035, -0.415744],
-    Normal { normal: (-0.0443368, -0.0170558, -0.998871],
-    Normal { normal: (-0.0261761, -0.0281665, -0.99926],
-    Normal { normal: (-0.0252939, -0.0283323, -0.999278],
-    Normal { normal: (-0.157482, -0.289392, -0.944167],
-    Normal { normal: (0.728244, 0.25241, -0.637142],
-    Normal { normal: (0.647055, 0.459725, -0.608254],
-    Normal { normal: (0.522994, 0.640657, -0.562171],
-    Normal { normal: (0.409978, 0.682857, -0.604669],
-    Normal { normal: (-0.230787, 0.972982, -0.00652338],
-    Normal { normal: (-0.548936, 0.835863, -0.00151111],
-    Normal { normal: (-0.875671, 0.482807, 0.00989278],
-    Normal { normal: (-0.877554, 0.479097, 0.0190923],
-    Normal { normal: (-0.69619, 0.717439, 0.024497],
-    Normal { normal: (-0.152878, 0.687211, 0.71019],
-    Normal { normal: (-0.316721, 0.63775, 0.702113],
-    Normal { normal: (-0.601067, 0.471452, 0.64533],
-    Normal { normal: (-0.635889, 0.44609, 0.6298],
-    Normal { normal: (-0.435746, 0.601008, 0.670011],
-    Normal { normal: (0.111112, -0.0850694, 0.99016],
-    Normal { normal: (0.22331, 0.00654036, 0.974726],
-    Normal { normal: (0.190097, 0.154964, 0.969458],
-    Normal { normal: (0.00527077, 0.189482, 0.98187],
-    Normal { normal: (-0.0117518, 0.246688, 0.969024],
-    Normal { normal: (0.343906, -0.722796, 0.599412],
-    Normal { normal: (0.572489, -0.567656, 0.591627],
-    Normal { normal: (0.787436, -0.256459, 0.560512],
-    Normal { normal: (0.647097, -0.306374, 0.698141],
-    Normal { normal: (0.427528, -0.499343, 0.753576],
-    Normal { normal: (0.410926, -0.911668, 0.00128446],
-    Normal { normal: (0.67152, -0.740986, -0.000899122],
-    Normal { normal: (0.922026, -0.38706, -0.00725269],
-    Normal { normal: (0.84691, -0.531556, -0.0138542],
-    Normal { normal: (0.535925, -0.8442, -0.0105045],
-    Normal { normal: (0.341188, -0.722822, -0.600931],
-    Normal { normal: (0.578664, -0.561139, -0.591838],
-    Normal { normal: (0.784869, -0.25102, -0.566542],
-    Normal { normal: (0.642681, -0.302257, -0.70399],
-    Normal { normal: (0.418589, -0.500042, -0.758117],
-    Normal { normal: (0.115806, -0.0791394, -0.990114],
-    Normal { normal: (0.232811, 0.0125652, -0.972441],
-    Normal { normal: (0.206662, 0.153601, -0.96628],
-    Normal { normal: (0.0244996, 0.161443, -0.986578],
-    Normal { normal: (0.00338193, 0.211115, -0.977455],
-    Normal { normal: (-0.134912, 0.687491, -0.713551],
-    Normal { normal: (-0.31954, 0.633073, -0.705062],
-    Normal { normal: (-0.603902, 0.461442, -0.649903],
-    Normal { normal: (-0.631816, 0.437169, -0.640072],
-    Normal { normal: (-0.424306, 0.612706, -0.66675],
-    Normal { normal: (-0.4258, 0.904753, 0.0108049],
-    Normal { normal: (0.0220472, 0.999756, 0.00162273],
-    Normal { normal: (0.999599, 0.0258705, 0.0115556],
-    Normal { normal: (0.709585, -0.704553, 0.00967183],
-    Normal { normal: (-0.259858, 0.791936, 0.552549],
-    Normal { normal: (0.00953916, 0.99972, -0.0216718],
-    Normal { normal: (0.410156, 0.332912, -0.849083],
-    Normal { normal: (0.541523, -0.54862, -0.637],
-    Normal { normal: (0.0463104, 0.455224, 0.889172],
-    Normal { normal: (-0.0106883, 0.988794, 0.148901],
-    Normal { normal: (-0.0443756, 0.682947, -0.729118],
-    Normal { normal: (0.122825, 0.00923214, -0.992385],
-    Normal { normal: (0.481839, -0.180439, 0.85748],
-    Normal { normal: (0.455272, 0.736752, 0.499925],
-    Normal { normal: (-0.220542, 0.907193, -0.358276],
-    Normal { normal: (-0.23592, 0.657249, -0.715797],
-    Normal { normal: (0.728092, -0.685302, -0.0155853],
-    Normal { normal: (0.888739, 0.45811, -0.0166791],
-    Normal { normal: (-0.260097, 0.965582, 0.000800195],
-    Normal { normal: (-0.371612, 0.928378, -0.00441745],
-    Normal { normal: (0.480166, -0.17836, -0.858853],
-    Normal { normal: (0.488103, 0.716801, -0.497947],
-    Normal { normal: (-0.222004, 0.905399, 0.361893],
-    Normal { normal: (-0.235405, 0.66318, 0.710477],
-    Normal { normal: (0.0587203, 0.437704, -0.8972],
-    Normal { normal: (0.00132612, 0.986459, -0.164003],
-    Normal { normal: (-0.0441901, 0.681677, 0.730317],
-    Normal { normal: (0.138801, -0.0341896, 0.98973],
-    Normal { normal: (-0.25889, 0.797206, -0.54538],
-    Normal { normal: (0.0122703, 0.999739, 0.0192865],
-    Normal { normal: (0.39863, 0.35489, 0.845663],
-    Normal { normal: (0.537564, -0.5814, 0.610737],
-    Normal { normal: (-0.0, 1.0, 0.0],
-    Normal { normal: (0.82454, 0.565804, 0.0],
-    Normal { normal: (0.917701, -0.397272, 0.0],
-    Normal { normal: (0.935269, -0.353939, 0.000112842],
-    Normal { normal: (0.780712, 0.624891, 0.0],
-    Normal { normal: (0.762641, 0.565035, 0.314825],
-    Normal { normal: (0.847982, -0.397998, 0.350034],
-    Normal { normal: (0.864141, -0.355261, 0.356441],
-    Normal { normal: (0.720991, 0.625625, 0.297933],
-    Normal { normal: (0.583357, 0.565165, 0.583338],
-    Normal { normal: (0.648485, -0.398726, 0.648448],
-    Normal { normal: (0.660872, -0.355894, 0.660748],
-    Normal { normal: (0.551862, 0.62529, 0.55178],
-    Normal { normal: (0.314824, 0.565051, 0.762629],
-    Normal { normal: (0.350045, -0.397976, 0.847988],
-    Normal { normal: (0.356474, -0.3552, 0.864153],
-    Normal { normal: (0.297983, 0.625515, 0.721067],
-    Normal { normal: (-0.0, 0.565804, 0.82454],
-    Normal { normal: (-0.0, -0.397272, 0.917701],
-    Normal { normal: (-0.000112839, -0.353939, 0.935269],
-    Normal { normal: (-0.0, 0.624891, 0.780712],
-    Normal { normal: (-0.314825, 0.565035, 0.762641],
-    Normal { normal: (-0.350034, -0.397998, 0.847982],
-    Normal { normal: (-0.356441, -0.355261, 0.864141],
-    Normal { normal: (-0.297933, 0.625625, 0.720991],
-    Normal { normal: (-0.583338, 0.565165, 0.583357],
-    Normal { normal: (-0.648448, -0.398726, 0.648485],
-    Normal { normal: (-0.660748, -0.355894, 0.660872],
-    Normal { normal: (-0.55178, 0.62529, 0.551862],
-    Normal { normal: (-0.762629, 0.565051, 0.314824],
-    Normal { normal: (-0.847988, -0.397976, 0.350045],
-    Normal { normal: (-0.864153, -0.3552, 0.356474],
-    Normal { normal: (-0.721067, 0.625515, 0.297983],
-    Normal { normal: (-0.82454, 0.565804, -0.0],
-    Normal { normal: (-0.917701, -0.397272, -0.0],
-    Normal { normal: (-0.935269, -0.353939, -0.000112839],
-    Normal { normal: (-0.780712, 0.624891, -0.0],
-    Normal { normal: (-0.76264, 0.565035, -0.314825],
-    Normal { normal: (-0.847982, -0.397998, -0.350034],
-    Normal { normal: (-0.864141, -0.355261, -0.356441],
-    Normal { normal: (-0.720991, 0.625625, -0.297933],
-    Normal { normal: (-0.583357, 0.565165, -0.583338],
-    Normal { normal: (-0.648485, -0.398726, -0.648448],
-    Normal { normal: (-0.660872, -0.355894, -0.660748],
-    Normal { normal: (-0.551862, 0.62529, -0.55178],
-    Normal { normal: (-0.314824, 0.565051, -0.762629],
-    Normal { normal: (-0.350045, -0.397976, -0.847988],
-    Normal { normal: (-0.356474, -0.3552, -0.864153],
-    Normal { normal: (-0.297983, 0.625515, -0.721067],
-    Normal { normal: (0.0, 0.565804, -0.82454],
-    Normal { normal: (0.0, -0.397272, -0.917701],
-    Normal { normal: (0.000112839, -0.353939, -0.935269],
-    Normal { normal: (0.0, 0.624891, -0.780712],
-    Normal { normal: (0.314825, 0.565035, -0.762641],
-    Normal { normal: (0.350034, -0.397998, -0.847982],
-    Normal { normal: (0.356441, -0.355261, -0.864141],
-    Normal { normal: (0.297933, 0.625625, -0.720991],
-    Normal { normal: (0.583338, 0.565165, -0.583357],
-    Normal { normal: (0.648448, -0.398726, -0.648485],
-    Normal { normal: (0.660748, -0.355894, -0.660872],
-    Normal { normal: (0.55178, 0.62529, -0.551862],
-    Normal { normal: (0.762629, 0.565051, -0.314824],
-    Normal { normal: (0.847988, -0.397976, -0.350045],
-    Normal { normal: (0.864153, -0.3552, -0.356474],
-    Normal { normal: (0.721067, 0.625515, -0.297983],
-    Normal { normal: (0.236584, 0.971611, 0.0],
-    Normal { normal: (0.173084, 0.984907, -0.0],
-    Normal { normal: (0.379703, 0.925108, 0.0],
-    Normal { normal: (0.526673, 0.850068, 0.0],
-    Normal { normal: (0.217978, 0.971775, 0.0902162],
-    Normal { normal: (0.15959, 0.984977, 0.0659615],
-    Normal { normal: (0.350498, 0.925312, 0.14474],
-    Normal { normal: (0.48559, 0.850653, 0.201474],
-    Normal { normal: (0.166631, 0.971838, 0.166631],
-    Normal { normal: (0.121908, 0.985026, 0.121908],
-    Normal { normal: (0.267668, 0.925585, 0.267668],
-    Normal { normal: (0.371315, 0.851029, 0.371315],
-    Normal { normal: (0.0902162, 0.971775, 0.217978],
-    Normal { normal: (0.0659615, 0.984977, 0.15959],
-    Normal { normal: (0.14474, 0.925312, 0.350498],
-    Normal { normal: (0.201474, 0.850653, 0.48559],
-    Normal { normal: (-0.0, 0.971611, 0.236584],
-    Normal { normal: (0.0, 0.984907, 0.173084],
-    Normal { normal: (0.0, 0.925108, 0.379703],
-    Normal { normal: (0.0, 0.850068, 0.526673],
-    Normal { normal: (-0.0902162, 0.971775, 0.217978],
-    Normal { normal: (-0.0659615, 0.984977, 0.15959],
-    Normal { normal: (-0.14474, 0.925312, 0.350498],
-    Normal { normal: (-0.201474, 0.850653, 0.48559],
-    Normal { normal: (-0.166631, 0.971838, 0.166631],
-    Normal { normal: (-0.121908, 0.985026, 0.121908],
-    Normal { normal: (-0.267668, 0.925585, 0.267668],
-    Normal { normal: (-0.371315, 0.851029, 0.371315],
-    Normal { normal: (-0.217978, 0.971775, 0.0902162],
-    Normal { normal: (-0.15959, 0.984977, 0.0659615],
-    Normal { normal: (-0.350498, 0.925312, 0.14474],
-    Normal { normal: (-0.48559, 0.850653, 0.201474],
-    Normal { normal: (-0.236583, 0.971611, -0.0],
-    Normal { normal: (-0.173084, 0.984907, 0.0],
-    Normal { normal: (-0.379703, 0.925108, -0.0],
-    Normal { normal: (-0.526673, 0.850068, 0.0],
-    Normal { normal: (-0.217978, 0.971775, -0.0902162],
-    Normal { normal: (-0.15959, 0.984977, -0.0659615],
-    Normal { normal: (-0.350498, 0.925312, -0.14474],
-    Normal { normal: (-0.48559, 0.850653, -0.201474],
-    Normal { normal: (-0.166631, 0.971838, -0.166631],
-    Normal { normal: (-0.121908, 0.985026, -0.121908],
-    Normal { normal: (-0.267668, 0.925585, -0.267668],
-    Normal { normal: (-0.371315, 0.851029, -0.371315],
-    Normal { normal: (-0.0902162, 0.971775, -0.217978],
-    Normal { normal: (-0.0659615, 0.984977, -0.15959],
-    Normal { normal: (-0.14474, 0.925312, -0.350498],
-    Normal { normal: (-0.201474, 0.850653, -0.485589],
-    Normal { normal: (0.0, 0.971611, -0.236584],
-    Normal { normal: (-0.0, 0.984907, -0.173084],
-    Normal { normal: (-0.0, 0.925108, -0.379703],
-    Normal { normal: (-0.0, 0.850068, -0.526673],
-    Normal { normal: (0.0902162, 0.971775, -0.217978],
-    Normal { normal: (0.0659615, 0.984977, -0.15959],
-    Normal { normal: (0.14474, 0.925312, -0.350498],
-    Normal { normal: (0.201474, 0.850653, -0.48559],
-    Normal { normal: (0.166631, 0.971838, -0.166631],
-    Normal { normal: (0.121908, 0.985026, -0.121908],
-    Normal { normal: (0.267668, 0.925585, -0.267668],
-    Normal { normal: (0.371315, 0.851029, -0.371315],
-    Normal { normal: (0.217978, 0.971775, -0.0902162],
-    Normal { normal: (0.15959, 0.984977, -0.0659615],
-    Normal { normal: (0.350498, 0.925312, -0.14474],
-    Normal { normal: (0.48559, 0.850653, -0.201474],
+    [-0.966742, -0.255752, 0.0],
+    [-0.966824, 0.255443, 0.0],
+    [-0.092052, 0.995754, 0.0],
+    [0.68205, 0.731305, 0.0],
+    [0.870301, 0.492521, -0.0],
+    [-0.893014, -0.256345, -0.369882],
+    [-0.893437, 0.255997, -0.369102],
+    [-0.0838771, 0.995843, -0.0355068],
+    [0.629724, 0.73186, 0.260439],
+    [0.803725, 0.49337, 0.332584],
+    [-0.683407, -0.256729, -0.683407],
+    [-0.683531, 0.256067, -0.683531],
+    [-0.0649249, 0.995776, -0.0649248],
+    [0.481398, 0.732469, 0.481398],
+    [0.614804, 0.493997, 0.614804],
+    [-0.369882, -0.256345, -0.893014],
+    [-0.369102, 0.255997, -0.893437],
+    [-0.0355067, 0.995843, -0.0838772],
+    [0.260439, 0.73186, 0.629724],
+    [0.332584, 0.49337, 0.803725],
+    [-0.00284834, -0.257863, -0.966177],
+    [-0.00192311, 0.254736, -0.967009],
+    [-0.000266114, 0.995734, -0.0922702],
+    [0.0, 0.731295, 0.682061],
+    [0.0, 0.492521, 0.870301],
+    [0.379058, -0.3593, -0.852771],
+    [0.37711, 0.149086, -0.914091],
+    [0.0275022, 0.992081, -0.122551],
+    [-0.26101, 0.726762, 0.635367],
+    [-0.332485, 0.492546, 0.804271],
+    [0.663548, -0.410791, -0.625264],
+    [0.712664, 0.0737216, -0.697621],
+    [0.0997268, 0.987509, -0.121984],
+    [-0.48732, 0.723754, 0.488568],
+    [-0.615242, 0.492602, 0.615484],
+    [0.880028, -0.332908, -0.338709],
+    [0.917276, 0.167113, -0.361493],
+    [0.113584, 0.992365, -0.0480695],
+    [-0.63415, 0.727508, 0.261889],
+    [-0.804126, 0.492634, 0.332705],
+    [0.96669, -0.255738, 0.0104537],
+    [0.967442, 0.252962, 0.00810329],
+    [0.0934365, 0.995624, 0.00128063],
+    [-0.682167, 0.731196, -0.00034353],
+    [-0.870322, 0.492483, -0.0],
+    [0.893014, -0.256345, 0.369882],
+    [0.893437, 0.255997, 0.369102],
+    [0.0838768, 0.995843, 0.0355066],
+    [-0.629724, 0.73186, -0.260439],
+    [-0.803725, 0.49337, -0.332584],
+    [0.683407, -0.256729, 0.683407],
+    [0.683531, 0.256067, 0.683531],
+    [0.0649249, 0.995776, 0.0649249],
+    [-0.481398, 0.732469, -0.481398],
+    [-0.614804, 0.493997, -0.614804],
+    [0.369882, -0.256345, 0.893014],
+    [0.369102, 0.255997, 0.893437],
+    [0.0355067, 0.995843, 0.083877],
+    [-0.260439, 0.73186, -0.629724],
+    [-0.332584, 0.49337, -0.803725],
+    [0.0, -0.255752, 0.966742],
+    [0.0, 0.255443, 0.966824],
+    [0.0, 0.995754, 0.092052],
+    [0.0, 0.731305, -0.68205],
+    [-0.0, 0.492521, -0.870301],
+    [-0.369882, -0.256345, 0.893014],
+    [-0.369102, 0.255996, 0.893437],
+    [-0.0355068, 0.995843, 0.0838771],
+    [0.260439, 0.73186, -0.629724],
+    [0.332584, 0.49337, -0.803725],
+    [-0.683407, -0.256729, 0.683407],
+    [-0.683531, 0.256067, 0.683531],
+    [-0.0649249, 0.995776, 0.064925],
+    [0.481398, 0.732469, -0.481398],
+    [0.614804, 0.493997, -0.614804],
+    [-0.893014, -0.256345, 0.369882],
+    [-0.893437, 0.255997, 0.369102],
+    [-0.0838767, 0.995843, 0.0355066],
+    [0.629724, 0.73186, -0.260439],
+    [0.803725, 0.49337, -0.332584],
+    [0.915321, 0.402725, 0.0],
+    [0.941808, 0.336151, -0.0],
+    [0.97869, 0.205342, 0.0],
+    [0.997804, -0.0662397, 0.0],
+    [0.845438, 0.403546, 0.349835],
+    [0.869996, 0.336859, 0.360047],
+    [0.904193, 0.205791, 0.37428],
+    [0.921879, -0.0663697, 0.381752],
+    [0.646802, 0.404096, 0.646802],
+    [0.665655, 0.337351, 0.665655],
+    [0.691923, 0.20612, 0.691923],
+    [0.705542, -0.0664796, 0.705543],
+    [0.349835, 0.403546, 0.845438],
+    [0.360047, 0.336859, 0.869996],
+    [0.37428, 0.205791, 0.904193],
+    [0.381752, -0.0663697, 0.921879],
+    [-0.0, 0.402725, 0.915321],
+    [0.0, 0.336151, 0.941808],
+    [-0.0, 0.205342, 0.97869],
+    [-0.0, -0.0662397, 0.997804],
+    [-0.349835, 0.403546, 0.845438],
+    [-0.360047, 0.336859, 0.869996],
+    [-0.37428, 0.205791, 0.904193],
+    [-0.381752, -0.0663697, 0.921879],
+    [-0.646802, 0.404096, 0.646802],
+    [-0.665655, 0.337351, 0.665655],
+    [-0.691923, 0.20612, 0.691923],
+    [-0.705543, -0.0664796, 0.705543],
+    [-0.845438, 0.403546, 0.349835],
+    [-0.869996, 0.336859, 0.360047],
+    [-0.904193, 0.205791, 0.37428],
+    [-0.921879, -0.0663697, 0.381752],
+    [-0.915321, 0.402725, -0.0],
+    [-0.941808, 0.336151, -0.0],
+    [-0.97869, 0.205342, -0.0],
+    [-0.997804, -0.0662397, -0.0],
+    [-0.845438, 0.403546, -0.349835],
+    [-0.869996, 0.336859, -0.360047],
+    [-0.904193, 0.205791, -0.37428],
+    [-0.921879, -0.0663697, -0.381752],
+    [-0.646802, 0.404096, -0.646802],
+    [-0.665655, 0.337351, -0.665655],
+    [-0.691923, 0.20612, -0.691923],
+    [-0.705542, -0.0664796, -0.705543],
+    [-0.349835, 0.403546, -0.845438],
+    [-0.360047, 0.336859, -0.869996],
+    [-0.37428, 0.205791, -0.904193],
+    [-0.381752, -0.0663697, -0.921879],
+    [0.0, 0.402725, -0.915321],
+    [-0.0, 0.336151, -0.941808],
+    [0.0, 0.205342, -0.97869],
+    [0.0, -0.0662397, -0.997804],
+    [0.349835, 0.403546, -0.845438],
+    [0.360047, 0.336859, -0.869996],
+    [0.37428, 0.205791, -0.904193],
+    [0.381752, -0.0663697, -0.921879],
+    [0.646802, 0.404096, -0.646802],
+    [0.665655, 0.337351, -0.665655],
+    [0.691923, 0.20612, -0.691923],
+    [0.705543, -0.0664796, -0.705542],
+    [0.845438, 0.403546, -0.349835],
+    [0.869996, 0.336859, -0.360047],
+    [0.904193, 0.205791, -0.37428],
+    [0.921879, -0.0663697, -0.381752],
+    [0.900182, -0.435513, -0.0],
+    [0.729611, -0.683863, -0.0],
+    [0.693951, -0.720022, -0.0],
+    [0.79395, -0.607984, 0.0],
+    [0.831437, -0.43618, 0.344179],
+    [0.673512, -0.684665, 0.278594],
+    [0.640399, -0.720924, 0.264874],
+    [0.732949, -0.608996, 0.303166],
+    [0.636092, -0.436777, 0.636092],
+    [0.514965, -0.685289, 0.514965],
+    [0.489651, -0.721446, 0.489651],
+    [0.560555, -0.609554, 0.560555],
+    [0.344179, -0.43618, 0.831437],
+    [0.278594, -0.684665, 0.673512],
+    [0.264874, -0.720924, 0.640399],
+    [0.303166, -0.608996, 0.732949],
+    [0.0, -0.435513, 0.900182],
+    [-0.0, -0.683863, 0.729611],
+    [0.0, -0.720022, 0.693951],
+    [-0.0, -0.607984, 0.79395],
+    [-0.344179, -0.43618, 0.831437],
+    [-0.278594, -0.684665, 0.673512],
+    [-0.264874, -0.720924, 0.640399],
+    [-0.303166, -0.608996, 0.732949],
+    [-0.636092, -0.436777, 0.636092],
+    [-0.514965, -0.685289, 0.514965],
+    [-0.489651, -0.721446, 0.489651],
+    [-0.560555, -0.609554, 0.560555],
+    [-0.831437, -0.43618, 0.344179],
+    [-0.673512, -0.684665, 0.278595],
+    [-0.640399, -0.720924, 0.264874],
+    [-0.732949, -0.608996, 0.303166],
+    [-0.900182, -0.435513, -0.0],
+    [-0.729611, -0.683863, -0.0],
+    [-0.693951, -0.720022, 0.0],
+    [-0.79395, -0.607983, -0.0],
+    [-0.831437, -0.43618, -0.344179],
+    [-0.673512, -0.684665, -0.278594],
+    [-0.640399, -0.720924, -0.264874],
+    [-0.732949, -0.608996, -0.303166],
+    [-0.636092, -0.436777, -0.636092],
+    [-0.514965, -0.685289, -0.514965],
+    [-0.489651, -0.721446, -0.489651],
+    [-0.560555, -0.609554, -0.560555],
+    [-0.344179, -0.43618, -0.831437],
+    [-0.278594, -0.684665, -0.673512],
+    [-0.264874, -0.720924, -0.640399],
+    [-0.303166, -0.608996, -0.732949],
+    [-0.0, -0.435513, -0.900182],
+    [0.0, -0.683863, -0.729611],
+    [-0.0, -0.720022, -0.693951],
+    [0.0, -0.607984, -0.79395],
+    [0.344179, -0.43618, -0.831437],
+    [0.278594, -0.684665, -0.673512],
+    [0.264874, -0.720924, -0.640399],
+    [0.303167, -0.608996, -0.732949],
+    [0.636092, -0.436777, -0.636092],
+    [0.514965, -0.685289, -0.514965],
+    [0.489651, -0.721446, -0.489651],
+    [0.560555, -0.609554, -0.560555],
+    [0.831437, -0.43618, -0.344179],
+    [0.673512, -0.684665, -0.278595],
+    [0.640399, -0.720924, -0.264874],
+    [0.732949, -0.608996, -0.303166],
+    [0.62386, -0.781536, 0.0],
+    [0.177291, -0.984159, -0.0],
+    [0.0492072, -0.998789, 0.0],
+    [0.0, -1.0, -0.0],
+    [0.576229, -0.781801, 0.238217],
+    [0.163629, -0.984208, 0.0675273],
+    [0.0454217, -0.998792, 0.0187357],
+    [0.440416, -0.782348, 0.440416],
+    [0.124903, -0.984276, 0.124903],
+    [0.0346621, -0.998798, 0.0346621],
+    [0.238217, -0.781801, 0.576229],
+    [0.0675273, -0.984208, 0.163629],
+    [0.0187357, -0.998792, 0.0454217],
+    [-0.0, -0.781536, 0.62386],
+    [0.0, -0.984159, 0.177291],
+    [-0.0, -0.998789, 0.0492072],
+    [-0.238216, -0.781801, 0.576229],
+    [-0.0675273, -0.984208, 0.163629],
+    [-0.0187357, -0.998792, 0.0454217],
+    [-0.440416, -0.782348, 0.440416],
+    [-0.124903, -0.984276, 0.124903],
+    [-0.0346621, -0.998798, 0.0346621],
+    [-0.576229, -0.781801, 0.238217],
+    [-0.163629, -0.984208, 0.0675273],
+    [-0.0454217, -0.998792, 0.0187357],
+    [-0.62386, -0.781536, -0.0],
+    [-0.177291, -0.984159, 0.0],
+    [-0.0492072, -0.998789, -0.0],
+    [-0.576229, -0.781801, -0.238217],
+    [-0.163629, -0.984208, -0.0675273],
+    [-0.0454217, -0.998792, -0.0187357],
+    [-0.440416, -0.782348, -0.440416],
+    [-0.124903, -0.984276, -0.124903],
+    [-0.0346621, -0.998798, -0.0346621],
+    [-0.238217, -0.781801, -0.576229],
+    [-0.0675273, -0.984208, -0.163629],
+    [-0.0187357, -0.998792, -0.0454217],
+    [0.0, -0.781536, -0.62386],
+    [-0.0, -0.984159, -0.177291],
+    [0.0, -0.998789, -0.0492072],
+    [0.238217, -0.781801, -0.576229],
+    [0.0675273, -0.984208, -0.163629],
+    [0.0187357, -0.998792, -0.0454217],
+    [0.440416, -0.782348, -0.440416],
+    [0.124903, -0.984276, -0.124903],
+    [0.0346621, -0.998798, -0.0346621],
+    [0.576229, -0.781801, -0.238217],
+    [0.163629, -0.984208, -0.0675273],
+    [0.0454217, -0.998792, -0.0187357],
+    [0.00778619, -0.99997, -0.000215809],
+    [0.0391385, -0.999233, -0.000988567],
+    [0.179511, -0.983746, -0.00436856],
+    [0.6123, -0.790556, -0.0104598],
+    [0.986152, -0.165707, -0.00666949],
+    [0.00703893, -0.812495, 0.582926],
+    [0.0361273, -0.837257, 0.545614],
+    [0.161845, -0.810421, 0.563048],
+    [0.482365, -0.595148, 0.642746],
+    [0.73872, -0.114593, 0.664199],
+    [-0.00190867, 0.162121, 0.986769],
+    [0.0027616, 0.0171073, 0.99985],
+    [0.0105326, 0.0733989, 0.997247],
+    [-0.0660406, 0.130069, 0.989303],
+    [-0.0944272, 0.0165946, 0.995393],
+    [-0.009203, 0.871509, 0.490293],
+    [-0.0486064, 0.840609, 0.539457],
+    [-0.223298, 0.802881, 0.552739],
+    [-0.596365, 0.559971, 0.575135],
+    [-0.803337, 0.0682361, 0.591602],
+    [-0.0105609, 0.999944, 0.000103364],
+    [-0.0587986, 0.99827, 0.000709759],
+    [-0.28071, 0.959787, 0.00326876],
+    [-0.749723, 0.661738, 0.0042684],
+    [-0.997351, 0.0727144, 0.00205923],
+    [-0.00879197, 0.871493, -0.49033],
+    [-0.0464937, 0.841178, -0.538756],
+    [-0.217909, 0.806807, -0.549161],
+    [-0.597291, 0.560026, -0.574121],
+    [-0.804, 0.0629127, -0.591291],
+    [-0.00180555, 0.161691, -0.98684],
+    [0.00203087, 0.014555, -0.999892],
+    [0.00921499, 0.0600698, -0.998152],
+    [-0.0593333, 0.113865, -0.991723],
+    [-0.0868992, 0.0122903, -0.996141],
+    [0.00641779, -0.812379, -0.583094],
+    [0.0337833, -0.837512, -0.545373],
+    [0.157112, -0.811947, -0.56219],
+    [0.484407, -0.589365, -0.646528],
+    [0.73887, -0.10132, -0.666187],
+    [0.946512, 0.32265, -0.0033571],
+    [0.82583, 0.56387, -0.00745213],
+    [0.650011, 0.759893, -0.00693681],
+    [0.532429, 0.846458, -0.00524544],
+    [0.725608, 0.259351, 0.637362],
+    [0.645945, 0.461988, 0.607719],
+    [0.531614, 0.63666, 0.558615],
+    [0.424964, 0.681717, 0.59554],
+    [-0.0495616, -0.019755, 0.998576],
+    [-0.0378162, -0.0356243, 0.99865],
+    [-0.0379139, -0.0365122, 0.998614],
+    [-0.168854, -0.297946, 0.93953],
+    [-0.742342, -0.299166, 0.599523],
+    [-0.619602, -0.529406, 0.579503],
+    [-0.483708, -0.685761, 0.543837],
+    [-0.445293, -0.794355, 0.413176],
+    [-0.926513, -0.376257, 0.00199587],
+    [-0.75392, -0.656952, 0.00431723],
+    [-0.566224, -0.824244, 0.00346105],
+    [-0.481804, -0.876277, 0.00185047],
+    [-0.744675, -0.294424, -0.598977],
+    [-0.621949, -0.528114, -0.578165],
+    [-0.481171, -0.68834, -0.542828],
+    [-0.438055, -0.797035, -0.415744],
+    [-0.0443368, -0.0170558, -0.998871],
+    [-0.0261761, -0.0281665, -0.99926],
+    [-0.0252939, -0.0283323, -0.999278],
+    [-0.157482, -0.289392, -0.944167],
+    [0.728244, 0.25241, -0.637142],
+    [0.647055, 0.459725, -0.608254],
+    [0.522994, 0.640657, -0.562171],
+    [0.409978, 0.682857, -0.604669],
+    [-0.230787, 0.972982, -0.00652338],
+    [-0.548936, 0.835863, -0.00151111],
+    [-0.875671, 0.482807, 0.00989278],
+    [-0.877554, 0.479097, 0.0190923],
+    [-0.69619, 0.717439, 0.024497],
+    [-0.152878, 0.687211, 0.71019],
+    [-0.316721, 0.63775, 0.702113],
+    [-0.601067, 0.471452, 0.64533],
+    [-0.635889, 0.44609, 0.6298],
+    [-0.435746, 0.601008, 0.670011],
+    [0.111112, -0.0850694, 0.99016],
+    [0.22331, 0.00654036, 0.974726],
+    [0.190097, 0.154964, 0.969458],
+    [0.00527077, 0.189482, 0.98187],
+    [-0.0117518, 0.246688, 0.969024],
+    [0.343906, -0.722796, 0.599412],
+    [0.572489, -0.567656, 0.591627],
+    [0.787436, -0.256459, 0.560512],
+    [0.647097, -0.306374, 0.698141],
+    [0.427528, -0.499343, 0.753576],
+    [0.410926, -0.911668, 0.00128446],
+    [0.67152, -0.740986, -0.000899122],
+    [0.922026, -0.38706, -0.00725269],
+    [0.84691, -0.531556, -0.0138542],
+    [0.535925, -0.8442, -0.0105045],
+    [0.341188, -0.722822, -0.600931],
+    [0.578664, -0.561139, -0.591838],
+    [0.784869, -0.25102, -0.566542],
+    [0.642681, -0.302257, -0.70399],
+    [0.418589, -0.500042, -0.758117],
+    [0.115806, -0.0791394, -0.990114],
+    [0.232811, 0.0125652, -0.972441],
+    [0.206662, 0.153601, -0.96628],
+    [0.0244996, 0.161443, -0.986578],
+    [0.00338193, 0.211115, -0.977455],
+    [-0.134912, 0.687491, -0.713551],
+    [-0.31954, 0.633073, -0.705062],
+    [-0.603902, 0.461442, -0.649903],
+    [-0.631816, 0.437169, -0.640072],
+    [-0.424306, 0.612706, -0.66675],
+    [-0.4258, 0.904753, 0.0108049],
+    [0.0220472, 0.999756, 0.00162273],
+    [0.999599, 0.0258705, 0.0115556],
+    [0.709585, -0.704553, 0.00967183],
+    [-0.259858, 0.791936, 0.552549],
+    [0.00953916, 0.99972, -0.0216718],
+    [0.410156, 0.332912, -0.849083],
+    [0.541523, -0.54862, -0.637],
+    [0.0463104, 0.455224, 0.889172],
+    [-0.0106883, 0.988794, 0.148901],
+    [-0.0443756, 0.682947, -0.729118],
+    [0.122825, 0.00923214, -0.992385],
+    [0.481839, -0.180439, 0.85748],
+    [0.455272, 0.736752, 0.499925],
+    [-0.220542, 0.907193, -0.358276],
+    [-0.23592, 0.657249, -0.715797],
+    [0.728092, -0.685302, -0.0155853],
+    [0.888739, 0.45811, -0.0166791],
+    [-0.260097, 0.965582, 0.000800195],
+    [-0.371612, 0.928378, -0.00441745],
+    [0.480166, -0.17836, -0.858853],
+    [0.488103, 0.716801, -0.497947],
+    [-0.222004, 0.905399, 0.361893],
+    [-0.235405, 0.66318, 0.710477],
+    [0.0587203, 0.437704, -0.8972],
+    [0.00132612, 0.986459, -0.164003],
+    [-0.0441901, 0.681677, 0.730317],
+    [0.138801, -0.0341896, 0.98973],
+    [-0.25889, 0.797206, -0.54538],
+    [0.0122703, 0.999739, 0.0192865],
+    [0.39863, 0.35489, 0.845663],
+    [0.537564, -0.5814, 0.610737],
+    [-0.0, 1.0, 0.0],
+    [0.82454, 0.565804, 0.0],
+    [0.917701, -0.397272, 0.0],
+    [0.935269, -0.353939, 0.000112842],
+    [0.780712, 0.624891, 0.0],
+    [0.762641, 0.565035, 0.314825],
+    [0.847982, -0.397998, 0.350034],
+    [0.864141, -0.355261, 0.356441],
+    [0.720991, 0.625625, 0.297933],
+    [0.583357, 0.565165, 0.583338],
+    [0.648485, -0.398726, 0.648448],
+    [0.660872, -0.355894, 0.660748],
+    [0.551862, 0.62529, 0.55178],
+    [0.314824, 0.565051, 0.762629],
+    [0.350045, -0.397976, 0.847988],
+    [0.356474, -0.3552, 0.864153],
+    [0.297983, 0.625515, 0.721067],
+    [-0.0, 0.565804, 0.82454],
+    [-0.0, -0.397272, 0.917701],
+    [-0.000112839, -0.353939, 0.935269],
+    [-0.0, 0.624891, 0.780712],
+    [-0.314825, 0.565035, 0.762641],
+    [-0.350034, -0.397998, 0.847982],
+    [-0.356441, -0.355261, 0.864141],
+    [-0.297933, 0.625625, 0.720991],
+    [-0.583338, 0.565165, 0.583357],
+    [-0.648448, -0.398726, 0.648485],
+    [-0.660748, -0.355894, 0.660872],
+    [-0.55178, 0.62529, 0.551862],
+    [-0.762629, 0.565051, 0.314824],
+    [-0.847988, -0.397976, 0.350045],
+    [-0.864153, -0.3552, 0.356474],
+    [-0.721067, 0.625515, 0.297983],
+    [-0.82454, 0.565804, -0.0],
+    [-0.917701, -0.397272, -0.0],
+    [-0.935269, -0.353939, -0.000112839],
+    [-0.780712, 0.624891, -0.0],
+    [-0.76264, 0.565035, -0.314825],
+    [-0.847982, -0.397998, -0.350034],
+    [-0.864141, -0.355261, -0.356441],
+    [-0.720991, 0.625625, -0.297933],
+    [-0.583357, 0.565165, -0.583338],
+    [-0.648485, -0.398726, -0.648448],
+    [-0.660872, -0.355894, -0.660748],
+    [-0.551862, 0.62529, -0.55178],
+    [-0.314824, 0.565051, -0.762629],
+    [-0.350045, -0.397976, -0.847988],
+    [-0.356474, -0.3552, -0.864153],
+    [-0.297983, 0.625515, -0.721067],
+    [0.0, 0.565804, -0.82454],
+    [0.0, -0.397272, -0.917701],
+    [0.000112839, -0.353939, -0.935269],
+    [0.0, 0.624891, -0.780712],
+    [0.314825, 0.565035, -0.762641],
+    [0.350034, -0.397998, -0.847982],
+    [0.356441, -0.355261, -0.864141],
+    [0.297933, 0.625625, -0.720991],
+    [0.583338, 0.565165, -0.583357],
+    [0.648448, -0.398726, -0.648485],
+    [0.660748, -0.355894, -0.660872],
+    [0.55178, 0.62529, -0.551862],
+    [0.762629, 0.565051, -0.314824],
+    [0.847988, -0.397976, -0.350045],
+    [0.864153, -0.3552, -0.356474],
+    [0.721067, 0.625515, -0.297983],
+    [0.236584, 0.971611, 0.0],
+    [0.173084, 0.984907, -0.0],
+    [0.379703, 0.925108, 0.0],
+    [0.526673, 0.850068, 0.0],
+    [0.217978, 0.971775, 0.0902162],
+    [0.15959, 0.984977, 0.0659615],
+    [0.350498, 0.925312, 0.14474],
+    [0.48559, 0.850653, 0.201474],
+    [0.166631, 0.971838, 0.166631],
+    [0.121908, 0.985026, 0.121908],
+    [0.267668, 0.925585, 0.267668],
+    [0.371315, 0.851029, 0.371315],
+    [0.0902162, 0.971775, 0.217978],
+    [0.0659615, 0.984977, 0.15959],
+    [0.14474, 0.925312, 0.350498],
+    [0.201474, 0.850653, 0.48559],
+    [-0.0, 0.971611, 0.236584],
+    [0.0, 0.984907, 0.173084],
+    [0.0, 0.925108, 0.379703],
+    [0.0, 0.850068, 0.526673],
+    [-0.0902162, 0.971775, 0.217978],
+    [-0.0659615, 0.984977, 0.15959],
+    [-0.14474, 0.925312, 0.350498],
+    [-0.201474, 0.850653, 0.48559],
+    [-0.166631, 0.971838, 0.166631],
+    [-0.121908, 0.985026, 0.121908],
+    [-0.267668, 0.925585, 0.267668],
+    [-0.371315, 0.851029, 0.371315],
+    [-0.217978, 0.971775, 0.0902162],
+    [-0.15959, 0.984977, 0.0659615],
+    [-0.350498, 0.925312, 0.14474],
+    [-0.48559, 0.850653, 0.201474],
+    [-0.236583, 0.971611, -0.0],
+    [-0.173084, 0.984907, 0.0],
+    [-0.379703, 0.925108, -0.0],
+    [-0.526673, 0.850068, 0.0],
+    [-0.217978, 0.971775, -0.0902162],
+    [-0.15959, 0.984977, -0.0659615],
+    [-0.350498, 0.925312, -0.14474],
+    [-0.48559, 0.850653, -0.201474],
+    [-0.166631, 0.971838, -0.166631],
+    [-0.121908, 0.985026, -0.121908],
+    [-0.267668, 0.925585, -0.267668],
+    [-0.371315, 0.851029, -0.371315],
+    [-0.0902162, 0.971775, -0.217978],
+    [-0.0659615, 0.984977, -0.15959],
+    [-0.14474, 0.925312, -0.350498],
+    [-0.201474, 0.850653, -0.485589],
+    [0.0, 0.971611, -0.236584],
+    [-0.0, 0.984907, -0.173084],
+    [-0.0, 0.925108, -0.379703],
+    [-0.0, 0.850068, -0.526673],
+    [0.0902162, 0.971775, -0.217978],
+    [0.0659615, 0.984977, -0.15959],
+    [0.14474, 0.925312, -0.350498],
+    [0.201474, 0.850653, -0.48559],
+    [0.166631, 0.971838, -0.166631],
+    [0.121908, 0.985026, -0.121908],
+    [0.267668, 0.925585, -0.267668],
+    [0.371315, 0.851029, -0.371315],
+    [0.217978, 0.971775, -0.0902162],
+    [0.15959, 0.984977, -0.0659615],
+    [0.350498, 0.925312, -0.14474],
+    [0.48559, 0.850653, -0.201474],
 ];
 
 pub const INDICES: [u16; 3072] = [